@@ -0,0 +1,1094 @@
+use crate::{
+    common::*,
+    executor::{BuildError, FunctionBuilder},
+};
+use std::collections::HashMap;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum AssembleErrorKind {
+    UnknownMnemonic,
+    UnknownOpType,
+    UnknownMathFn,
+    IncorrectOperand,
+    IncorrectNumber,
+    MissingOperand,
+    UnexpectedToken,
+    /// The operands of a binary op have different offsets.
+    OffsetMismatch,
+    UnknownDirective,
+    IncorrectName,
+    /// The op or directive is placed before the first `.fn`.
+    MissingFunction,
+    UndefinedLabel,
+    DuplicateLabel,
+    UndefinedFunction,
+    DuplicateFunction,
+}
+
+/// The error at the line and column of the source, both starting from 1.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct AssembleError {
+    pub line: usize,
+    pub column: usize,
+    pub kind: AssembleErrorKind,
+}
+
+#[derive(Copy, Clone, Debug)]
+struct Token<'s> {
+    text: &'s str,
+    column: usize,
+}
+
+impl Token<'_> {
+    fn error(&self, line: usize, kind: AssembleErrorKind) -> AssembleError {
+        AssembleError {
+            line,
+            column: self.column,
+            kind,
+        }
+    }
+}
+
+struct LineParser<'s> {
+    tokens: Vec<Token<'s>>,
+    pos: usize,
+    line: usize,
+    end: usize,
+}
+
+impl<'s> LineParser<'s> {
+    /// Splits the line into tokens. The `//` comment is skipped.
+    fn new(src: &'s str, line: usize) -> Self {
+        let src = match src.find("//") {
+            Some(comment) => &src[..comment],
+            None => src,
+        };
+
+        let mut tokens = Vec::new();
+        let mut start = None;
+
+        for (i, c) in src.char_indices() {
+            match (c.is_whitespace(), start) {
+                (false, None) => start = Some(i),
+                (true, Some(s)) => {
+                    tokens.push(Self::token(src, s, i));
+                    start = None;
+                }
+                _ => (),
+            }
+        }
+
+        if let Some(s) = start {
+            tokens.push(Self::token(src, s, src.len()));
+        }
+
+        Self {
+            tokens,
+            pos: 0,
+            line,
+            end: src.chars().count() + 1,
+        }
+    }
+
+    fn token(src: &'s str, start: usize, end: usize) -> Token<'s> {
+        Token {
+            text: &src[start..end],
+            column: src[..start].chars().count() + 1,
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.tokens.len() == self.pos
+    }
+
+    fn peek(&self, n: usize) -> Option<Token<'s>> {
+        self.tokens.get(self.pos + n).copied()
+    }
+
+    fn next(&mut self) -> Result<Token<'s>, AssembleError> {
+        let token = self.tokens.get(self.pos).copied().ok_or(AssembleError {
+            line: self.line,
+            column: self.end,
+            kind: AssembleErrorKind::MissingOperand,
+        })?;
+
+        self.pos += 1;
+        Ok(token)
+    }
+
+    fn finish(&self) -> Result<(), AssembleError> {
+        match self.tokens.get(self.pos) {
+            Some(token) => Err(token.error(self.line, AssembleErrorKind::UnexpectedToken)),
+            None => Ok(()),
+        }
+    }
+
+    fn number(&mut self) -> Result<UWord, AssembleError> {
+        let token = self.next()?;
+        parse_number(token.text).map_err(|kind| token.error(self.line, kind))
+    }
+
+    fn name(&mut self) -> Result<Token<'s>, AssembleError> {
+        let token = self.next()?;
+
+        if is_name(token.text) {
+            Ok(token)
+        } else {
+            Err(token.error(self.line, AssembleErrorKind::IncorrectName))
+        }
+    }
+
+    fn ty(&mut self) -> Result<OpType, AssembleError> {
+        use OpType::*;
+
+        let token = self.next()?;
+
+        Ok(match token.text {
+            "u8" => U8,
+            "i8" => I8,
+            "u16" => U16,
+            "i16" => I16,
+            "u32" => U32,
+            "i32" => I32,
+            "u64" => U64,
+            "i64" => I64,
+            "uw" => Uw,
+            "iw" => Iw,
+            "f32" => F32,
+            "f64" => F64,
+            "u128" => U128,
+            "i128" => I128,
+            "f16" => F16,
+            _ => return Err(token.error(self.line, AssembleErrorKind::UnknownOpType)),
+        })
+    }
+
+    fn math_fn(&mut self) -> Result<MathFn, AssembleError> {
+        use MathFn::*;
+
+        let token = self.next()?;
+
+        Ok(match token.text {
+            "sin" => Sin,
+            "cos" => Cos,
+            "ln" => Ln,
+            "exp" => Exp,
+            _ => return Err(token.error(self.line, AssembleErrorKind::UnknownMathFn)),
+        })
+    }
+
+    /// Parses the operand with an optional offset like `loc(1){val(2)}`.
+    fn operand_with_offset(&mut self) -> Result<(Operand, Option<Operand>), AssembleError> {
+        let token = self.next()?;
+        let line = self.line;
+
+        let (x, offset) = match token.text.find('{') {
+            Some(i) => {
+                let offset = token.text[i + 1..]
+                    .strip_suffix('}')
+                    .ok_or_else(|| token.error(line, AssembleErrorKind::IncorrectOperand))?;
+
+                (&token.text[..i], Some(offset))
+            }
+            None => (token.text, None),
+        };
+
+        let x = parse_operand(x).map_err(|kind| token.error(line, kind))?;
+        let offset = match offset {
+            Some(offset) => Some(parse_operand(offset).map_err(|kind| token.error(line, kind))?),
+            None => None,
+        };
+
+        Ok((x, offset))
+    }
+
+    fn operand(&mut self) -> Result<Operand, AssembleError> {
+        let column = self.tokens.get(self.pos).map(|token| token.column);
+
+        match self.operand_with_offset()? {
+            (x, None) => Ok(x),
+            (_, Some(_)) => Err(AssembleError {
+                line: self.line,
+                column: column.unwrap_or(self.end),
+                kind: AssembleErrorKind::IncorrectOperand,
+            }),
+        }
+    }
+
+    fn un(&mut self) -> Result<UnOp, AssembleError> {
+        let (x, offset) = self.operand_with_offset()?;
+        let un = UnOp::new(x);
+
+        Ok(match offset {
+            Some(offset) => un.with_first(offset),
+            None => un,
+        })
+    }
+
+    fn bin(&mut self) -> Result<BinOp, AssembleError> {
+        let (x, first) = self.operand_with_offset()?;
+        let (y, second) = self.operand_with_offset()?;
+        let bin = BinOp::new(x, y);
+
+        Ok(match (first, second) {
+            (None, None) => bin,
+            (Some(offset), None) => bin.with_first(offset),
+            (None, Some(offset)) => bin.with_second(offset),
+            (Some(a), Some(b)) if a == b => bin.with_both(a),
+            (Some(_), Some(_)) => {
+                let token = self.tokens[self.pos - 1];
+                return Err(token.error(self.line, AssembleErrorKind::OffsetMismatch));
+            }
+        })
+    }
+}
+
+fn is_name(s: &str) -> bool {
+    let mut chars = s.chars();
+
+    match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() || c == '_' => (),
+        _ => return false,
+    }
+
+    chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn parse_operand(s: &str) -> Result<Operand, AssembleErrorKind> {
+    use Operand::*;
+
+    if s == "emp" {
+        return Ok(Emp);
+    }
+
+    let (kind, val) = s
+        .strip_suffix(')')
+        .and_then(|s| s.split_once('('))
+        .ok_or(AssembleErrorKind::IncorrectOperand)?;
+
+    let val = parse_number(val)?;
+
+    Ok(match kind {
+        "loc" => Loc(val),
+        "ind" => Ind(val),
+        "ret" => Ret(val),
+        "val" => Val(val),
+        "ref" => Ref(val),
+        "glb" => Glb(val),
+        "gin" => GlbInd(val),
+        _ => return Err(AssembleErrorKind::IncorrectOperand),
+    })
+}
+
+/// Parses a decimal, `0x`, `0o` or `0b` number. A negative number is
+/// stored in two's complement.
+fn parse_number(s: &str) -> Result<UWord, AssembleErrorKind> {
+    let (negative, s) = match s.strip_prefix('-') {
+        Some(s) => (true, s),
+        None => (false, s),
+    };
+
+    let (s, radix) = if let Some(s) = s.strip_prefix("0x") {
+        (s, 16)
+    } else if let Some(s) = s.strip_prefix("0o") {
+        (s, 8)
+    } else if let Some(s) = s.strip_prefix("0b") {
+        (s, 2)
+    } else {
+        (s, 10)
+    };
+
+    let digits: String = s.chars().filter(|&c| c != '_').collect();
+    if digits.is_empty() || !digits.chars().all(|c| c.is_digit(radix)) {
+        return Err(AssembleErrorKind::IncorrectNumber);
+    }
+
+    let val =
+        UWord::from_str_radix(&digits, radix).map_err(|_| AssembleErrorKind::IncorrectNumber)?;
+
+    Ok(if negative { val.wrapping_neg() } else { val })
+}
+
+fn parse_op(p: &mut LineParser) -> Result<Op, AssembleError> {
+    use Op::*;
+
+    let mnemonic = p.next()?;
+
+    let op = match mnemonic.text {
+        "nop" => Nop,
+        "end" => End(p.operand()?),
+        "slp" => Slp(p.operand()?),
+        "set" => {
+            let (t, b) = (p.ty()?, p.bin()?);
+            Set(b, t)
+        }
+        "cnv" => {
+            let (t, v, x, y) = (p.ty()?, p.ty()?, p.operand()?, p.operand()?);
+            Cnv(x, y, t, v)
+        }
+        "add" => {
+            let (t, b) = (p.ty()?, p.bin()?);
+            Add(b, t)
+        }
+        "sub" => {
+            let (t, b) = (p.ty()?, p.bin()?);
+            Sub(b, t)
+        }
+        "mul" => {
+            let (t, b) = (p.ty()?, p.bin()?);
+            Mul(b, t)
+        }
+        "div" => {
+            let (t, b) = (p.ty()?, p.bin()?);
+            Div(b, t)
+        }
+        "mod" => {
+            let (t, b) = (p.ty()?, p.bin()?);
+            Mod(b, t)
+        }
+        "shl" => {
+            let (t, b) = (p.ty()?, p.bin()?);
+            Shl(b, t)
+        }
+        "shr" => {
+            let (t, b) = (p.ty()?, p.bin()?);
+            Shr(b, t)
+        }
+        "and" => {
+            let (t, b) = (p.ty()?, p.bin()?);
+            And(b, t)
+        }
+        "or" => {
+            let (t, b) = (p.ty()?, p.bin()?);
+            Or(b, t)
+        }
+        "xor" => {
+            let (t, b) = (p.ty()?, p.bin()?);
+            Xor(b, t)
+        }
+        "not" => {
+            let (t, u) = (p.ty()?, p.un()?);
+            Not(u, t)
+        }
+        "neg" => {
+            let (t, u) = (p.ty()?, p.un()?);
+            Neg(u, t)
+        }
+        "inc" => {
+            let (t, u) = (p.ty()?, p.un()?);
+            Inc(u, t)
+        }
+        "dec" => {
+            let (t, u) = (p.ty()?, p.un()?);
+            Dec(u, t)
+        }
+        "go" => Go(p.operand()?),
+        "ift" => {
+            let (t, u) = (p.ty()?, p.un()?);
+            Ift(u, t)
+        }
+        "iff" => {
+            let (t, u) = (p.ty()?, p.un()?);
+            Iff(u, t)
+        }
+        "ife" => {
+            let (t, b) = (p.ty()?, p.bin()?);
+            Ife(b, t)
+        }
+        "ifl" => {
+            let (t, b) = (p.ty()?, p.bin()?);
+            Ifl(b, t)
+        }
+        "ifg" => {
+            let (t, b) = (p.ty()?, p.bin()?);
+            Ifg(b, t)
+        }
+        "ine" => {
+            let (t, b) = (p.ty()?, p.bin()?);
+            Ine(b, t)
+        }
+        "inl" => {
+            let (t, b) = (p.ty()?, p.bin()?);
+            Inl(b, t)
+        }
+        "ing" => {
+            let (t, b) = (p.ty()?, p.bin()?);
+            Ing(b, t)
+        }
+        "ifa" => {
+            let (t, b) = (p.ty()?, p.bin()?);
+            Ifa(b, t)
+        }
+        "ifo" => {
+            let (t, b) = (p.ty()?, p.bin()?);
+            Ifo(b, t)
+        }
+        "ifx" => {
+            let (t, b) = (p.ty()?, p.bin()?);
+            Ifx(b, t)
+        }
+        "ina" => {
+            let (t, b) = (p.ty()?, p.bin()?);
+            Ina(b, t)
+        }
+        "ino" => {
+            let (t, b) = (p.ty()?, p.bin()?);
+            Ino(b, t)
+        }
+        "inx" => {
+            let (t, b) = (p.ty()?, p.bin()?);
+            Inx(b, t)
+        }
+        "app" => App(p.operand()?),
+        "par" => {
+            let (t, u) = (p.ty()?, p.un()?);
+            Par(u, t)
+        }
+        "clf" => Clf(p.operand()?),
+        "ret" => {
+            let (t, u) = (p.ty()?, p.un()?);
+            Ret(u, t)
+        }
+        "in" => In(p.bin()?),
+        "out" => Out(p.un()?),
+        "fls" => Fls,
+        "sfd" => Sfd(p.operand()?),
+        "gfd" => Gfd(p.operand()?),
+        "cfd" => Cfd(p.operand()?),
+        "zer" => {
+            let (x, y) = (p.operand()?, p.operand()?);
+            Zer(x, y)
+        }
+        "cmp" => {
+            let (x, y, z) = (p.operand()?, p.operand()?, p.operand()?);
+            Cmp(x, y, z)
+        }
+        "cpy" => {
+            let (x, y, z) = (p.operand()?, p.operand()?, p.operand()?);
+            Cpy(x, y, z)
+        }
+        "ads" => {
+            let (t, b) = (p.ty()?, p.bin()?);
+            Ads(b, t)
+        }
+        "sbs" => {
+            let (t, b) = (p.ty()?, p.bin()?);
+            Sbs(b, t)
+        }
+        "mls" => {
+            let (t, b) = (p.ty()?, p.bin()?);
+            Mls(b, t)
+        }
+        "sqr" => {
+            let (t, u) = (p.ty()?, p.un()?);
+            Sqr(u, t)
+        }
+        "fun" => {
+            let (t, m, u) = (p.ty()?, p.math_fn()?, p.un()?);
+            Fun(u, t, m)
+        }
+        "rol" => {
+            let (t, x, y) = (p.ty()?, p.operand()?, p.operand()?);
+            Rol(x, y, t)
+        }
+        "ror" => {
+            let (t, x, y) = (p.ty()?, p.operand()?, p.operand()?);
+            Ror(x, y, t)
+        }
+        "cnt" => {
+            let (t, u) = (p.ty()?, p.un()?);
+            Cnt(u, t)
+        }
+        "clz" => {
+            let (t, u) = (p.ty()?, p.un()?);
+            Clz(u, t)
+        }
+        "ctz" => {
+            let (t, u) = (p.ty()?, p.un()?);
+            Ctz(u, t)
+        }
+        "lda" => {
+            let (x, y) = (p.operand()?, p.operand()?);
+            Lda(x, y)
+        }
+        "sta" => {
+            let (x, y) = (p.operand()?, p.operand()?);
+            Sta(x, y)
+        }
+        "swp" => {
+            let (t, x, y) = (p.ty()?, p.operand()?, p.operand()?);
+            Swp(x, y, t)
+        }
+        "cas" => {
+            let (t, x, y, z) = (p.ty()?, p.operand()?, p.operand()?, p.operand()?);
+            Cas(x, y, z, t)
+        }
+        "min" => {
+            let (t, b) = (p.ty()?, p.bin()?);
+            Min(b, t)
+        }
+        "max" => {
+            let (t, b) = (p.ty()?, p.bin()?);
+            Max(b, t)
+        }
+        "abs" => {
+            let (t, u) = (p.ty()?, p.un()?);
+            Abs(u, t)
+        }
+        "eof" => Eof(p.operand()?),
+        "inb" => {
+            let (x, y) = (p.operand()?, p.operand()?);
+            Inb(x, y)
+        }
+        "outb" => {
+            let (x, y) = (p.operand()?, p.operand()?);
+            Outb(x, y)
+        }
+        "trp" => Trp(p.operand()?),
+        "skp" => Skp(p.operand()?),
+        "sel" => {
+            let (t, x, y, z, w) = (
+                p.ty()?,
+                p.operand()?,
+                p.operand()?,
+                p.operand()?,
+                p.operand()?,
+            );
+            Sel(x, y, z, w, t)
+        }
+        "gip" => Gip(p.operand()?),
+        "gor" => Gor(p.operand()?),
+        "tap" => Tap(p.operand()?),
+        "scn" => {
+            let (x, y, z, w) = (p.operand()?, p.operand()?, p.operand()?, p.operand()?);
+            Scn(x, y, z, w)
+        }
+        "fil" => {
+            let (x, y, z) = (p.operand()?, p.operand()?, p.operand()?);
+            Fil(x, y, z)
+        }
+        "ifn" => {
+            let (t, u) = (p.ty()?, p.un()?);
+            Ifn(u, t)
+        }
+        "cnr" => {
+            let (t, v, x, y) = (p.ty()?, p.ty()?, p.operand()?, p.operand()?);
+            Cnr(x, y, t, v)
+        }
+        "rem" => {
+            let (x, y) = (p.operand()?, p.operand()?);
+            Rem(x, y)
+        }
+        "oui" => {
+            let (t, x) = (p.ty()?, p.operand()?);
+            Oui(x, t)
+        }
+        "ouf" => {
+            let (t, x) = (p.ty()?, p.operand()?);
+            Ouf(x, t)
+        }
+        "ouc" => Ouc(p.operand()?),
+        "rnd" => {
+            let (t, x) = (p.ty()?, p.operand()?);
+            Rnd(x, t)
+        }
+        "yld" => Yld(p.operand()?),
+        "bnd" => {
+            let (x, y, z) = (p.operand()?, p.operand()?, p.operand()?);
+            Bnd(x, y, z)
+        }
+        "bit" => {
+            let (t, v, x, y) = (p.ty()?, p.ty()?, p.operand()?, p.operand()?);
+            Bit(x, y, t, v)
+        }
+        "grp" => Grp(p.operand()?),
+        "srp" => Srp(p.operand()?),
+        "ovf" => Ovf(p.operand()?),
+        "adc" => {
+            let (t, b) = (p.ty()?, p.bin()?);
+            Adc(b, t)
+        }
+        "sbb" => {
+            let (t, b) = (p.ty()?, p.bin()?);
+            Sbb(b, t)
+        }
+        _ => return Err(mnemonic.error(p.line, AssembleErrorKind::UnknownMnemonic)),
+    };
+
+    p.finish()?;
+    Ok(op)
+}
+
+/// Assembles ops from the mnemonic syntax, one op per line.
+///
+/// Operands are written like `loc(8)` and an offset follows the operand in
+/// braces like `loc(1){loc(6)}`. Empty lines and `//` comments are skipped.
+pub fn assemble(src: &str) -> Result<Vec<Op>, AssembleError> {
+    let mut ops = Vec::new();
+
+    for (i, line) in src.lines().enumerate() {
+        let mut p = LineParser::new(line, i + 1);
+
+        if !p.is_empty() {
+            ops.push(parse_op(&mut p)?);
+        }
+    }
+
+    Ok(ops)
+}
+
+struct FunctionAssembler {
+    builder: FunctionBuilder,
+    labels: HashMap<String, (usize, usize)>,
+    jumps: HashMap<String, (usize, usize)>,
+    lines: Vec<LineEntry>,
+}
+
+impl FunctionAssembler {
+    fn new() -> Self {
+        Self {
+            builder: FunctionBuilder::new(0),
+            labels: HashMap::new(),
+            jumps: HashMap::new(),
+            lines: Vec::new(),
+        }
+    }
+
+    /// Records the source location of the next pushed op.
+    fn location(&mut self, line: usize, column: usize) {
+        self.lines.push(LineEntry {
+            pc: self.lines.len() as UWord,
+            location: SourceLocation {
+                line: line as UWord,
+                column: column as UWord,
+            },
+        });
+    }
+
+    fn label(&mut self, name: &str, line: usize, column: usize) -> Result<(), AssembleError> {
+        // An operand keyword as `emp` is parsed as the `go` operand, not the label
+        if parse_operand(name).is_ok() {
+            return Err(AssembleError {
+                line,
+                column,
+                kind: AssembleErrorKind::IncorrectName,
+            });
+        }
+
+        if self
+            .labels
+            .insert(name.to_string(), (line, column))
+            .is_some()
+        {
+            return Err(AssembleError {
+                line,
+                column,
+                kind: AssembleErrorKind::DuplicateLabel,
+            });
+        }
+
+        self.builder.label(name);
+        Ok(())
+    }
+
+    fn go(&mut self, name: &str, line: usize, column: usize) {
+        self.jumps.entry(name.to_string()).or_insert((line, column));
+        self.builder.go(name);
+    }
+
+    fn build(self) -> Result<OwnedFunction, AssembleError> {
+        let function = self.builder.build().map_err(|e| {
+            let (name, kind) = match &e {
+                BuildError::UndefinedLabel(name) => (name, AssembleErrorKind::UndefinedLabel),
+                BuildError::DuplicateLabel(name) => (name, AssembleErrorKind::DuplicateLabel),
+            };
+
+            let (line, column) = self.jumps.get(name).copied().unwrap_or_default();
+            AssembleError { line, column, kind }
+        })?;
+
+        Ok(OwnedFunction {
+            lines: self.lines,
+            ..function
+        })
+    }
+}
+
+/// Assembles functions from the mnemonic syntax.
+///
+/// The `.fn name` directive starts a function and `.frame n` sets its frame size.
+/// A label is defined as `name:` before an op and `go name` jumps to it.
+/// Labels are local to the function and resolved after the whole function is read.
+/// The functions are numbered in the order of definition and `app name` or
+/// `tap name` calls the function by its name, which can be defined later.
+/// The line table of each function maps its ops to their lines and columns.
+pub fn assemble_functions(src: &str) -> Result<Vec<OwnedFunction>, AssembleError> {
+    let mut functions = Vec::new();
+    let mut current: Option<FunctionAssembler> = None;
+    let mut names: HashMap<&str, UWord> = HashMap::new();
+    // The function, the pc, the line and the name of each call by name
+    let mut calls: Vec<(usize, usize, usize, Token)> = Vec::new();
+
+    for (i, text) in src.lines().enumerate() {
+        let line = i + 1;
+        let mut p = LineParser::new(text, line);
+
+        let first = match p.peek(0) {
+            Some(token) => token,
+            None => continue,
+        };
+
+        if first.text == ".fn" {
+            p.next()?;
+            let name = p.name()?;
+            p.finish()?;
+
+            if parse_operand(name.text).is_ok() {
+                return Err(name.error(line, AssembleErrorKind::IncorrectName));
+            }
+
+            if let Some(f) = current.take() {
+                functions.push(f.build()?);
+            }
+
+            let id = functions.len() as UWord;
+            if names.insert(name.text, id).is_some() {
+                return Err(name.error(line, AssembleErrorKind::DuplicateFunction));
+            }
+
+            current = Some(FunctionAssembler::new());
+            continue;
+        }
+
+        let f = current
+            .as_mut()
+            .ok_or_else(|| first.error(line, AssembleErrorKind::MissingFunction))?;
+
+        if first.text.starts_with('.') {
+            p.next()?;
+
+            match first.text {
+                ".frame" => {
+                    let size = p.number()?;
+                    f.builder.frame_size(size);
+                }
+                _ => return Err(first.error(line, AssembleErrorKind::UnknownDirective)),
+            }
+
+            p.finish()?;
+            continue;
+        }
+
+        if let Some(name) = first.text.strip_suffix(':') {
+            if !is_name(name) {
+                return Err(first.error(line, AssembleErrorKind::IncorrectName));
+            }
+
+            p.next()?;
+            f.label(name, line, first.column)?;
+
+            if p.is_empty() {
+                continue;
+            }
+        }
+
+        if let Some(token) = p.peek(0) {
+            f.location(line, token.column);
+        }
+
+        match (p.peek(0), p.peek(1)) {
+            (Some(go), Some(label))
+                if go.text == "go" && is_name(label.text) && parse_operand(label.text).is_err() =>
+            {
+                p.next()?;
+                p.next()?;
+                p.finish()?;
+                f.go(label.text, line, label.column);
+            }
+            (Some(app), Some(name))
+                if (app.text == "app" || app.text == "tap")
+                    && is_name(name.text)
+                    && parse_operand(name.text).is_err() =>
+            {
+                p.next()?;
+                p.next()?;
+                p.finish()?;
+
+                // The location of the op is already recorded, and
+                // the function id is set when all the functions are read
+                let pc = f.lines.len() - 1;
+                calls.push((functions.len(), pc, line, name));
+                let op = if app.text == "app" {
+                    Op::App(Operand::Val(0))
+                } else {
+                    Op::Tap(Operand::Val(0))
+                };
+                f.builder.op(op);
+            }
+            _ => {
+                f.builder.op(parse_op(&mut p)?);
+            }
+        }
+    }
+
+    if let Some(f) = current {
+        functions.push(f.build()?);
+    }
+
+    for (function, pc, line, name) in calls {
+        let id = *names
+            .get(name.text)
+            .ok_or_else(|| name.error(line, AssembleErrorKind::UndefinedFunction))?;
+
+        let op = &mut functions[function].program[pc];
+        *op = match *op {
+            Op::Tap(_) => Op::Tap(Operand::Val(id)),
+            _ => Op::App(Operand::Val(id)),
+        };
+    }
+
+    Ok(functions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn assemble_ops() {
+        let src = "
+            // u8 a
+            set u8 loc(1){loc(6)} val(72)
+            add i32 loc(0) val(12) // add 12
+
+            mod u32 ret(8){val(5)} ref(16){val(5)}
+            div u32 ret(8) ref(16){val(0x5)}
+            inc i16 ind(16){ref(1)}
+            cnv u8 u16 loc(12) loc(9)
+            fun f32 ln gin(0)
+            par iw emp
+            go val(-1)
+            fls
+        ";
+
+        let expected = [
+            Op::Set(
+                BinOp::new(Operand::Loc(1), Operand::Val(72)).with_first(Operand::Loc(6)),
+                OpType::U8,
+            ),
+            Op::Add(BinOp::new(Operand::Loc(0), Operand::Val(12)), OpType::I32),
+            Op::Mod(
+                BinOp::new(Operand::Ret(8), Operand::Ref(16)).with_both(Operand::Val(5)),
+                OpType::U32,
+            ),
+            Op::Div(
+                BinOp::new(Operand::Ret(8), Operand::Ref(16)).with_second(Operand::Val(5)),
+                OpType::U32,
+            ),
+            Op::Inc(
+                UnOp::new(Operand::Ind(16)).with_first(Operand::Ref(1)),
+                OpType::I16,
+            ),
+            Op::Cnv(Operand::Loc(12), Operand::Loc(9), OpType::U8, OpType::U16),
+            Op::Fun(UnOp::new(Operand::GlbInd(0)), OpType::F32, MathFn::Ln),
+            Op::Par(UnOp::new(Operand::Emp), OpType::Iw),
+            Op::Go(Operand::Val(UWord::MAX)),
+            Op::Fls,
+        ];
+
+        assert_eq!(assemble(src), Ok(expected.to_vec()));
+    }
+
+    #[test]
+    fn assemble_display_round_trip() {
+        let ops = [
+            Op::Cas(
+                Operand::Loc(0),
+                Operand::Val(1),
+                Operand::Val(2),
+                OpType::U64,
+            ),
+            Op::Sel(
+                Operand::Loc(0),
+                Operand::Loc(4),
+                Operand::Loc(8),
+                Operand::Glb(0),
+                OpType::F64,
+            ),
+            Op::Rol(Operand::Loc(0), Operand::Val(3), OpType::U16),
+            Op::Out(UnOp::new(Operand::Loc(0)).with_first(Operand::Loc(1))),
+            Op::In(BinOp::new(Operand::Loc(0), Operand::Emp)),
+            Op::Cpy(Operand::Loc(0), Operand::Loc(1), Operand::Val(12)),
+            Op::Oui(Operand::Loc(0), OpType::I8),
+        ];
+
+        let src: String = ops.iter().map(|op| format!("{}\n", op)).collect();
+        assert_eq!(assemble(&src), Ok(ops.to_vec()));
+    }
+
+    #[test]
+    fn assemble_errors() {
+        let error = |line, column, kind| Err(AssembleError { line, column, kind });
+
+        assert_eq!(
+            assemble("nop\n  mov u8 loc(0) loc(1)"),
+            error(2, 3, AssembleErrorKind::UnknownMnemonic)
+        );
+        assert_eq!(
+            assemble("add u9 loc(0) loc(1)"),
+            error(1, 5, AssembleErrorKind::UnknownOpType)
+        );
+        assert_eq!(
+            assemble("add u8 loc(0) lok(1)"),
+            error(1, 15, AssembleErrorKind::IncorrectOperand)
+        );
+        assert_eq!(
+            assemble("add u8 loc(0) loc(1x)"),
+            error(1, 15, AssembleErrorKind::IncorrectNumber)
+        );
+        assert_eq!(
+            assemble("add u8 loc(0)"),
+            error(1, 14, AssembleErrorKind::MissingOperand)
+        );
+        assert_eq!(
+            assemble("end loc(0) loc(1)"),
+            error(1, 12, AssembleErrorKind::UnexpectedToken)
+        );
+        assert_eq!(
+            assemble("add u8 loc(0){val(1)} loc(1){val(2)}"),
+            error(1, 23, AssembleErrorKind::OffsetMismatch)
+        );
+        assert_eq!(
+            assemble("end loc(0){val(1)}"),
+            error(1, 5, AssembleErrorKind::IncorrectOperand)
+        );
+    }
+
+    #[test]
+    fn assemble_gcd() {
+        use crate::executor::{Executor, RunOutcome};
+
+        let src = "
+            .fn main
+            .frame 12
+                // u32 result
+                // u32 x
+                // u32 y
+                set u32 loc(4) val(234)
+                set u32 loc(8) val(533)
+                app gcd
+                par u32 loc(4)
+                par u32 loc(8)
+                clf val(0)
+                end val(0)
+
+            .fn gcd
+            .frame 12
+                // u32 a
+                // u32 b
+                // u32 c
+            loop:
+                set u32 loc(8) loc(0)
+                mod u32 loc(8) loc(4)
+                set u32 loc(0) loc(4)
+                set u32 loc(4) loc(8)
+                ift u32 loc(4)
+                go loop
+                ret u32 loc(0)
+        ";
+
+        let owned = assemble_functions(src).unwrap();
+        assert_eq!(owned[0].program[2], Op::App(Operand::Val(1)));
+        assert_eq!(owned[1].frame_size, 12);
+        assert_eq!(owned[1].program[5], Op::Go(Operand::Val(0)));
+        assert_eq!(
+            owned[1].source_location(5),
+            Some(SourceLocation {
+                line: 26,
+                column: 17
+            })
+        );
+
+        let functions: Vec<_> = owned.iter().map(|f| f.function()).collect();
+        let mut exe = Executor::new(&functions);
+        exe.call(0, 0).unwrap();
+
+        assert_eq!(exe.run_until_break(), Ok(RunOutcome::End(0)));
+        assert_eq!(exe.return_value::<u32>(0), Ok(13));
+    }
+
+    #[test]
+    fn assemble_label_errors() {
+        let error = |line, column, kind| Err(AssembleError { line, column, kind });
+
+        assert_eq!(
+            assemble_functions(".fn f\n  go end\n  nop\n  go end"),
+            error(2, 6, AssembleErrorKind::UndefinedLabel)
+        );
+        assert_eq!(
+            assemble_functions(".fn f\na: nop\na: nop"),
+            error(3, 1, AssembleErrorKind::DuplicateLabel)
+        );
+        assert_eq!(
+            assemble_functions(".fn f\n  go emp\nemp: nop"),
+            error(3, 1, AssembleErrorKind::IncorrectName)
+        );
+        assert_eq!(
+            assemble_functions(".fn f\na: nop\n.fn g\na: go a"),
+            Ok(vec![
+                OwnedFunction {
+                    frame_size: 0,
+                    program: vec![Op::Nop],
+                    lines: vec![LineEntry {
+                        pc: 0,
+                        location: SourceLocation { line: 2, column: 4 },
+                    }],
+                },
+                OwnedFunction {
+                    frame_size: 0,
+                    program: vec![Op::Go(Operand::Val(0))],
+                    lines: vec![LineEntry {
+                        pc: 0,
+                        location: SourceLocation { line: 4, column: 4 },
+                    }],
+                },
+            ])
+        );
+        assert_eq!(
+            assemble_functions("nop"),
+            error(1, 1, AssembleErrorKind::MissingFunction)
+        );
+        assert_eq!(
+            assemble_functions(".fn f\n.size 4"),
+            error(2, 1, AssembleErrorKind::UnknownDirective)
+        );
+        assert_eq!(
+            assemble_functions(".fn 1f"),
+            error(1, 5, AssembleErrorKind::IncorrectName)
+        );
+    }
+
+    #[test]
+    fn assemble_function_names() {
+        let error = |line, column, kind| Err(AssembleError { line, column, kind });
+
+        let owned = assemble_functions(".fn f\n  tap g\n  app f\n.fn g\n  nop").unwrap();
+        assert_eq!(
+            owned[0].program,
+            [Op::Tap(Operand::Val(1)), Op::App(Operand::Val(0))]
+        );
+
+        assert_eq!(
+            assemble_functions(".fn f\n  app g"),
+            error(2, 7, AssembleErrorKind::UndefinedFunction)
+        );
+        assert_eq!(
+            assemble_functions(".fn f\n  nop\n.fn f"),
+            error(3, 5, AssembleErrorKind::DuplicateFunction)
+        );
+        assert_eq!(
+            assemble_functions(".fn emp"),
+            error(1, 5, AssembleErrorKind::IncorrectName)
+        );
+    }
+}
@@ -4,5 +4,6 @@ pub const OP_TYPE_BITS: u8 = 0b0000_1111;
 pub const OP_TYPE_LEFT_BITS: u8 = 0b1111_0000;
 
 pub const SIZE_BITS: u8 = 0b0000_1111;
+/// All eight operand kinds are in use.
 pub const KIND_BITS: u8 = 0b0111_0000;
 pub const LONG_OPERAND_BIT: u8 = 0b1000_0000;
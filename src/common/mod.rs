@@ -2,13 +2,80 @@
 mod tests;
 
 pub mod bits;
+#[cfg(feature = "std")]
 mod expected;
 pub mod op_codes;
 mod operations;
 
+#[cfg(feature = "std")]
 pub use expected::*;
 pub use operations::*;
 
+use alloc::vec::Vec;
+
+/// Magic bytes at the start of an encoded program.
+pub const PROGRAM_MAGIC: [u8; 4] = *b"NIBC";
+
+/// Version of the encoded program layout.
+/// The version 1 has no data section and the version 2 has no line tables.
+/// Both are still decoded.
+pub const PROGRAM_VERSION: u8 = 3;
+
+/// Bytes loaded to the memory at `addr` before execution.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DataSegment {
+    pub addr: UWord,
+    pub bytes: Vec<u8>,
+    /// Marks the loaded bytes as read-only.
+    pub readonly: bool,
+}
+
+/// Position in the source the op was produced from.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct SourceLocation {
+    pub line: UWord,
+    pub column: UWord,
+}
+
+/// Row of the function line table. The location applies to the ops
+/// from `pc` up to the `pc` of the next row.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct LineEntry {
+    pub pc: UWord,
+    pub location: SourceLocation,
+}
+
+/// Returns the source location of the op at `pc` from the line table sorted
+/// by `pc`. It doesn't check that `pc` is inside the program, so the ops
+/// after the last row map to its location.
+///
+/// The borrowed `Function` of the executor has no line table, so a host
+/// running it looks up the table of the function it was made from.
+pub fn source_location(lines: &[LineEntry], pc: UWord) -> Option<SourceLocation> {
+    let n = lines.partition_point(|entry| entry.pc <= pc);
+    n.checked_sub(1).map(|i| lines[i].location)
+}
+
+/// Function which owns its program.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct OwnedFunction {
+    pub frame_size: UWord,
+    pub program: Vec<Op>,
+    /// Line table sorted by `pc`, empty if the function has no debug info.
+    pub lines: Vec<LineEntry>,
+}
+
+impl OwnedFunction {
+    /// Returns the source location of the op at `pc` from the line table.
+    pub fn source_location(&self, pc: UWord) -> Option<SourceLocation> {
+        if pc as usize >= self.program.len() {
+            return None;
+        }
+
+        source_location(&self.lines, pc)
+    }
+}
+
 #[cfg(feature = "w32")]
 pub type UWord = u32;
 #[cfg(feature = "w32")]
@@ -144,3 +144,135 @@ pub const CPY: u8 = 0x2F;
 
 /// Get interpreter value.
 pub const GIV: u8 = 0x30;
+
+/// Saturating addition.
+pub const ADS: u8 = 0x31;
+
+/// Saturating subtraction.
+pub const SBS: u8 = 0x32;
+
+/// Saturating multiplication.
+pub const MLS: u8 = 0x33;
+
+/// Square root.
+pub const SQR: u8 = 0x34;
+
+/// Math function.
+pub const FUN: u8 = 0x35;
+
+/// Rotate left.
+pub const ROL: u8 = 0x36;
+
+/// Rotate right.
+pub const ROR: u8 = 0x37;
+
+/// Count ones.
+pub const CNT: u8 = 0x38;
+
+/// Count leading zeros.
+pub const CLZ: u8 = 0x39;
+
+/// Count trailing zeros.
+pub const CTZ: u8 = 0x3A;
+
+/// Atomic load.
+pub const LDA: u8 = 0x3B;
+
+/// Atomic store.
+pub const STA: u8 = 0x3C;
+
+/// Swap.
+pub const SWP: u8 = 0x3D;
+
+/// Compare and set. The success flag replaces the compared value.
+pub const CAS: u8 = 0x3E;
+
+/// Minimum.
+pub const MIN: u8 = 0x3F;
+
+/// Maximum.
+pub const MAX: u8 = 0x40;
+
+/// Absolute value.
+pub const ABS: u8 = 0x41;
+
+/// End of file check.
+pub const EOF: u8 = 0x42;
+
+/// Read bytes.
+pub const INB: u8 = 0x43;
+
+/// Write bytes.
+pub const OUTB: u8 = 0x44;
+
+/// Trap.
+pub const TRP: u8 = 0x45;
+
+/// Skip ops.
+pub const SKP: u8 = 0x46;
+
+/// Select.
+pub const SEL: u8 = 0x47;
+
+/// Get instruction pointer.
+pub const GIP: u8 = 0x48;
+
+/// Go relative.
+pub const GOR: u8 = 0x49;
+
+/// Prepare a tail call.
+pub const TAP: u8 = 0x4A;
+
+/// Scan memory for a byte.
+pub const SCN: u8 = 0x4B;
+
+/// Fill memory with a byte.
+pub const FIL: u8 = 0x4C;
+
+/// If NaN.
+pub const IFN: u8 = 0x4D;
+
+/// Convert with rounding.
+pub const CNR: u8 = 0x4E;
+
+/// Get remaining memory.
+pub const REM: u8 = 0x4F;
+
+/// Write an integer in decimal.
+pub const OUI: u8 = 0x50;
+
+/// Write a float in decimal.
+pub const OUF: u8 = 0x51;
+
+/// Write a char in UTF-8.
+pub const OUC: u8 = 0x52;
+
+/// Random value.
+pub const RND: u8 = 0x53;
+
+/// Yield to the host.
+pub const YLD: u8 = 0x54;
+
+/// Bounds check.
+pub const BND: u8 = 0x55;
+
+/// Bit cast.
+pub const BIT: u8 = 0x56;
+
+/// Get return pointer.
+pub const GRP: u8 = 0x57;
+
+/// Set return pointer.
+pub const SRP: u8 = 0x58;
+
+/// Close file descriptor.
+pub const CFD: u8 = 0x59;
+
+/// Get overflow flag.
+pub const OVF: u8 = 0x5A;
+
+/// Add with carry.
+pub const ADC: u8 = 0x5B;
+
+/// Subtract with borrow.
+pub const SBB: u8 = 0x5C;
@@ -5,9 +5,11 @@ pub enum UndefinedOperation {
     OpType,
     Kind,
     Variant,
+    MathFn,
 }
 
-#[derive(Copy, Clone, Eq, PartialEq)]
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Operand {
     /// Local variable.
     ///
@@ -39,6 +41,11 @@ pub enum Operand {
     /// Expressed as `.x` or `glb(12)`.
     Glb(UWord),
 
+    /// Global indirection access.
+    ///
+    /// Expressed as `*.x` or `gin(12)`.
+    GlbInd(UWord),
+
     /// Empty.
     ///
     /// Expressed as `emp`.
@@ -57,6 +64,7 @@ impl Operand {
             4 => Ref(val),
             5 => Glb(val),
             6 => Emp,
+            7 => GlbInd(val),
             _ => return Err(UndefinedOperation::Kind),
         })
     }
@@ -72,6 +80,7 @@ impl Operand {
             Ref(_) => 4,
             Glb(_) => 5,
             Emp => 6,
+            GlbInd(_) => 7,
         }
     }
 
@@ -85,6 +94,7 @@ impl Operand {
             Val(v) => Some(v),
             Ref(v) => Some(v),
             Glb(v) => Some(v),
+            GlbInd(v) => Some(v),
             Emp => None,
         }
     }
@@ -102,6 +112,7 @@ impl Operand {
             Val(v) => Val(f(v)),
             Ref(v) => Ref(f(v)),
             Glb(v) => Glb(f(v)),
+            GlbInd(v) => GlbInd(f(v)),
             Emp => Emp,
         }
     }
@@ -113,8 +124,8 @@ impl From<u8> for Operand {
     }
 }
 
-impl std::fmt::Debug for Operand {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Debug for Operand {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         use Operand::*;
 
         match self {
@@ -124,17 +135,34 @@ impl std::fmt::Debug for Operand {
             Val(v) => write!(f, "val({:?})", v),
             Ref(v) => write!(f, "ref({:?})", v),
             Glb(v) => write!(f, "glb({:?})", v),
+            GlbInd(v) => write!(f, "gin({:?})", v),
             Emp => write!(f, "emp"),
         }
     }
 }
 
+impl core::fmt::Display for Operand {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum UnOp {
     None { x: Operand },
     First { x: Operand, offset: Operand },
 }
 
+impl core::fmt::Display for UnOp {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            UnOp::None { x } => write!(f, "{}", x),
+            UnOp::First { x, offset } => write!(f, "{}{{{}}}", x, offset),
+        }
+    }
+}
+
 impl UnOp {
     pub fn new(x: Operand) -> Self {
         UnOp::None { x }
@@ -164,6 +192,7 @@ impl UnOp {
 }
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum BinOp {
     None {
         x: Operand,
@@ -186,6 +215,19 @@ pub enum BinOp {
     },
 }
 
+impl core::fmt::Display for BinOp {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            BinOp::None { x, y } => write!(f, "{} {}", x, y),
+            BinOp::First { x, y, offset } => write!(f, "{}{{{}}} {}", x, offset, y),
+            BinOp::Second { x, y, offset } => write!(f, "{} {}{{{}}}", x, y, offset),
+            BinOp::Both { x, y, offset } => {
+                write!(f, "{}{{{}}} {}{{{}}}", x, offset, y, offset)
+            }
+        }
+    }
+}
+
 impl BinOp {
     pub fn new(x: Operand, y: Operand) -> Self {
         BinOp::None { x, y }
@@ -223,9 +265,19 @@ impl BinOp {
             BinOp::Both { .. } => Variant::Both,
         }
     }
+
+    pub fn x(&self) -> Operand {
+        match self {
+            BinOp::None { x, .. } => *x,
+            BinOp::First { x, .. } => *x,
+            BinOp::Second { x, .. } => *x,
+            BinOp::Both { x, .. } => *x,
+        }
+    }
 }
 
 #[derive(Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Op {
     Nop,
     End(Operand),
@@ -237,8 +289,8 @@ pub enum Op {
     Mul(BinOp, OpType),
     Div(BinOp, OpType),
     Mod(BinOp, OpType),
-    Shl(Operand, Operand, OpType),
-    Shr(Operand, Operand, OpType),
+    Shl(BinOp, OpType),
+    Shr(BinOp, OpType),
     And(BinOp, OpType),
     Or(BinOp, OpType),
     Xor(BinOp, OpType),
@@ -273,17 +325,77 @@ pub enum Op {
     Zer(Operand, Operand),
     Cmp(Operand, Operand, Operand),
     Cpy(Operand, Operand, Operand),
+    Ads(BinOp, OpType),
+    Sbs(BinOp, OpType),
+    Mls(BinOp, OpType),
+    Sqr(UnOp, OpType),
+    Fun(UnOp, OpType, MathFn),
+    Rol(Operand, Operand, OpType),
+    Ror(Operand, Operand, OpType),
+    Cnt(UnOp, OpType),
+    Clz(UnOp, OpType),
+    Ctz(UnOp, OpType),
+    Lda(Operand, Operand),
+    Sta(Operand, Operand),
+    Swp(Operand, Operand, OpType),
+    Cas(Operand, Operand, Operand, OpType),
+    Min(BinOp, OpType),
+    Max(BinOp, OpType),
+    Abs(UnOp, OpType),
+    Eof(Operand),
+    Inb(Operand, Operand),
+    Outb(Operand, Operand),
+    Trp(Operand),
+    Skp(Operand),
+    Sel(Operand, Operand, Operand, Operand, OpType),
+    Gip(Operand),
+    Gor(Operand),
+    Tap(Operand),
+    Scn(Operand, Operand, Operand, Operand),
+    Fil(Operand, Operand, Operand),
+    Ifn(UnOp, OpType),
+    Cnr(Operand, Operand, OpType, OpType),
+    Rem(Operand, Operand),
+    Oui(Operand, OpType),
+    Ouf(Operand, OpType),
+    Ouc(Operand),
+    Rnd(Operand, OpType),
+    Yld(Operand),
+    Bnd(Operand, Operand, Operand),
+    Bit(Operand, Operand, OpType, OpType),
+    Grp(Operand),
+    Srp(Operand),
+    Cfd(Operand),
+    Ovf(Operand),
+    Adc(BinOp, OpType),
+    Sbb(BinOp, OpType),
 }
 
 impl Op {
+    /// Checks if the op guards the next op.
+    /// Several conditional ops in a row must all pass to run the guarded op.
     pub fn is_conditional(&self) -> bool {
         use Op::*;
 
-        match self {
-            Ift(..) | Iff(..) | Ife(..) | Ifl(..) | Ifg(..) | Ine(..) | Inl(..) | Ing(..)
-            | Ifa(..) | Ifo(..) | Ifx(..) | Ina(..) | Ino(..) | Inx(..) | Cmp(..) => true,
-            _ => false,
-        }
+        matches!(
+            self,
+            Ift(..)
+                | Iff(..)
+                | Ife(..)
+                | Ifl(..)
+                | Ifg(..)
+                | Ine(..)
+                | Inl(..)
+                | Ing(..)
+                | Ifa(..)
+                | Ifo(..)
+                | Ifx(..)
+                | Ina(..)
+                | Ino(..)
+                | Inx(..)
+                | Cmp(..)
+                | Ifn(..)
+        )
     }
 
     pub fn op_code(&self) -> u8 {
@@ -337,66 +449,252 @@ impl Op {
             Zer(..) => ZER,
             Cmp(..) => CMP,
             Cpy(..) => CPY,
+            Ads(..) => ADS,
+            Sbs(..) => SBS,
+            Mls(..) => MLS,
+            Sqr(..) => SQR,
+            Fun(..) => FUN,
+            Rol(..) => ROL,
+            Ror(..) => ROR,
+            Cnt(..) => CNT,
+            Clz(..) => CLZ,
+            Ctz(..) => CTZ,
+            Lda(..) => LDA,
+            Sta(..) => STA,
+            Swp(..) => SWP,
+            Cas(..) => CAS,
+            Min(..) => MIN,
+            Max(..) => MAX,
+            Abs(..) => ABS,
+            Eof(..) => EOF,
+            Inb(..) => INB,
+            Outb(..) => OUTB,
+            Trp(..) => TRP,
+            Skp(..) => SKP,
+            Sel(..) => SEL,
+            Gip(..) => GIP,
+            Gor(..) => GOR,
+            Tap(..) => TAP,
+            Scn(..) => SCN,
+            Fil(..) => FIL,
+            Ifn(..) => IFN,
+            Cnr(..) => CNR,
+            Rem(..) => REM,
+            Oui(..) => OUI,
+            Ouf(..) => OUF,
+            Ouc(..) => OUC,
+            Rnd(..) => RND,
+            Yld(..) => YLD,
+            Bnd(..) => BND,
+            Bit(..) => BIT,
+            Grp(..) => GRP,
+            Srp(..) => SRP,
+            Cfd(..) => CFD,
+            Ovf(..) => OVF,
+            Adc(..) => ADC,
+            Sbb(..) => SBB,
         }
     }
 }
 
-impl std::fmt::Debug for Op {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        use Op::*;
+/// Operand of the formatted op.
+enum Arg {
+    Type(OpType),
+    Operand(Operand),
+    Un(UnOp),
+    Bin(BinOp),
+    Fn(MathFn),
+}
 
+impl core::fmt::Debug for Arg {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match self {
-            Nop => write!(f, "nop"),
-            End(x) => write!(f, "end {:?}", x),
-            Slp(x) => write!(f, "slp {:?}", x),
-            Set(b, t) => write!(f, "set {:?} {:?}", t, b),
-            Cnv(x, y, t, v) => write!(f, "cnv {:?} {:?} {:?} {:?}", t, v, x, y),
-            Add(b, t) => write!(f, "add {:?} {:?}", t, b),
-            Sub(b, t) => write!(f, "sub {:?} {:?}", t, b),
-            Mul(b, t) => write!(f, "mul {:?} {:?}", t, b),
-            Div(b, t) => write!(f, "div {:?} {:?}", t, b),
-            Mod(b, t) => write!(f, "mod {:?} {:?}", t, b),
-            Shl(x, y, t) => write!(f, "shl {:?} {:?} {:?}", t, x, y),
-            Shr(x, y, t) => write!(f, "shr {:?} {:?} {:?}", t, x, y),
-            And(b, t) => write!(f, "and {:?} {:?}", t, b),
-            Or(b, t) => write!(f, "or  {:?} {:?}", t, b),
-            Xor(b, t) => write!(f, "xor {:?} {:?}", t, b),
-            Not(u, t) => write!(f, "not {:?} {:?}", t, u),
-            Neg(u, t) => write!(f, "neg {:?} {:?}", t, u),
-            Inc(u, t) => write!(f, "inc {:?} {:?}", t, u),
-            Dec(u, t) => write!(f, "dec {:?} {:?}", t, u),
-            Go(x) => write!(f, "go  {:?}", x),
-            Ift(u, t) => write!(f, "ift {:?} {:?}", t, u),
-            Iff(u, t) => write!(f, "iff {:?} {:?}", t, u),
-            Ife(b, t) => write!(f, "ife {:?} {:?}", t, b),
-            Ifl(b, t) => write!(f, "ifl {:?} {:?}", t, b),
-            Ifg(b, t) => write!(f, "ifg {:?} {:?}", t, b),
-            Ine(b, t) => write!(f, "ine {:?} {:?}", t, b),
-            Inl(b, t) => write!(f, "inl {:?} {:?}", t, b),
-            Ing(b, t) => write!(f, "ing {:?} {:?}", t, b),
-            Ifa(b, t) => write!(f, "ifa {:?} {:?}", t, b),
-            Ifo(b, t) => write!(f, "ifo {:?} {:?}", t, b),
-            Ifx(b, t) => write!(f, "ifx {:?} {:?}", t, b),
-            Ina(b, t) => write!(f, "ina {:?} {:?}", t, b),
-            Ino(b, t) => write!(f, "ino {:?} {:?}", t, b),
-            Inx(b, t) => write!(f, "inx {:?} {:?}", t, b),
-            App(x) => write!(f, "app {:?}", x),
-            Par(u, t) => write!(f, "par {:?} {:?}", t, u),
-            Clf(x) => write!(f, "clf {:?}", x),
-            Ret(u, t) => write!(f, "ret {:?} {:?}", t, u),
-            In(b) => write!(f, "in  {:?}", b),
-            Out(u) => write!(f, "out {:?}", u),
-            Fls => write!(f, "fls"),
-            Sfd(x) => write!(f, "sfd {:?}", x),
-            Gfd(x) => write!(f, "gfd {:?}", x),
-            Zer(x, y) => write!(f, "zer {:?} {:?}", x, y),
-            Cmp(x, y, z) => write!(f, "cmp {:?} {:?} {:?}", x, y, z),
-            Cpy(x, y, z) => write!(f, "cpy {:?} {:?} {:?}", x, y, z),
+            Arg::Type(t) => write!(f, "{:?}", t),
+            Arg::Operand(x) => write!(f, "{:?}", x),
+            Arg::Un(u) => write!(f, "{:?}", u),
+            Arg::Bin(b) => write!(f, "{:?}", b),
+            Arg::Fn(m) => write!(f, "{:?}", m),
         }
     }
 }
 
-#[derive(Copy, Clone, Eq, PartialEq)]
+impl core::fmt::Display for Arg {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Arg::Type(t) => write!(f, "{}", t),
+            Arg::Operand(x) => write!(f, "{}", x),
+            Arg::Un(u) => write!(f, "{}", u),
+            Arg::Bin(b) => write!(f, "{}", b),
+            Arg::Fn(m) => write!(f, "{}", m),
+        }
+    }
+}
+
+impl Op {
+    /// Writes the mnemonic and the operands. The `Debug` form aligns the mnemonics
+    /// and the types, and shows the operand variants.
+    fn fmt_with(&self, f: &mut core::fmt::Formatter<'_>, debug: bool) -> core::fmt::Result {
+        use Op::*;
+
+        let (name, args): (&str, &[Arg]) = match *self {
+            Nop => ("nop", &[]),
+            End(x) => ("end", &[Arg::Operand(x)]),
+            Slp(x) => ("slp", &[Arg::Operand(x)]),
+            Set(b, t) => ("set", &[Arg::Type(t), Arg::Bin(b)]),
+            Cnv(x, y, t, v) => (
+                "cnv",
+                &[Arg::Type(t), Arg::Type(v), Arg::Operand(x), Arg::Operand(y)],
+            ),
+            Add(b, t) => ("add", &[Arg::Type(t), Arg::Bin(b)]),
+            Sub(b, t) => ("sub", &[Arg::Type(t), Arg::Bin(b)]),
+            Mul(b, t) => ("mul", &[Arg::Type(t), Arg::Bin(b)]),
+            Div(b, t) => ("div", &[Arg::Type(t), Arg::Bin(b)]),
+            Mod(b, t) => ("mod", &[Arg::Type(t), Arg::Bin(b)]),
+            Shl(b, t) => ("shl", &[Arg::Type(t), Arg::Bin(b)]),
+            Shr(b, t) => ("shr", &[Arg::Type(t), Arg::Bin(b)]),
+            And(b, t) => ("and", &[Arg::Type(t), Arg::Bin(b)]),
+            Or(b, t) => ("or", &[Arg::Type(t), Arg::Bin(b)]),
+            Xor(b, t) => ("xor", &[Arg::Type(t), Arg::Bin(b)]),
+            Not(u, t) => ("not", &[Arg::Type(t), Arg::Un(u)]),
+            Neg(u, t) => ("neg", &[Arg::Type(t), Arg::Un(u)]),
+            Inc(u, t) => ("inc", &[Arg::Type(t), Arg::Un(u)]),
+            Dec(u, t) => ("dec", &[Arg::Type(t), Arg::Un(u)]),
+            Go(x) => ("go", &[Arg::Operand(x)]),
+            Ift(u, t) => ("ift", &[Arg::Type(t), Arg::Un(u)]),
+            Iff(u, t) => ("iff", &[Arg::Type(t), Arg::Un(u)]),
+            Ife(b, t) => ("ife", &[Arg::Type(t), Arg::Bin(b)]),
+            Ifl(b, t) => ("ifl", &[Arg::Type(t), Arg::Bin(b)]),
+            Ifg(b, t) => ("ifg", &[Arg::Type(t), Arg::Bin(b)]),
+            Ine(b, t) => ("ine", &[Arg::Type(t), Arg::Bin(b)]),
+            Inl(b, t) => ("inl", &[Arg::Type(t), Arg::Bin(b)]),
+            Ing(b, t) => ("ing", &[Arg::Type(t), Arg::Bin(b)]),
+            Ifa(b, t) => ("ifa", &[Arg::Type(t), Arg::Bin(b)]),
+            Ifo(b, t) => ("ifo", &[Arg::Type(t), Arg::Bin(b)]),
+            Ifx(b, t) => ("ifx", &[Arg::Type(t), Arg::Bin(b)]),
+            Ina(b, t) => ("ina", &[Arg::Type(t), Arg::Bin(b)]),
+            Ino(b, t) => ("ino", &[Arg::Type(t), Arg::Bin(b)]),
+            Inx(b, t) => ("inx", &[Arg::Type(t), Arg::Bin(b)]),
+            App(x) => ("app", &[Arg::Operand(x)]),
+            Par(u, t) => ("par", &[Arg::Type(t), Arg::Un(u)]),
+            Clf(x) => ("clf", &[Arg::Operand(x)]),
+            Ret(u, t) => ("ret", &[Arg::Type(t), Arg::Un(u)]),
+            In(b) => ("in", &[Arg::Bin(b)]),
+            Out(u) => ("out", &[Arg::Un(u)]),
+            Fls => ("fls", &[]),
+            Sfd(x) => ("sfd", &[Arg::Operand(x)]),
+            Gfd(x) => ("gfd", &[Arg::Operand(x)]),
+            Zer(x, y) => ("zer", &[Arg::Operand(x), Arg::Operand(y)]),
+            Cmp(x, y, z) => ("cmp", &[Arg::Operand(x), Arg::Operand(y), Arg::Operand(z)]),
+            Cpy(x, y, z) => ("cpy", &[Arg::Operand(x), Arg::Operand(y), Arg::Operand(z)]),
+            Ads(b, t) => ("ads", &[Arg::Type(t), Arg::Bin(b)]),
+            Sbs(b, t) => ("sbs", &[Arg::Type(t), Arg::Bin(b)]),
+            Mls(b, t) => ("mls", &[Arg::Type(t), Arg::Bin(b)]),
+            Sqr(u, t) => ("sqr", &[Arg::Type(t), Arg::Un(u)]),
+            Fun(u, t, m) => ("fun", &[Arg::Type(t), Arg::Fn(m), Arg::Un(u)]),
+            Rol(x, y, t) => ("rol", &[Arg::Type(t), Arg::Operand(x), Arg::Operand(y)]),
+            Ror(x, y, t) => ("ror", &[Arg::Type(t), Arg::Operand(x), Arg::Operand(y)]),
+            Cnt(u, t) => ("cnt", &[Arg::Type(t), Arg::Un(u)]),
+            Clz(u, t) => ("clz", &[Arg::Type(t), Arg::Un(u)]),
+            Ctz(u, t) => ("ctz", &[Arg::Type(t), Arg::Un(u)]),
+            Lda(x, y) => ("lda", &[Arg::Operand(x), Arg::Operand(y)]),
+            Sta(x, y) => ("sta", &[Arg::Operand(x), Arg::Operand(y)]),
+            Swp(x, y, t) => ("swp", &[Arg::Type(t), Arg::Operand(x), Arg::Operand(y)]),
+            Cas(x, y, z, t) => (
+                "cas",
+                &[
+                    Arg::Type(t),
+                    Arg::Operand(x),
+                    Arg::Operand(y),
+                    Arg::Operand(z),
+                ],
+            ),
+            Min(b, t) => ("min", &[Arg::Type(t), Arg::Bin(b)]),
+            Max(b, t) => ("max", &[Arg::Type(t), Arg::Bin(b)]),
+            Abs(u, t) => ("abs", &[Arg::Type(t), Arg::Un(u)]),
+            Eof(x) => ("eof", &[Arg::Operand(x)]),
+            Inb(x, y) => ("inb", &[Arg::Operand(x), Arg::Operand(y)]),
+            Outb(x, y) => ("outb", &[Arg::Operand(x), Arg::Operand(y)]),
+            Trp(x) => ("trp", &[Arg::Operand(x)]),
+            Skp(x) => ("skp", &[Arg::Operand(x)]),
+            Sel(x, y, z, w, t) => (
+                "sel",
+                &[
+                    Arg::Type(t),
+                    Arg::Operand(x),
+                    Arg::Operand(y),
+                    Arg::Operand(z),
+                    Arg::Operand(w),
+                ],
+            ),
+            Gip(x) => ("gip", &[Arg::Operand(x)]),
+            Gor(x) => ("gor", &[Arg::Operand(x)]),
+            Tap(x) => ("tap", &[Arg::Operand(x)]),
+            Scn(x, y, z, w) => (
+                "scn",
+                &[
+                    Arg::Operand(x),
+                    Arg::Operand(y),
+                    Arg::Operand(z),
+                    Arg::Operand(w),
+                ],
+            ),
+            Fil(x, y, z) => ("fil", &[Arg::Operand(x), Arg::Operand(y), Arg::Operand(z)]),
+            Ifn(u, t) => ("ifn", &[Arg::Type(t), Arg::Un(u)]),
+            Cnr(x, y, t, v) => (
+                "cnr",
+                &[Arg::Type(t), Arg::Type(v), Arg::Operand(x), Arg::Operand(y)],
+            ),
+            Rem(x, y) => ("rem", &[Arg::Operand(x), Arg::Operand(y)]),
+            Oui(x, t) => ("oui", &[Arg::Type(t), Arg::Operand(x)]),
+            Ouf(x, t) => ("ouf", &[Arg::Type(t), Arg::Operand(x)]),
+            Ouc(x) => ("ouc", &[Arg::Operand(x)]),
+            Rnd(x, t) => ("rnd", &[Arg::Type(t), Arg::Operand(x)]),
+            Yld(x) => ("yld", &[Arg::Operand(x)]),
+            Bnd(x, y, z) => ("bnd", &[Arg::Operand(x), Arg::Operand(y), Arg::Operand(z)]),
+            Bit(x, y, t, v) => (
+                "bit",
+                &[Arg::Type(t), Arg::Type(v), Arg::Operand(x), Arg::Operand(y)],
+            ),
+            Grp(x) => ("grp", &[Arg::Operand(x)]),
+            Srp(x) => ("srp", &[Arg::Operand(x)]),
+            Cfd(x) => ("cfd", &[Arg::Operand(x)]),
+            Ovf(x) => ("ovf", &[Arg::Operand(x)]),
+            Adc(b, t) => ("adc", &[Arg::Type(t), Arg::Bin(b)]),
+            Sbb(b, t) => ("sbb", &[Arg::Type(t), Arg::Bin(b)]),
+        };
+
+        if debug {
+            write!(f, "{:<3}", name)?;
+        } else {
+            f.write_str(name)?;
+        }
+
+        for arg in args {
+            if debug {
+                write!(f, " {:?}", arg)?;
+            } else {
+                write!(f, " {}", arg)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl core::fmt::Debug for Op {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        self.fmt_with(f, true)
+    }
+}
+
+impl core::fmt::Display for Op {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        self.fmt_with(f, false)
+    }
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum OpType {
     U8,
     I8,
@@ -410,6 +708,9 @@ pub enum OpType {
     Iw,
     F32,
     F64,
+    U128,
+    I128,
+    F16,
 }
 
 impl OpType {
@@ -429,6 +730,9 @@ impl OpType {
             9 => Iw,
             11 => F32,
             13 => F64,
+            14 => U128,
+            15 => I128,
+            10 => F16,
             _ => return Err(UndefinedOperation::OpType),
         })
     }
@@ -449,53 +753,93 @@ impl OpType {
             Iw => 9,
             F32 => 11,
             F64 => 13,
+            U128 => 14,
+            I128 => 15,
+            F16 => 10,
         }
     }
 
+    /// Size of the type in bytes.
     pub fn size(&self) -> UWord {
         use OpType::*;
 
         match self {
-            U8 => std::mem::size_of::<u8>() as UWord,
-            I8 => std::mem::size_of::<i8>() as UWord,
-            U16 => std::mem::size_of::<u16>() as UWord,
-            I16 => std::mem::size_of::<i16>() as UWord,
-            U32 => std::mem::size_of::<u32>() as UWord,
-            I32 => std::mem::size_of::<i32>() as UWord,
-            U64 => std::mem::size_of::<u64>() as UWord,
-            I64 => std::mem::size_of::<i64>() as UWord,
-            Uw => std::mem::size_of::<UWord>() as UWord,
-            Iw => std::mem::size_of::<IWord>() as UWord,
-            F32 => std::mem::size_of::<f32>() as UWord,
-            F64 => std::mem::size_of::<f64>() as UWord,
+            U8 => core::mem::size_of::<u8>() as UWord,
+            I8 => core::mem::size_of::<i8>() as UWord,
+            U16 => core::mem::size_of::<u16>() as UWord,
+            I16 => core::mem::size_of::<i16>() as UWord,
+            U32 => core::mem::size_of::<u32>() as UWord,
+            I32 => core::mem::size_of::<i32>() as UWord,
+            U64 => core::mem::size_of::<u64>() as UWord,
+            I64 => core::mem::size_of::<i64>() as UWord,
+            Uw => core::mem::size_of::<UWord>() as UWord,
+            Iw => core::mem::size_of::<IWord>() as UWord,
+            F32 => core::mem::size_of::<f32>() as UWord,
+            F64 => core::mem::size_of::<f64>() as UWord,
+            U128 => core::mem::size_of::<u128>() as UWord,
+            I128 => core::mem::size_of::<i128>() as UWord,
+            F16 => 2,
         }
     }
-}
 
-impl std::fmt::Debug for OpType {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    /// Checks if the type is `f16`, `f32` or `f64`.
+    pub fn is_float(&self) -> bool {
+        matches!(self, OpType::F16 | OpType::F32 | OpType::F64)
+    }
+
+    /// Checks if the type is a signed integer or a float.
+    pub fn is_signed(&self) -> bool {
+        use OpType::*;
+
+        matches!(self, I8 | I16 | I32 | I64 | Iw | F32 | F64 | I128 | F16)
+    }
+
+    /// Checks if the type is an integer, signed or unsigned.
+    /// The bitwise ops are only defined for integer types.
+    pub fn is_integer(&self) -> bool {
+        !self.is_float()
+    }
+
+    fn name(&self) -> &'static str {
         use OpType::*;
 
         match self {
-            U8 => write!(f, "u8 "),
-            I8 => write!(f, "i8 "),
-            U16 => write!(f, "u16"),
-            I16 => write!(f, "i16"),
-            U32 => write!(f, "u32"),
-            I32 => write!(f, "i32"),
-            U64 => write!(f, "u64"),
-            I64 => write!(f, "i64"),
-            Uw => write!(f, "uw "),
-            Iw => write!(f, "iw "),
-            F32 => write!(f, "f32"),
-            F64 => write!(f, "f64"),
+            U8 => "u8",
+            I8 => "i8",
+            U16 => "u16",
+            I16 => "i16",
+            U32 => "u32",
+            I32 => "i32",
+            U64 => "u64",
+            I64 => "i64",
+            Uw => "uw",
+            Iw => "iw",
+            F32 => "f32",
+            F64 => "f64",
+            U128 => "u128",
+            I128 => "i128",
+            F16 => "f16",
         }
     }
 }
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+impl core::fmt::Debug for OpType {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{:<3}", self.name())
+    }
+}
+
+impl core::fmt::Display for OpType {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Variant {
     /// `x y` variant.
+    #[default]
     None,
 
     /// `x{q} y` variant.
@@ -533,8 +877,59 @@ impl Variant {
     }
 }
 
-impl Default for Variant {
-    fn default() -> Self {
-        Variant::None
+#[derive(Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MathFn {
+    Sin,
+    Cos,
+    Ln,
+    Exp,
+}
+
+impl MathFn {
+    pub fn new(value: u8) -> Result<Self, UndefinedOperation> {
+        use MathFn::*;
+
+        Ok(match value {
+            0 => Sin,
+            1 => Cos,
+            2 => Ln,
+            3 => Exp,
+            _ => return Err(UndefinedOperation::MathFn),
+        })
+    }
+
+    pub fn as_byte(&self) -> u8 {
+        use MathFn::*;
+
+        match self {
+            Sin => 0,
+            Cos => 1,
+            Ln => 2,
+            Exp => 3,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        use MathFn::*;
+
+        match self {
+            Sin => "sin",
+            Cos => "cos",
+            Ln => "ln",
+            Exp => "exp",
+        }
+    }
+}
+
+impl core::fmt::Debug for MathFn {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{:<3}", self.name())
+    }
+}
+
+impl core::fmt::Display for MathFn {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(self.name())
     }
 }
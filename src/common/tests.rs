@@ -3,11 +3,161 @@ use super::*;
 #[test]
 #[cfg(feature = "w32")]
 fn op_size_of() {
-    assert_eq!(std::mem::size_of::<Op>(), 32)
+    assert_eq!(std::mem::size_of::<Op>(), 36)
 }
 
 #[test]
 #[cfg(feature = "w64")]
 fn op_size_of() {
-    assert_eq!(std::mem::size_of::<Op>(), 64)
+    assert_eq!(std::mem::size_of::<Op>(), 72)
+}
+
+#[test]
+fn op_display() {
+    let op = Op::Set(BinOp::new(Operand::Loc(8), Operand::Loc(16)), OpType::I16);
+    assert_eq!(op.to_string(), "set i16 loc(8) loc(16)");
+
+    let op = Op::Set(
+        BinOp::new(Operand::Loc(1), Operand::Val(72)).with_first(Operand::Loc(6)),
+        OpType::U8,
+    );
+    assert_eq!(op.to_string(), "set u8 loc(1){loc(6)} val(72)");
+
+    let op = Op::Mod(
+        BinOp::new(Operand::Ret(8), Operand::Ref(16)).with_both(Operand::Val(5)),
+        OpType::U32,
+    );
+    assert_eq!(op.to_string(), "mod u32 ret(8){val(5)} ref(16){val(5)}");
+
+    let op = Op::Div(
+        BinOp::new(Operand::Ret(8), Operand::Ref(16)).with_second(Operand::Val(5)),
+        OpType::Uw,
+    );
+    assert_eq!(op.to_string(), "div uw ret(8) ref(16){val(5)}");
+
+    let op = Op::Inc(
+        UnOp::new(Operand::Loc(12)).with_first(Operand::Ind(0)),
+        OpType::U16,
+    );
+    assert_eq!(op.to_string(), "inc u16 loc(12){ind(0)}");
+
+    let op = Op::Cnv(Operand::Loc(12), Operand::Loc(9), OpType::U8, OpType::U16);
+    assert_eq!(op.to_string(), "cnv u8 u16 loc(12) loc(9)");
+
+    let op = Op::Fun(UnOp::new(Operand::Loc(0)), OpType::F32, MathFn::Ln);
+    assert_eq!(op.to_string(), "fun f32 ln loc(0)");
+
+    assert_eq!(Op::Go(Operand::Val(7)).to_string(), "go val(7)");
+    assert_eq!(
+        Op::Par(UnOp::new(Operand::Emp), OpType::I8).to_string(),
+        "par i8 emp"
+    );
+    assert_eq!(Op::Nop.to_string(), "nop");
+}
+
+#[test]
+fn op_type_predicates() {
+    use OpType::*;
+
+    let types = [
+        U8, I8, U16, I16, U32, I32, U64, I64, Uw, Iw, F32, F64, U128, I128, F16,
+    ];
+    let sizes = [1, 1, 2, 2, 4, 4, 8, 8, 0, 0, 4, 8, 16, 16, 2];
+
+    for (ty, size) in types.iter().zip(sizes.iter()) {
+        let size = if *size == 0 {
+            std::mem::size_of::<UWord>() as UWord
+        } else {
+            *size
+        };
+
+        assert_eq!(ty.size(), size);
+        assert_eq!(ty.is_float(), matches!(ty, F16 | F32 | F64));
+        assert_eq!(ty.is_integer(), !ty.is_float());
+    }
+
+    let signed: Vec<_> = types.iter().filter(|ty| ty.is_signed()).collect();
+    assert_eq!(
+        signed,
+        [&I8, &I16, &I32, &I64, &Iw, &F32, &F64, &I128, &F16]
+    );
+}
+
+#[test]
+fn operand_ord_hash() {
+    use std::collections::{BTreeMap, HashSet};
+
+    let mut operands = vec![
+        Operand::Glb(1),
+        Operand::Val(7),
+        Operand::Loc(4),
+        Operand::Emp,
+        Operand::Loc(0),
+    ];
+    operands.sort();
+    assert_eq!(
+        operands,
+        [
+            Operand::Loc(0),
+            Operand::Loc(4),
+            Operand::Val(7),
+            Operand::Glb(1),
+            Operand::Emp,
+        ]
+    );
+
+    let set: HashSet<_> = [Operand::Loc(1), Operand::Loc(1), Operand::Ret(1)]
+        .iter()
+        .copied()
+        .collect();
+    assert_eq!(set.len(), 2);
+
+    let mut counts = BTreeMap::new();
+    for &ty in &[OpType::F64, OpType::U8, OpType::F64, OpType::I32] {
+        *counts.entry(ty).or_insert(0) += 1;
+    }
+    let counts: Vec<_> = counts.into_iter().collect();
+    assert_eq!(
+        counts,
+        [(OpType::U8, 1), (OpType::I32, 1), (OpType::F64, 2)]
+    );
+    assert!(OpType::U128 < OpType::F16);
+}
+
+#[test]
+fn line_table_lookup() {
+    let at = |line| SourceLocation { line, column: 1 };
+    let lines = [
+        LineEntry {
+            pc: 1,
+            location: at(3),
+        },
+        LineEntry {
+            pc: 4,
+            location: at(7),
+        },
+    ];
+
+    assert_eq!(source_location(&lines, 0), None);
+    assert_eq!(source_location(&lines, 1), Some(at(3)));
+    assert_eq!(source_location(&lines, 3), Some(at(3)));
+    assert_eq!(source_location(&lines, 9), Some(at(7)));
+    assert_eq!(source_location(&[], 0), None);
+}
+
+#[test]
+#[cfg(feature = "serde")]
+fn op_serde_round_trip() {
+    let ops = [
+        Op::Add(BinOp::new(Operand::Loc(4), Operand::Val(1)), OpType::U32),
+        Op::Inc(UnOp::new(Operand::Emp), OpType::F64),
+        Op::Nop,
+    ];
+
+    let json = serde_json::to_string(&ops).unwrap();
+    assert_eq!(
+        json,
+        r#"[{"Add":[{"None":{"x":{"Loc":4},"y":{"Val":1}}},"U32"]},{"Inc":[{"None":{"x":"Emp"}},"F64"]},"Nop"]"#
+    );
+    assert_eq!(serde_json::from_str::<Vec<Op>>(&json).unwrap(), ops);
 }
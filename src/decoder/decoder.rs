@@ -5,10 +5,28 @@ use std::io::{self, Read};
 #[derive(Debug)]
 pub enum DecodeError {
     ReadError(io::Error),
-    UnexpectedEnd,
-    UnknownOpCode,
+    /// The input ended at the byte offset.
+    UnexpectedEnd {
+        offset: usize,
+    },
+    /// The unknown op code is at the byte offset.
+    UnknownOpCode {
+        offset: usize,
+    },
     UndefinedOperation(UndefinedOperation),
-    IncorrectVariant,
+    /// The variant byte is not supported by the decoded item.
+    IncorrectVariant {
+        got: u8,
+    },
+    IncorrectMagic,
+    UnsupportedVersion(u8),
+    /// The line table row `pc` is out of the program or not increasing.
+    IncorrectLinePc(UWord),
+    /// The flags byte of the data segment is unknown.
+    IncorrectSegmentFlags(u8),
+    LimitExceeded,
+    /// The operand value is greater than the config allows.
+    OperandTooLarge(Operand),
 }
 
 impl From<UndefinedOperation> for DecodeError {
@@ -23,8 +41,46 @@ impl From<io::Error> for DecodeError {
     }
 }
 
+impl DecodeError {
+    /// Sets the offset of the error after `consumed` bytes were read.
+    fn at(self, consumed: usize) -> Self {
+        match self {
+            DecodeError::UnexpectedEnd { .. } => DecodeError::UnexpectedEnd { offset: consumed },
+            DecodeError::UnknownOpCode { .. } => DecodeError::UnknownOpCode {
+                // The op code is the last read byte
+                offset: consumed.saturating_sub(1),
+            },
+            e => e,
+        }
+    }
+}
+
 impl ExpectedError for DecodeError {
-    const ERROR: Self = DecodeError::UnexpectedEnd;
+    // The offset is set by the caller which counts the read bytes.
+    const ERROR: Self = DecodeError::UnexpectedEnd { offset: 0 };
+}
+
+/// Reader which counts the read bytes.
+struct Counter<R> {
+    inner: R,
+    count: usize,
+}
+
+impl<R> Counter<R> {
+    fn new(inner: R) -> Self {
+        Self { inner, count: 0 }
+    }
+}
+
+impl<R> Read for Counter<R>
+where
+    R: Read,
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.count += n;
+        Ok(n)
+    }
 }
 
 trait ReadU8 {
@@ -42,7 +98,124 @@ where
     }
 }
 
+/// Decodes the op. The error offset is relative to the start of the op.
 fn decode_op<R>(bytes: &mut R) -> Result<Op, DecodeError>
+where
+    R: Read,
+{
+    let mut counter = Counter::new(bytes);
+    read_op(&mut counter).map_err(|e| e.at(counter.count))
+}
+
+/// Lazily decodes ops until the input ends.
+///
+/// The iterator ends if the input ends between ops and yields
+/// `UnexpectedEnd` if it ends inside an op. After an error it yields nothing.
+/// The error offsets are relative to the start of the op.
+pub fn decode_iter<R>(mut bytes: R) -> impl Iterator<Item = Result<Op, DecodeError>>
+where
+    R: Read,
+{
+    let mut failed = false;
+
+    std::iter::from_fn(move || {
+        if failed {
+            return None;
+        }
+
+        let mut code = [0];
+        let res = match bytes.read(&mut code) {
+            Ok(0) => return None,
+            Ok(_) => decode_op(&mut code.as_ref().chain(&mut bytes)),
+            Err(e) => Err(e.into()),
+        };
+
+        failed = res.is_err();
+        Some(res)
+    })
+}
+
+/// Incremental decoder fed with successive chunks of the input.
+///
+/// The bytes of an op split between chunks are kept until the op is complete.
+/// The error offsets are relative to the start of the op.
+/// After an error it yields nothing.
+#[derive(Debug, Default)]
+pub struct Decoder {
+    pending: Vec<u8>,
+    failed: bool,
+    config: DecoderConfig,
+}
+
+impl Decoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a decoder checking the operands against the `max_operand_value`
+    /// of the config.
+    pub fn with_config(config: DecoderConfig) -> Self {
+        Self {
+            config,
+            ..Self::default()
+        }
+    }
+
+    /// Appends the chunk and decodes all ops which became complete.
+    pub fn push(&mut self, buf: &[u8]) -> Vec<Result<Op, DecodeError>> {
+        if self.failed {
+            return Vec::new();
+        }
+
+        self.pending.extend_from_slice(buf);
+
+        let mut ops = Vec::new();
+        let mut rest = self.pending.as_slice();
+        while !rest.is_empty() {
+            let mut bytes = rest;
+            let op = decode_op(&mut bytes)
+                .and_then(|op| check_operands(&op, self.config.max_operand_value).map(|_| op));
+            match op {
+                Ok(op) => {
+                    ops.push(Ok(op));
+                    rest = bytes;
+                }
+                Err(DecodeError::UnexpectedEnd { .. }) => break,
+                Err(e) => {
+                    ops.push(Err(e));
+                    self.failed = true;
+                    break;
+                }
+            }
+        }
+
+        let consumed = self.pending.len() - rest.len();
+        self.pending.drain(..consumed);
+        if self.failed {
+            self.pending.clear();
+        }
+
+        ops
+    }
+
+    /// Returns the number of buffered bytes of an incomplete op.
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Checks the input ended between ops.
+    pub fn finish(self) -> Result<(), DecodeError> {
+        if self.pending.is_empty() {
+            Ok(())
+        } else {
+            Err(DecodeError::UnexpectedEnd {
+                offset: self.pending.len(),
+            })
+        }
+    }
+}
+
+fn read_op<R>(bytes: &mut R) -> Result<Op, DecodeError>
 where
     R: Read,
 {
@@ -82,16 +255,12 @@ where
             Mod(bin_op, op_type)
         }
         SHL => {
-            let op_type = decode(bytes)?;
-            let x = decode(bytes)?;
-            let y = decode(bytes)?;
-            Shl(x, y, op_type)
+            let (bin_op, op_type) = decode(bytes)?;
+            Shl(bin_op, op_type)
         }
         SHR => {
-            let op_type = decode(bytes)?;
-            let x = decode(bytes)?;
-            let y = decode(bytes)?;
-            Shr(x, y, op_type)
+            let (bin_op, op_type) = decode(bytes)?;
+            Shr(bin_op, op_type)
         }
         AND => {
             let (bin_op, op_type) = decode(bytes)?;
@@ -236,7 +405,190 @@ where
             let z = decode(bytes)?;
             Cpy(x, y, z)
         }
-        _ => return Err(DecodeError::UnknownOpCode),
+        ADS => {
+            let (bin_op, op_type) = decode(bytes)?;
+            Ads(bin_op, op_type)
+        }
+        SBS => {
+            let (bin_op, op_type) = decode(bytes)?;
+            Sbs(bin_op, op_type)
+        }
+        MLS => {
+            let (bin_op, op_type) = decode(bytes)?;
+            Mls(bin_op, op_type)
+        }
+        SQR => {
+            let (op_type, var): (OpType, Variant) = decode(bytes)?;
+            let un_op = decode_with(bytes, var)?;
+
+            Sqr(un_op, op_type)
+        }
+        FUN => {
+            let (op_type, var): (OpType, Variant) = decode(bytes)?;
+            let math_fn = decode(bytes)?;
+            let un_op = decode_with(bytes, var)?;
+
+            Fun(un_op, op_type, math_fn)
+        }
+        ROL => {
+            let op_type = decode(bytes)?;
+            let x = decode(bytes)?;
+            let y = decode(bytes)?;
+            Rol(x, y, op_type)
+        }
+        ROR => {
+            let op_type = decode(bytes)?;
+            let x = decode(bytes)?;
+            let y = decode(bytes)?;
+            Ror(x, y, op_type)
+        }
+        CNT => {
+            let (op_type, var): (OpType, Variant) = decode(bytes)?;
+            let un_op = decode_with(bytes, var)?;
+
+            Cnt(un_op, op_type)
+        }
+        CLZ => {
+            let (op_type, var): (OpType, Variant) = decode(bytes)?;
+            let un_op = decode_with(bytes, var)?;
+
+            Clz(un_op, op_type)
+        }
+        CTZ => {
+            let (op_type, var): (OpType, Variant) = decode(bytes)?;
+            let un_op = decode_with(bytes, var)?;
+
+            Ctz(un_op, op_type)
+        }
+        LDA => {
+            let x = decode(bytes)?;
+            let y = decode(bytes)?;
+            Lda(x, y)
+        }
+        STA => {
+            let x = decode(bytes)?;
+            let y = decode(bytes)?;
+            Sta(x, y)
+        }
+        SWP => {
+            let op_type = decode(bytes)?;
+            let x = decode(bytes)?;
+            let y = decode(bytes)?;
+            Swp(x, y, op_type)
+        }
+        CAS => {
+            let op_type = decode(bytes)?;
+            let x = decode(bytes)?;
+            let y = decode(bytes)?;
+            let z = decode(bytes)?;
+            Cas(x, y, z, op_type)
+        }
+        MIN => {
+            let (bin_op, op_type) = decode(bytes)?;
+            Min(bin_op, op_type)
+        }
+        MAX => {
+            let (bin_op, op_type) = decode(bytes)?;
+            Max(bin_op, op_type)
+        }
+        ABS => {
+            let (op_type, var): (OpType, Variant) = decode(bytes)?;
+            let un_op = decode_with(bytes, var)?;
+
+            Abs(un_op, op_type)
+        }
+        EOF => Eof(decode(bytes)?),
+        INB => {
+            let x = decode(bytes)?;
+            let y = decode(bytes)?;
+            Inb(x, y)
+        }
+        OUTB => {
+            let x = decode(bytes)?;
+            let y = decode(bytes)?;
+            Outb(x, y)
+        }
+        TRP => Trp(decode(bytes)?),
+        SKP => Skp(decode(bytes)?),
+        SEL => {
+            let op_type = decode(bytes)?;
+            let x = decode(bytes)?;
+            let y = decode(bytes)?;
+            let z = decode(bytes)?;
+            let w = decode(bytes)?;
+            Sel(x, y, z, w, op_type)
+        }
+        GIP => Gip(decode(bytes)?),
+        GOR => Gor(decode(bytes)?),
+        TAP => Tap(decode(bytes)?),
+        SCN => {
+            let x = decode(bytes)?;
+            let y = decode(bytes)?;
+            let z = decode(bytes)?;
+            let w = decode(bytes)?;
+            Scn(x, y, z, w)
+        }
+        FIL => {
+            let x = decode(bytes)?;
+            let y = decode(bytes)?;
+            let z = decode(bytes)?;
+            Fil(x, y, z)
+        }
+        IFN => {
+            let (op_type, var): (OpType, Variant) = decode(bytes)?;
+            let un_op = decode_with(bytes, var)?;
+
+            Ifn(un_op, op_type)
+        }
+        CNR => {
+            let (t, u) = decode(bytes)?;
+            Cnr(decode(bytes)?, decode(bytes)?, t, u)
+        }
+        REM => {
+            let x = decode(bytes)?;
+            let y = decode(bytes)?;
+            Rem(x, y)
+        }
+        OUI => {
+            let op_type = decode(bytes)?;
+            let x = decode(bytes)?;
+            Oui(x, op_type)
+        }
+        OUF => {
+            let op_type = decode(bytes)?;
+            let x = decode(bytes)?;
+            Ouf(x, op_type)
+        }
+        OUC => Ouc(decode(bytes)?),
+        RND => {
+            let op_type = decode(bytes)?;
+            let x = decode(bytes)?;
+            Rnd(x, op_type)
+        }
+        YLD => Yld(decode(bytes)?),
+        BND => {
+            let x = decode(bytes)?;
+            let y = decode(bytes)?;
+            let z = decode(bytes)?;
+            Bnd(x, y, z)
+        }
+        BIT => {
+            let (t, u) = decode(bytes)?;
+            Bit(decode(bytes)?, decode(bytes)?, t, u)
+        }
+        GRP => Grp(decode(bytes)?),
+        SRP => Srp(decode(bytes)?),
+        CFD => Cfd(decode(bytes)?),
+        OVF => Ovf(decode(bytes)?),
+        ADC => {
+            let (bin_op, op_type) = decode(bytes)?;
+            Adc(bin_op, op_type)
+        }
+        SBB => {
+            let (bin_op, op_type) = decode(bytes)?;
+            Sbb(bin_op, op_type)
+        }
+        _ => return Err(DecodeError::UnknownOpCode { offset: 0 }),
     };
 
     Ok(op)
@@ -253,6 +605,357 @@ impl Decode<()> for Op {
     }
 }
 
+fn read_word<R>(bytes: &mut R) -> Result<UWord, DecodeError>
+where
+    R: Read,
+{
+    let mut buf = [0; std::mem::size_of::<UWord>()];
+    bytes.read(&mut buf).expected::<DecodeError>(buf.len())?;
+    Ok(UWord::from_le_bytes(buf))
+}
+
+/// Limits of the decoded program.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct DecoderConfig {
+    pub max_functions: UWord,
+    pub max_program_len: UWord,
+    /// Limits the total length of the data section bytes.
+    pub max_data_len: UWord,
+    /// Limits the values of the operands except `Val`.
+    pub max_operand_value: UWord,
+}
+
+impl Default for DecoderConfig {
+    fn default() -> Self {
+        Self {
+            max_functions: 1 << 12,
+            max_program_len: 1 << 16,
+            max_data_len: 1 << 20,
+            max_operand_value: UWord::MAX,
+        }
+    }
+}
+
+/// Decodes a program written by `encode_program` with the default limits.
+///
+/// The layout is the magic bytes, the version byte and the function count,
+/// followed by the frame size, the op count, the ops and the line table of
+/// each function and the data section, see `encode_program_with_data`.
+/// The words are little endian.
+pub fn decode_program<R>(bytes: &mut R) -> Result<Vec<OwnedFunction>, DecodeError>
+where
+    R: Read,
+{
+    decode_program_with(bytes, &DecoderConfig::default())
+}
+
+/// Decodes a program, returning `LimitExceeded` if the function count or
+/// the op count of any function is greater than the config allows and
+/// `OperandTooLarge` if an operand value is.
+///
+/// The offsets of the errors are relative to the start of the program.
+pub fn decode_program_with<R>(
+    bytes: &mut R,
+    config: &DecoderConfig,
+) -> Result<Vec<OwnedFunction>, DecodeError>
+where
+    R: Read,
+{
+    decode_program_with_data(bytes, config).map(|(functions, _)| functions)
+}
+
+/// Decodes a program with its data section. The data section of
+/// the version 1 program and the line tables before the version 3 are empty.
+pub fn decode_program_with_data<R>(
+    bytes: &mut R,
+    config: &DecoderConfig,
+) -> Result<(Vec<OwnedFunction>, Vec<DataSegment>), DecodeError>
+where
+    R: Read,
+{
+    let mut counter = Counter::new(bytes);
+    read_program(&mut counter, config).map_err(|e| e.at(counter.count))
+}
+
+fn read_program<R>(
+    bytes: &mut R,
+    config: &DecoderConfig,
+) -> Result<(Vec<OwnedFunction>, Vec<DataSegment>), DecodeError>
+where
+    R: Read,
+{
+    let mut magic = [0; PROGRAM_MAGIC.len()];
+    bytes
+        .read(&mut magic)
+        .expected::<DecodeError>(magic.len())?;
+
+    if magic != PROGRAM_MAGIC {
+        return Err(DecodeError::IncorrectMagic);
+    }
+
+    let version = bytes.read_u8()?;
+    if version == 0 || version > PROGRAM_VERSION {
+        return Err(DecodeError::UnsupportedVersion(version));
+    }
+
+    let n_functions = read_word(bytes)?;
+    if n_functions > config.max_functions {
+        return Err(DecodeError::LimitExceeded);
+    }
+
+    let mut functions = Vec::with_capacity(n_functions as usize);
+
+    for _ in 0..n_functions {
+        let frame_size = read_word(bytes)?;
+        let n_ops = read_word(bytes)?;
+        if n_ops > config.max_program_len {
+            return Err(DecodeError::LimitExceeded);
+        }
+        let program = (0..n_ops)
+            .map(|_| {
+                let op = decode_op(bytes)?;
+                check_operands(&op, config.max_operand_value)?;
+                Ok(op)
+            })
+            .collect::<Result<_, DecodeError>>()?;
+
+        let lines = if version < 3 {
+            vec![]
+        } else {
+            read_lines(bytes, config, n_ops)?
+        };
+
+        functions.push(OwnedFunction {
+            frame_size,
+            program,
+            lines,
+        });
+    }
+
+    let data = if version == 1 {
+        vec![]
+    } else {
+        read_data(bytes, config)?
+    };
+
+    Ok((functions, data))
+}
+
+fn read_lines<R>(
+    bytes: &mut R,
+    config: &DecoderConfig,
+    n_ops: UWord,
+) -> Result<Vec<LineEntry>, DecodeError>
+where
+    R: Read,
+{
+    let n_lines = read_word(bytes)?;
+    if n_lines > config.max_program_len {
+        return Err(DecodeError::LimitExceeded);
+    }
+
+    let mut lines: Vec<LineEntry> = Vec::with_capacity(n_lines as usize);
+
+    for _ in 0..n_lines {
+        let pc = read_word(bytes)?;
+
+        // The rows must be sorted by `pc` to be searched
+        let unsorted = matches!(lines.last(), Some(last) if last.pc >= pc);
+        if unsorted || pc >= n_ops {
+            return Err(DecodeError::IncorrectLinePc(pc));
+        }
+
+        lines.push(LineEntry {
+            pc,
+            location: SourceLocation {
+                line: read_word(bytes)?,
+                column: read_word(bytes)?,
+            },
+        });
+    }
+
+    Ok(lines)
+}
+
+fn check_operands(op: &Op, max: UWord) -> Result<(), DecodeError> {
+    let mut res = Ok(());
+    for_each_operand(op, |operand| match operand {
+        Operand::Val(_) => {}
+        _ if res.is_ok() && matches!(operand.get(), Some(v) if v > max) => {
+            res = Err(DecodeError::OperandTooLarge(operand));
+        }
+        _ => {}
+    });
+
+    res
+}
+
+fn for_each_operand<F>(op: &Op, mut f: F)
+where
+    F: FnMut(Operand),
+{
+    use Op::*;
+
+    let un = |un: &UnOp, f: &mut F| match *un {
+        UnOp::None { x } => f(x),
+        UnOp::First { x, offset } => {
+            f(x);
+            f(offset);
+        }
+    };
+
+    let bin = |bin: &BinOp, f: &mut F| match *bin {
+        BinOp::None { x, y } => {
+            f(x);
+            f(y);
+        }
+        BinOp::First { x, y, offset }
+        | BinOp::Second { x, y, offset }
+        | BinOp::Both { x, y, offset } => {
+            f(x);
+            f(y);
+            f(offset);
+        }
+    };
+
+    match op {
+        Nop | Fls => {}
+        Set(b, _)
+        | Add(b, _)
+        | Sub(b, _)
+        | Mul(b, _)
+        | Div(b, _)
+        | Mod(b, _)
+        | Shl(b, _)
+        | Shr(b, _)
+        | And(b, _)
+        | Or(b, _)
+        | Xor(b, _)
+        | Ife(b, _)
+        | Ifl(b, _)
+        | Ifg(b, _)
+        | Ine(b, _)
+        | Inl(b, _)
+        | Ing(b, _)
+        | Ifa(b, _)
+        | Ifo(b, _)
+        | Ifx(b, _)
+        | Ina(b, _)
+        | Ino(b, _)
+        | Inx(b, _)
+        | Ads(b, _)
+        | Sbs(b, _)
+        | Mls(b, _)
+        | Min(b, _)
+        | Max(b, _)
+        | Adc(b, _)
+        | Sbb(b, _)
+        | In(b) => bin(b, &mut f),
+        Not(u, _)
+        | Neg(u, _)
+        | Inc(u, _)
+        | Dec(u, _)
+        | Ift(u, _)
+        | Iff(u, _)
+        | Par(u, _)
+        | Ret(u, _)
+        | Sqr(u, _)
+        | Cnt(u, _)
+        | Clz(u, _)
+        | Ctz(u, _)
+        | Abs(u, _)
+        | Ifn(u, _)
+        | Fun(u, _, _)
+        | Out(u) => un(u, &mut f),
+        End(x)
+        | Slp(x)
+        | Go(x)
+        | App(x)
+        | Clf(x)
+        | Sfd(x)
+        | Gfd(x)
+        | Eof(x)
+        | Trp(x)
+        | Skp(x)
+        | Gip(x)
+        | Gor(x)
+        | Tap(x)
+        | Ouc(x)
+        | Yld(x)
+        | Grp(x)
+        | Srp(x)
+        | Cfd(x)
+        | Ovf(x)
+        | Oui(x, _)
+        | Ouf(x, _)
+        | Rnd(x, _) => f(*x),
+        Cnv(x, y, _, _)
+        | Cnr(x, y, _, _)
+        | Bit(x, y, _, _)
+        | Zer(x, y)
+        | Lda(x, y)
+        | Sta(x, y)
+        | Inb(x, y)
+        | Outb(x, y)
+        | Rem(x, y)
+        | Rol(x, y, _)
+        | Ror(x, y, _)
+        | Swp(x, y, _) => {
+            f(*x);
+            f(*y);
+        }
+        Cmp(x, y, z) | Cpy(x, y, z) | Fil(x, y, z) | Bnd(x, y, z) | Cas(x, y, z, _) => {
+            f(*x);
+            f(*y);
+            f(*z);
+        }
+        Scn(a, b, c, d) | Sel(a, b, c, d, _) => {
+            f(*a);
+            f(*b);
+            f(*c);
+            f(*d);
+        }
+    }
+}
+
+fn read_data<R>(bytes: &mut R, config: &DecoderConfig) -> Result<Vec<DataSegment>, DecodeError>
+where
+    R: Read,
+{
+    // The count isn't trusted to preallocate, each segment is read from the input
+    let n_segments = read_word(bytes)?;
+    let mut data = Vec::new();
+    let mut total_len: UWord = 0;
+
+    for _ in 0..n_segments {
+        let addr = read_word(bytes)?;
+        let readonly = match bytes.read_u8()? {
+            0 => false,
+            1 => true,
+            flags => return Err(DecodeError::IncorrectSegmentFlags(flags)),
+        };
+
+        let len = read_word(bytes)?;
+        total_len = total_len
+            .checked_add(len)
+            .filter(|&total| total <= config.max_data_len)
+            .ok_or(DecodeError::LimitExceeded)?;
+
+        let mut segment = vec![0; len as usize];
+        bytes
+            .read(&mut segment)
+            .expected::<DecodeError>(segment.len())?;
+
+        data.push(DataSegment {
+            addr,
+            bytes: segment,
+            readonly,
+        });
+    }
+
+    Ok(data)
+}
+
 impl Decode<()> for (BinOp, OpType) {
     type Err = DecodeError;
 
@@ -297,7 +1000,7 @@ impl Decode<Variant> for UnOp {
         Ok(match var {
             Variant::None => un_op,
             Variant::First => un_op.with_first(decode(bytes)?),
-            _ => return Err(DecodeError::IncorrectVariant),
+            _ => return Err(DecodeError::IncorrectVariant { got: var.as_byte() }),
         })
     }
 }
@@ -332,6 +1035,20 @@ impl Decode<()> for OpType {
     }
 }
 
+impl Decode<()> for MathFn {
+    type Err = DecodeError;
+
+    fn decode<R>(bytes: &mut R, _: ()) -> Result<Self, Self::Err>
+    where
+        R: Read,
+    {
+        let meta = bytes.read_u8()?;
+        let math_fn = MathFn::new(meta)?;
+
+        Ok(math_fn)
+    }
+}
+
 impl Decode<()> for (OpType, OpType) {
     type Err = DecodeError;
 
@@ -392,6 +1109,295 @@ mod tests {
     use super::*;
     use op_codes::*;
 
+    #[test]
+    fn decode_program_header() {
+        let mut code = b"NIBX".as_ref();
+        let actual = decode_program(&mut code);
+        assert!(matches!(actual, Err(DecodeError::IncorrectMagic)));
+
+        let mut code = b"NIBC\x07".as_ref();
+        let actual = decode_program(&mut code);
+        assert!(matches!(actual, Err(DecodeError::UnsupportedVersion(7))));
+
+        let mut code = b"NIBC\x01".as_ref();
+        let actual = decode_program(&mut code);
+        assert!(matches!(
+            actual,
+            Err(DecodeError::UnexpectedEnd { offset: 5 })
+        ));
+    }
+
+    #[test]
+    fn decode_program_limits() {
+        let config = DecoderConfig {
+            max_functions: 1,
+            max_program_len: 1,
+            max_data_len: 1,
+            max_operand_value: UWord::MAX,
+        };
+
+        let mut code = b"NIBC\x01".to_vec();
+        code.extend_from_slice(&(2 as UWord).to_le_bytes());
+        let actual = decode_program_with(&mut code.as_slice(), &config);
+        assert!(matches!(actual, Err(DecodeError::LimitExceeded)));
+
+        let mut code = b"NIBC\x01".to_vec();
+        code.extend_from_slice(&(1 as UWord).to_le_bytes());
+        code.extend_from_slice(&(0 as UWord).to_le_bytes());
+        code.extend_from_slice(&UWord::MAX.to_le_bytes());
+        let actual = decode_program_with(&mut code.as_slice(), &config);
+        assert!(matches!(actual, Err(DecodeError::LimitExceeded)));
+
+        let mut code = b"NIBC\x01".to_vec();
+        code.extend_from_slice(&(1 as UWord).to_le_bytes());
+        code.extend_from_slice(&(0 as UWord).to_le_bytes());
+        code.extend_from_slice(&(1 as UWord).to_le_bytes());
+        code.push(NOP);
+        let actual = decode_program_with(&mut code.as_slice(), &config).unwrap();
+        assert_eq!(actual[0].program, [Op::Nop]);
+    }
+
+    #[test]
+    fn decode_program_lines() {
+        let program = |pcs: &[UWord]| {
+            let mut code = b"NIBC\x03".to_vec();
+            code.extend_from_slice(&(1 as UWord).to_le_bytes());
+            code.extend_from_slice(&(0 as UWord).to_le_bytes());
+            code.extend_from_slice(&(3 as UWord).to_le_bytes());
+            code.extend_from_slice(&[NOP, NOP, NOP]);
+            code.extend_from_slice(&(pcs.len() as UWord).to_le_bytes());
+            for &pc in pcs {
+                code.extend_from_slice(&pc.to_le_bytes());
+                code.extend_from_slice(&(1 as UWord).to_le_bytes());
+                code.extend_from_slice(&(1 as UWord).to_le_bytes());
+            }
+            code.extend_from_slice(&(0 as UWord).to_le_bytes());
+            code
+        };
+
+        let code = program(&[0, 2]);
+        let actual = decode_program(&mut code.as_slice()).unwrap();
+        assert_eq!(actual[0].lines.len(), 2);
+
+        let code = program(&[2, 1]);
+        let actual = decode_program(&mut code.as_slice());
+        assert!(matches!(actual, Err(DecodeError::IncorrectLinePc(1))));
+
+        let code = program(&[1, 1]);
+        let actual = decode_program(&mut code.as_slice());
+        assert!(matches!(actual, Err(DecodeError::IncorrectLinePc(1))));
+
+        let code = program(&[3]);
+        let actual = decode_program(&mut code.as_slice());
+        assert!(matches!(actual, Err(DecodeError::IncorrectLinePc(3))));
+    }
+
+    #[test]
+    fn decode_program_data_limits() {
+        let config = DecoderConfig {
+            max_data_len: 4,
+            ..DecoderConfig::default()
+        };
+
+        let program = |segments: &[(UWord, u8, UWord)]| {
+            let mut code = b"NIBC\x02".to_vec();
+            code.extend_from_slice(&(0 as UWord).to_le_bytes());
+            code.extend_from_slice(&(segments.len() as UWord).to_le_bytes());
+            for &(addr, flags, len) in segments {
+                code.extend_from_slice(&addr.to_le_bytes());
+                code.push(flags);
+                code.extend_from_slice(&len.to_le_bytes());
+                code.resize(code.len() + len.min(8) as usize, 7);
+            }
+            code
+        };
+
+        let code = program(&[(0, 1, 2), (8, 0, 2)]);
+        let (_, data) = decode_program_with_data(&mut code.as_slice(), &config).unwrap();
+        assert_eq!(data.len(), 2);
+        assert!(data[0].readonly);
+        assert_eq!(data[1].bytes, [7, 7]);
+
+        let code = program(&[(0, 0, 2), (8, 0, 3)]);
+        let actual = decode_program_with_data(&mut code.as_slice(), &config);
+        assert!(matches!(actual, Err(DecodeError::LimitExceeded)));
+
+        let code = program(&[(0, 0, 1), (8, 0, UWord::MAX)]);
+        let actual = decode_program_with_data(&mut code.as_slice(), &config);
+        assert!(matches!(actual, Err(DecodeError::LimitExceeded)));
+
+        let code = program(&[(0, 2, 0)]);
+        let actual = decode_program_with_data(&mut code.as_slice(), &config);
+        assert!(matches!(actual, Err(DecodeError::IncorrectSegmentFlags(2))));
+
+        // The huge count isn't preallocated and the input ends
+        let mut code = program(&[]);
+        let n = code.len() - std::mem::size_of::<UWord>();
+        code[n..].copy_from_slice(&UWord::MAX.to_le_bytes());
+        let actual = decode_program_with_data(&mut code.as_slice(), &config);
+        assert!(matches!(actual, Err(DecodeError::UnexpectedEnd { .. })));
+    }
+
+    #[test]
+    fn decode_program_max_operand_value() {
+        use crate::encoder::encode::Encode;
+
+        let config = DecoderConfig {
+            max_operand_value: 0xFFFF,
+            ..DecoderConfig::default()
+        };
+
+        let program = |op: Op| {
+            let mut code = b"NIBC\x01".to_vec();
+            code.extend_from_slice(&(1 as UWord).to_le_bytes());
+            code.extend_from_slice(&(0 as UWord).to_le_bytes());
+            code.extend_from_slice(&(1 as UWord).to_le_bytes());
+            op.encode(&mut code).unwrap();
+            code
+        };
+
+        let code = program(Op::Inc(UnOp::new(Operand::Glb(0x1_0000)), OpType::U8));
+        let actual = decode_program_with(&mut code.as_slice(), &config);
+        assert!(matches!(
+            actual,
+            Err(DecodeError::OperandTooLarge(Operand::Glb(0x1_0000)))
+        ));
+
+        let code = program(Op::Set(
+            BinOp::new(Operand::Loc(0), Operand::Ind(8)).with_first(Operand::Ref(0x2_0000)),
+            OpType::U8,
+        ));
+        let actual = decode_program_with(&mut code.as_slice(), &config);
+        assert!(matches!(
+            actual,
+            Err(DecodeError::OperandTooLarge(Operand::Ref(0x2_0000)))
+        ));
+
+        let code = program(Op::Set(
+            BinOp::new(Operand::Glb(0xFFFF), Operand::Val(0x1_0000)),
+            OpType::U32,
+        ));
+        let actual = decode_program_with(&mut code.as_slice(), &config).unwrap();
+        assert_eq!(actual[0].program.len(), 1);
+    }
+
+    #[test]
+    fn decode_program_error_offset() {
+        let header = 5 + 3 * std::mem::size_of::<UWord>();
+
+        let mut code = b"NIBC\x01".to_vec();
+        code.extend_from_slice(&(1 as UWord).to_le_bytes());
+        code.extend_from_slice(&(0 as UWord).to_le_bytes());
+        code.extend_from_slice(&(3 as UWord).to_le_bytes());
+        code.extend_from_slice(&[NOP, 0xFF, NOP]);
+        let actual = decode_program(&mut code.as_slice());
+        assert!(matches!(
+            actual,
+            Err(DecodeError::UnknownOpCode { offset }) if offset == header + 1
+        ));
+
+        let mut code = b"NIBC\x01".to_vec();
+        code.extend_from_slice(&(1 as UWord).to_le_bytes());
+        code.extend_from_slice(&(0 as UWord).to_le_bytes());
+        code.extend_from_slice(&(2 as UWord).to_le_bytes());
+        code.extend_from_slice(&[NOP, INC, 0b0000_0010]);
+        let actual = decode_program(&mut code.as_slice());
+        assert!(matches!(
+            actual,
+            Err(DecodeError::UnexpectedEnd { offset }) if offset == header + 3
+        ));
+    }
+
+    #[test]
+    fn decode_iter_ops() {
+        let code = [NOP, INC, 0b0000_0010, 12, NOP];
+        let actual: Vec<_> = decode_iter(code.as_ref())
+            .collect::<Result<_, _>>()
+            .unwrap();
+
+        assert_eq!(
+            actual,
+            [
+                Op::Nop,
+                Op::Inc(UnOp::new(Operand::Loc(12)), OpType::U16),
+                Op::Nop
+            ]
+        );
+
+        assert_eq!(decode_iter([].as_ref()).count(), 0);
+
+        let code = [NOP, INC, 0b0000_0010];
+        let mut iter = decode_iter(code.as_ref());
+        assert!(matches!(iter.next(), Some(Ok(Op::Nop))));
+        assert!(matches!(
+            iter.next(),
+            Some(Err(DecodeError::UnexpectedEnd { offset: 2 }))
+        ));
+        assert!(iter.next().is_none());
+    }
+
+    #[test]
+    fn decode_incremental() {
+        let code = [
+            NOP,
+            // inc u16 loc(12)
+            INC,
+            0b0000_0010,
+            12,
+            NOP,
+        ];
+
+        let mut decoder = Decoder::new();
+        let mut actual = Vec::new();
+        for byte in code.iter() {
+            for op in decoder.push(&[*byte]) {
+                actual.push(op.unwrap());
+            }
+        }
+
+        assert_eq!(
+            actual,
+            [
+                Op::Nop,
+                Op::Inc(UnOp::new(Operand::Loc(12)), OpType::U16),
+                Op::Nop
+            ]
+        );
+        assert_eq!(decoder.pending(), 0);
+        assert!(decoder.finish().is_ok());
+
+        let mut decoder = Decoder::new();
+        assert!(decoder.push(&code[..2]).len() == 1);
+        assert_eq!(decoder.pending(), 1);
+        assert!(matches!(
+            decoder.finish(),
+            Err(DecodeError::UnexpectedEnd { offset: 1 })
+        ));
+
+        let mut decoder = Decoder::new();
+        let res = decoder.push(&[NOP, 0xFF, NOP]);
+        assert!(matches!(res[0], Ok(Op::Nop)));
+        assert!(matches!(
+            res[1],
+            Err(DecodeError::UnknownOpCode { offset: 0 })
+        ));
+        assert_eq!(res.len(), 2);
+        assert!(decoder.push(&[NOP]).is_empty());
+
+        let mut decoder = Decoder::with_config(DecoderConfig {
+            max_operand_value: 11,
+            ..DecoderConfig::default()
+        });
+        let res = decoder.push(&code);
+        assert!(matches!(res[0], Ok(Op::Nop)));
+        assert!(matches!(
+            res[1],
+            Err(DecodeError::OperandTooLarge(Operand::Loc(12)))
+        ));
+        assert_eq!(res.len(), 2);
+        assert!(decoder.push(&[NOP]).is_empty());
+    }
+
     #[test]
     fn decode_unexpected_end() {
         let code = [
@@ -402,7 +1408,10 @@ mod tests {
         let mut code = code.as_ref();
         let actual = decode_op(&mut code);
 
-        assert!(matches!(actual, Err(DecodeError::UnexpectedEnd)));
+        assert!(matches!(
+            actual,
+            Err(DecodeError::UnexpectedEnd { offset: 1 })
+        ));
         assert!(code.is_empty());
     }
 
@@ -420,7 +1429,10 @@ mod tests {
         let mut code = code.as_ref();
         let actual = decode_op(&mut code);
 
-        assert!(matches!(actual, Err(DecodeError::UnknownOpCode)));
+        assert!(matches!(
+            actual,
+            Err(DecodeError::UnknownOpCode { offset: 0 })
+        ));
     }
 
     #[test]
@@ -438,7 +1450,10 @@ mod tests {
         let mut code = code.as_ref();
         let actual = decode_op(&mut code);
 
-        assert!(matches!(actual, Err(DecodeError::IncorrectVariant)));
+        assert!(matches!(
+            actual,
+            Err(DecodeError::IncorrectVariant { got: 2 })
+        ));
     }
 
     #[test]
@@ -650,7 +1665,7 @@ mod tests {
             9,
         ];
 
-        let expected = Op::Shl(Operand::Loc(12), Operand::Loc(9), OpType::U32);
+        let expected = Op::Shl(BinOp::new(Operand::Loc(12), Operand::Loc(9)), OpType::U32);
 
         let mut code = code.as_ref();
         let actual = decode_op(&mut code).unwrap();
@@ -842,4 +1857,272 @@ mod tests {
         assert_eq!(actual, expected);
         assert!(code.is_empty());
     }
+
+    #[test]
+    fn decode_fil() {
+        let code = [
+            // fil loc(0) val(171) val(12)
+            FIL,
+            0,
+            0b1011_0000,
+            0xAB,
+            0b1011_0000,
+            12,
+        ];
+
+        let expected = Op::Fil(Operand::Loc(0), Operand::Val(0xAB), Operand::Val(12));
+
+        let mut code = code.as_ref();
+        let actual = decode_op(&mut code).unwrap();
+
+        assert_eq!(actual, expected);
+        assert!(code.is_empty());
+    }
+
+    #[test]
+    fn decode_ads() {
+        let code = [
+            // ads u8 loc(0) val(12)
+            ADS,
+            0b0000_0000,
+            0,
+            0b1011_0000,
+            12,
+        ];
+
+        let expected = Op::Ads(BinOp::new(Operand::Loc(0), Operand::Val(12)), OpType::U8);
+
+        let mut code = code.as_ref();
+        let actual = decode_op(&mut code).unwrap();
+
+        assert_eq!(actual, expected);
+        assert!(code.is_empty());
+    }
+
+    #[test]
+    fn decode_sqr() {
+        let code = [
+            // sqr f32 loc(4)
+            SQR,
+            0b0000_1011,
+            4,
+        ];
+
+        let expected = Op::Sqr(UnOp::new(Operand::Loc(4)), OpType::F32);
+
+        let mut code = code.as_ref();
+        let actual = decode_op(&mut code).unwrap();
+
+        assert_eq!(actual, expected);
+        assert!(code.is_empty());
+    }
+
+    #[test]
+    fn decode_fun() {
+        let code = [
+            // fun f64 exp loc(8)
+            FUN,
+            0b0000_1101,
+            3,
+            8,
+        ];
+
+        let expected = Op::Fun(UnOp::new(Operand::Loc(8)), OpType::F64, MathFn::Exp);
+
+        let mut code = code.as_ref();
+        let actual = decode_op(&mut code).unwrap();
+
+        assert_eq!(actual, expected);
+        assert!(code.is_empty());
+    }
+
+    #[test]
+    fn decode_rol() {
+        let code = [
+            // rol u32 loc(12) val(9)
+            ROL,
+            0b0000_0100,
+            12,
+            0b1011_0000,
+            9,
+        ];
+
+        let expected = Op::Rol(Operand::Loc(12), Operand::Val(9), OpType::U32);
+
+        let mut code = code.as_ref();
+        let actual = decode_op(&mut code).unwrap();
+
+        assert_eq!(actual, expected);
+        assert!(code.is_empty());
+    }
+
+    #[test]
+    fn decode_sel() {
+        let code = [
+            // sel u8 loc(0) loc(1) val(10) val(20)
+            SEL,
+            0b0000_0000,
+            0,
+            1,
+            0b1011_0000,
+            10,
+            0b1011_0000,
+            20,
+        ];
+
+        let expected = Op::Sel(
+            Operand::Loc(0),
+            Operand::Loc(1),
+            Operand::Val(10),
+            Operand::Val(20),
+            OpType::U8,
+        );
+
+        let mut code = code.as_ref();
+        let actual = decode_op(&mut code).unwrap();
+
+        assert_eq!(actual, expected);
+        assert!(code.is_empty());
+    }
+
+    #[test]
+    fn decode_ror_round_trip() {
+        use crate::encoder::encode::Encode;
+
+        let expected = Op::Ror(Operand::Ind(300), Operand::Loc(1), OpType::I64);
+
+        let mut buf = vec![];
+        expected.encode(&mut buf).unwrap();
+
+        let mut code = buf.as_slice();
+        let actual = decode_op(&mut code).unwrap();
+
+        assert_eq!(actual, expected);
+        assert!(code.is_empty());
+    }
+
+    #[test]
+    fn decode_wide_round_trip() {
+        use crate::encoder::encode::Encode;
+
+        let ops = [
+            Op::Add(BinOp::new(Operand::Loc(0), Operand::Val(1)), OpType::U128),
+            Op::Cnv(Operand::Loc(0), Operand::Loc(16), OpType::I128, OpType::U64),
+        ];
+
+        for &expected in &ops {
+            let mut buf = vec![];
+            expected.encode(&mut buf).unwrap();
+
+            let mut code = buf.as_slice();
+            let actual = decode_op(&mut code).unwrap();
+
+            assert_eq!(actual, expected);
+            assert!(code.is_empty());
+        }
+    }
+
+    #[test]
+    fn decode_cnt() {
+        let code = [
+            // cnt u32 loc(4){loc(0)}
+            CNT,
+            0b0100_0100,
+            4,
+            0,
+        ];
+
+        let expected = Op::Cnt(
+            UnOp::new(Operand::Loc(4)).with_first(Operand::Loc(0)),
+            OpType::U32,
+        );
+
+        let mut code = code.as_ref();
+        let actual = decode_op(&mut code).unwrap();
+
+        assert_eq!(actual, expected);
+        assert!(code.is_empty());
+    }
+
+    #[test]
+    fn decode_lda_sta() {
+        let code = [
+            // lda loc(4) glb(256)
+            LDA,
+            4,
+            0b1101_0001,
+            0,
+            1,
+            // sta loc(0) loc(8)
+            STA,
+            0,
+            8,
+        ];
+
+        let mut code = code.as_ref();
+        let actual = decode_op(&mut code).unwrap();
+        assert_eq!(actual, Op::Lda(Operand::Loc(4), Operand::Glb(256)));
+
+        let actual = decode_op(&mut code).unwrap();
+        assert_eq!(actual, Op::Sta(Operand::Loc(0), Operand::Loc(8)));
+        assert!(code.is_empty());
+    }
+
+    #[test]
+    fn decode_swp_round_trip() {
+        use crate::encoder::encode::Encode;
+
+        let expected = Op::Swp(Operand::Loc(4), Operand::Ind(200), OpType::F64);
+
+        let mut buf = vec![];
+        expected.encode(&mut buf).unwrap();
+
+        let mut code = buf.as_slice();
+        let actual = decode_op(&mut code).unwrap();
+
+        assert_eq!(actual, expected);
+        assert!(code.is_empty());
+    }
+
+    #[test]
+    fn decode_cas() {
+        let code = [
+            // cas u32 loc(0) loc(4) val(9)
+            CAS,
+            0b0000_0100,
+            0,
+            4,
+            0b1011_0000,
+            9,
+        ];
+
+        let expected = Op::Cas(
+            Operand::Loc(0),
+            Operand::Loc(4),
+            Operand::Val(9),
+            OpType::U32,
+        );
+
+        let mut code = code.as_ref();
+        let actual = decode_op(&mut code).unwrap();
+
+        assert_eq!(actual, expected);
+        assert!(code.is_empty());
+    }
+
+    #[test]
+    fn decode_glb_ind() {
+        let code = [
+            // gin(512)
+            0b1111_0001,
+            0,
+            2,
+        ];
+
+        let mut code = code.as_ref();
+        let actual: Operand = decode(&mut code).unwrap();
+
+        assert_eq!(actual, Operand::GlbInd(512));
+        assert!(code.is_empty());
+    }
 }
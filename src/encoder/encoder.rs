@@ -1,5 +1,8 @@
 use super::encode::*;
-use crate::common::{bits::LONG_OPERAND_BIT, *};
+use crate::{
+    common::{bits::LONG_OPERAND_BIT, *},
+    executor::Function,
+};
 use std::io::{self, Write};
 
 #[derive(Debug)]
@@ -65,17 +68,13 @@ where
             MOD.encode(buf)?;
             (b, t).encode(buf)
         }
-        Shl(x, y, t) => {
+        Shl(b, t) => {
             SHL.encode(buf)?;
-            t.encode(buf)?;
-            x.encode(buf)?;
-            y.encode(buf)
+            (b, t).encode(buf)
         }
-        Shr(x, y, t) => {
+        Shr(b, t) => {
             SHR.encode(buf)?;
-            t.encode(buf)?;
-            x.encode(buf)?;
-            y.encode(buf)
+            (b, t).encode(buf)
         }
         And(b, t) => {
             AND.encode(buf)?;
@@ -215,9 +214,314 @@ where
             y.encode(buf)?;
             z.encode(buf)
         }
+        Ads(b, t) => {
+            ADS.encode(buf)?;
+            (b, t).encode(buf)
+        }
+        Sbs(b, t) => {
+            SBS.encode(buf)?;
+            (b, t).encode(buf)
+        }
+        Mls(b, t) => {
+            MLS.encode(buf)?;
+            (b, t).encode(buf)
+        }
+        Sqr(u, t) => {
+            SQR.encode(buf)?;
+            (u, t).encode(buf)
+        }
+        Fun(u, t, m) => {
+            FUN.encode(buf)?;
+            (t, u.variant()).encode(buf)?;
+            m.as_byte().encode(buf)?;
+            u.encode(buf)
+        }
+        Rol(x, y, t) => {
+            ROL.encode(buf)?;
+            t.encode(buf)?;
+            x.encode(buf)?;
+            y.encode(buf)
+        }
+        Ror(x, y, t) => {
+            ROR.encode(buf)?;
+            t.encode(buf)?;
+            x.encode(buf)?;
+            y.encode(buf)
+        }
+        Cnt(u, t) => {
+            CNT.encode(buf)?;
+            (u, t).encode(buf)
+        }
+        Clz(u, t) => {
+            CLZ.encode(buf)?;
+            (u, t).encode(buf)
+        }
+        Ctz(u, t) => {
+            CTZ.encode(buf)?;
+            (u, t).encode(buf)
+        }
+        Lda(x, y) => {
+            LDA.encode(buf)?;
+            x.encode(buf)?;
+            y.encode(buf)
+        }
+        Sta(x, y) => {
+            STA.encode(buf)?;
+            x.encode(buf)?;
+            y.encode(buf)
+        }
+        Swp(x, y, t) => {
+            SWP.encode(buf)?;
+            t.encode(buf)?;
+            x.encode(buf)?;
+            y.encode(buf)
+        }
+        Cas(x, y, z, t) => {
+            CAS.encode(buf)?;
+            t.encode(buf)?;
+            x.encode(buf)?;
+            y.encode(buf)?;
+            z.encode(buf)
+        }
+        Min(b, t) => {
+            MIN.encode(buf)?;
+            (b, t).encode(buf)
+        }
+        Max(b, t) => {
+            MAX.encode(buf)?;
+            (b, t).encode(buf)
+        }
+        Abs(u, t) => {
+            ABS.encode(buf)?;
+            (u, t).encode(buf)
+        }
+        Eof(x) => {
+            EOF.encode(buf)?;
+            x.encode(buf)
+        }
+        Inb(x, y) => {
+            INB.encode(buf)?;
+            x.encode(buf)?;
+            y.encode(buf)
+        }
+        Outb(x, y) => {
+            OUTB.encode(buf)?;
+            x.encode(buf)?;
+            y.encode(buf)
+        }
+        Trp(x) => {
+            TRP.encode(buf)?;
+            x.encode(buf)
+        }
+        Skp(x) => {
+            SKP.encode(buf)?;
+            x.encode(buf)
+        }
+        Sel(x, y, z, w, t) => {
+            SEL.encode(buf)?;
+            t.encode(buf)?;
+            x.encode(buf)?;
+            y.encode(buf)?;
+            z.encode(buf)?;
+            w.encode(buf)
+        }
+        Gip(x) => {
+            GIP.encode(buf)?;
+            x.encode(buf)
+        }
+        Gor(x) => {
+            GOR.encode(buf)?;
+            x.encode(buf)
+        }
+        Tap(x) => {
+            TAP.encode(buf)?;
+            x.encode(buf)
+        }
+        Scn(x, y, z, w) => {
+            SCN.encode(buf)?;
+            x.encode(buf)?;
+            y.encode(buf)?;
+            z.encode(buf)?;
+            w.encode(buf)
+        }
+        Fil(x, y, z) => {
+            FIL.encode(buf)?;
+            x.encode(buf)?;
+            y.encode(buf)?;
+            z.encode(buf)
+        }
+        Ifn(u, t) => {
+            IFN.encode(buf)?;
+            (u, t).encode(buf)
+        }
+        Cnr(x, y, t, u) => {
+            CNR.encode(buf)?;
+            (t, u).encode(buf)?;
+            x.encode(buf)?;
+            y.encode(buf)
+        }
+        Rem(x, y) => {
+            REM.encode(buf)?;
+            x.encode(buf)?;
+            y.encode(buf)
+        }
+        Oui(x, t) => {
+            OUI.encode(buf)?;
+            t.encode(buf)?;
+            x.encode(buf)
+        }
+        Ouf(x, t) => {
+            OUF.encode(buf)?;
+            t.encode(buf)?;
+            x.encode(buf)
+        }
+        Ouc(x) => {
+            OUC.encode(buf)?;
+            x.encode(buf)
+        }
+        Rnd(x, t) => {
+            RND.encode(buf)?;
+            t.encode(buf)?;
+            x.encode(buf)
+        }
+        Yld(x) => {
+            YLD.encode(buf)?;
+            x.encode(buf)
+        }
+        Bnd(x, y, z) => {
+            BND.encode(buf)?;
+            x.encode(buf)?;
+            y.encode(buf)?;
+            z.encode(buf)
+        }
+        Bit(x, y, t, u) => {
+            BIT.encode(buf)?;
+            (t, u).encode(buf)?;
+            x.encode(buf)?;
+            y.encode(buf)
+        }
+        Grp(x) => {
+            GRP.encode(buf)?;
+            x.encode(buf)
+        }
+        Srp(x) => {
+            SRP.encode(buf)?;
+            x.encode(buf)
+        }
+        Cfd(x) => {
+            CFD.encode(buf)?;
+            x.encode(buf)
+        }
+        Ovf(x) => {
+            OVF.encode(buf)?;
+            x.encode(buf)
+        }
+        Adc(b, t) => {
+            ADC.encode(buf)?;
+            (b, t).encode(buf)
+        }
+        Sbb(b, t) => {
+            SBB.encode(buf)?;
+            (b, t).encode(buf)
+        }
     }
 }
 
+/// Encodes the functions in the layout read by `decode_program`.
+pub fn encode_program<W>(functions: &[Function], buf: &mut W) -> Result<(), EncodeError>
+where
+    W: Write,
+{
+    encode_program_with_data(functions, &[], buf)
+}
+
+/// Encodes the functions followed by the data section.
+/// The line tables of the functions are empty.
+///
+/// The ops of each function are followed by its line table, the row count,
+/// then the pc, the line and the column of each row.
+/// The section is the segment count, then the address, the flags byte,
+/// the length and the bytes of each segment. The flag `1` is read-only.
+pub fn encode_program_with_data<W>(
+    functions: &[Function],
+    data: &[DataSegment],
+    buf: &mut W,
+) -> Result<(), EncodeError>
+where
+    W: Write,
+{
+    let functions = functions
+        .iter()
+        .map(|f| (f.frame_size(), f.program(), &[][..]));
+
+    write_program(functions, data, buf)
+}
+
+/// Encodes the owned functions with their line tables followed by the data section.
+pub fn encode_owned_program<W>(
+    functions: &[OwnedFunction],
+    data: &[DataSegment],
+    buf: &mut W,
+) -> Result<(), EncodeError>
+where
+    W: Write,
+{
+    let functions = functions
+        .iter()
+        .map(|f| (f.frame_size, f.program.as_slice(), f.lines.as_slice()));
+
+    write_program(functions, data, buf)
+}
+
+fn write_program<'f, I, W>(
+    functions: I,
+    data: &[DataSegment],
+    buf: &mut W,
+) -> Result<(), EncodeError>
+where
+    I: ExactSizeIterator<Item = (UWord, &'f [Op], &'f [LineEntry])>,
+    W: Write,
+{
+    let write_word = |buf: &mut W, word: UWord| {
+        let bytes = word.to_le_bytes();
+        buf.write(&bytes).expected::<EncodeError>(bytes.len())
+    };
+
+    buf.write(&PROGRAM_MAGIC)
+        .expected::<EncodeError>(PROGRAM_MAGIC.len())?;
+    PROGRAM_VERSION.encode(buf)?;
+    write_word(buf, functions.len() as UWord)?;
+
+    for (frame_size, program, lines) in functions {
+        write_word(buf, frame_size)?;
+        write_word(buf, program.len() as UWord)?;
+
+        for op in program {
+            op.encode(buf)?;
+        }
+
+        write_word(buf, lines.len() as UWord)?;
+
+        for entry in lines {
+            write_word(buf, entry.pc)?;
+            write_word(buf, entry.location.line)?;
+            write_word(buf, entry.location.column)?;
+        }
+    }
+
+    write_word(buf, data.len() as UWord)?;
+
+    for segment in data {
+        write_word(buf, segment.addr)?;
+        (segment.readonly as u8).encode(buf)?;
+        write_word(buf, segment.bytes.len() as UWord)?;
+        buf.write(&segment.bytes)
+            .expected::<EncodeError>(segment.bytes.len())?;
+    }
+
+    Ok(())
+}
+
 impl Encode for Op {
     type Err = EncodeError;
 
@@ -555,7 +859,7 @@ mod tests {
 
     #[test]
     fn encode_shl() {
-        let op = Op::Shl(Operand::Loc(12), Operand::Loc(9), OpType::U32);
+        let op = Op::Shl(BinOp::new(Operand::Loc(12), Operand::Loc(9)), OpType::U32);
 
         let mut buf = vec![];
         encode_op(op, &mut buf).unwrap();
@@ -648,4 +952,131 @@ mod tests {
 
         assert_eq!(buf, &[CPY, 0, 1, 0b1011_0000, 12]);
     }
+
+    #[test]
+    fn encode_ads() {
+        let op = Op::Ads(BinOp::new(Operand::Loc(0), Operand::Val(12)), OpType::U8);
+
+        let mut buf = vec![];
+        encode_op(op, &mut buf).unwrap();
+
+        assert_eq!(buf, &[ADS, 0b0000_0000, 0, 0b1011_0000, 12]);
+    }
+
+    #[test]
+    fn encode_fun() {
+        let op = Op::Fun(UnOp::new(Operand::Loc(8)), OpType::F64, MathFn::Exp);
+
+        let mut buf = vec![];
+        encode_op(op, &mut buf).unwrap();
+
+        assert_eq!(buf, &[FUN, 0b0000_1101, 3, 8]);
+    }
+
+    #[test]
+    fn encode_clz() {
+        let op = Op::Clz(UnOp::new(Operand::Loc(4)), OpType::U64);
+
+        let mut buf = vec![];
+        encode_op(op, &mut buf).unwrap();
+
+        assert_eq!(buf, &[CLZ, 0b0000_0110, 4]);
+    }
+
+    #[test]
+    fn encode_program_round_trip() {
+        use crate::decoder::decode_program;
+
+        let main = [
+            Op::App(Operand::Val(1)),
+            Op::Clf(Operand::Loc(0)),
+            Op::End(Operand::Loc(0)),
+        ];
+        let fun = [
+            Op::Set(BinOp::new(Operand::Loc(0), Operand::Val(300)), OpType::U16),
+            Op::Ret(UnOp::new(Operand::Loc(0)), OpType::U16),
+        ];
+        let functions = [Function::new(2, &main), Function::new(2, &fun)];
+
+        let mut buf = vec![];
+        encode_program(&functions, &mut buf).unwrap();
+        assert_eq!(&buf[..5], b"NIBC\x03");
+
+        let decoded = decode_program(&mut buf.as_slice()).unwrap();
+        assert_eq!(decoded.len(), 2);
+        assert_eq!(decoded[0].frame_size, 2);
+        assert_eq!(decoded[0].program, main);
+        assert_eq!(decoded[1].frame_size, 2);
+        assert_eq!(decoded[1].program, fun);
+    }
+
+    #[test]
+    fn encode_program_lines_round_trip() {
+        use crate::decoder::decode_program;
+
+        let main = vec![Op::Nop, Op::Nop, Op::End(Operand::Loc(0))];
+        let lines = vec![
+            LineEntry {
+                pc: 0,
+                location: SourceLocation { line: 3, column: 5 },
+            },
+            LineEntry {
+                pc: 2,
+                location: SourceLocation { line: 7, column: 1 },
+            },
+        ];
+        let functions = [OwnedFunction {
+            frame_size: 1,
+            program: main,
+            lines,
+        }];
+
+        let mut buf = vec![];
+        encode_owned_program(&functions, &[], &mut buf).unwrap();
+
+        let decoded = decode_program(&mut buf.as_slice()).unwrap();
+        assert_eq!(decoded, functions);
+
+        let function = &decoded[0];
+        let location = |line, column| Some(SourceLocation { line, column });
+        assert_eq!(function.source_location(0), location(3, 5));
+        assert_eq!(function.source_location(1), location(3, 5));
+        assert_eq!(function.source_location(2), location(7, 1));
+        assert_eq!(function.source_location(3), None);
+
+        let mut buf = vec![];
+        encode_program(&[function.function()], &mut buf).unwrap();
+        let decoded = decode_program(&mut buf.as_slice()).unwrap();
+        assert!(decoded[0].lines.is_empty());
+        assert_eq!(decoded[0].source_location(0), None);
+    }
+
+    #[test]
+    fn encode_program_data_round_trip() {
+        use crate::decoder::{decode_program_with_data, DecoderConfig};
+
+        let main = [Op::End(Operand::Loc(0))];
+        let functions = [Function::new(1, &main)];
+        let data = [
+            DataSegment {
+                addr: 4,
+                bytes: b"Hello!".to_vec(),
+                readonly: true,
+            },
+            DataSegment {
+                addr: 12,
+                bytes: vec![],
+                readonly: false,
+            },
+        ];
+
+        let mut buf = vec![];
+        encode_program_with_data(&functions, &data, &mut buf).unwrap();
+
+        let config = DecoderConfig::default();
+        let (decoded, decoded_data) =
+            decode_program_with_data(&mut buf.as_slice(), &config).unwrap();
+        assert_eq!(decoded[0].program, main);
+        assert_eq!(decoded_data, data);
+    }
 }
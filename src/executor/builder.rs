@@ -0,0 +1,150 @@
+use super::Function;
+use crate::common::{Op, Operand, OwnedFunction, UWord};
+use alloc::{
+    collections::BTreeMap,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum BuildError {
+    UndefinedLabel(String),
+    DuplicateLabel(String),
+}
+
+#[derive(Debug)]
+enum Item {
+    Op(Op),
+    Go(String),
+}
+
+/// Builds a function program with jumps to named labels.
+///
+/// The label names are resolved to program counter values by `build`.
+#[derive(Debug)]
+pub struct FunctionBuilder {
+    frame_size: UWord,
+    items: Vec<Item>,
+    labels: BTreeMap<String, UWord>,
+    duplicate: Option<String>,
+}
+
+impl FunctionBuilder {
+    pub fn new(frame_size: UWord) -> Self {
+        Self {
+            frame_size,
+            items: Vec::new(),
+            labels: BTreeMap::new(),
+            duplicate: None,
+        }
+    }
+
+    pub fn frame_size(&mut self, frame_size: UWord) -> &mut Self {
+        self.frame_size = frame_size;
+        self
+    }
+
+    pub fn op(&mut self, op: Op) -> &mut Self {
+        self.items.push(Item::Op(op));
+        self
+    }
+
+    /// Defines the label pointing to the next pushed op.
+    pub fn label(&mut self, name: &str) -> &mut Self {
+        let pc = self.items.len() as UWord;
+
+        if self.labels.insert(name.to_string(), pc).is_some() && self.duplicate.is_none() {
+            self.duplicate = Some(name.to_string());
+        }
+
+        self
+    }
+
+    /// Pushes the `go` op to the label. The label can be defined later.
+    pub fn go(&mut self, label: &str) -> &mut Self {
+        self.items.push(Item::Go(label.to_string()));
+        self
+    }
+
+    pub fn build(&self) -> Result<OwnedFunction, BuildError> {
+        if let Some(name) = &self.duplicate {
+            return Err(BuildError::DuplicateLabel(name.clone()));
+        }
+
+        let program = self
+            .items
+            .iter()
+            .map(|item| match item {
+                Item::Op(op) => Ok(*op),
+                Item::Go(label) => self
+                    .labels
+                    .get(label)
+                    .map(|&pc| Op::Go(Operand::Val(pc)))
+                    .ok_or_else(|| BuildError::UndefinedLabel(label.clone())),
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(OwnedFunction {
+            frame_size: self.frame_size,
+            program,
+            lines: Vec::new(),
+        })
+    }
+}
+
+impl OwnedFunction {
+    pub fn function(&self) -> Function<'_> {
+        Function::new(self.frame_size, &self.program)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::{BinOp, OpType, UnOp};
+
+    #[test]
+    fn builder_labels() {
+        let f = FunctionBuilder::new(4)
+            .label("loop")
+            .op(Op::Inc(UnOp::new(Operand::Loc(0)), OpType::U32))
+            .op(Op::Ifl(
+                BinOp::new(Operand::Loc(0), Operand::Val(3)),
+                OpType::U32,
+            ))
+            .go("loop")
+            .op(Op::Ift(UnOp::new(Operand::Loc(0)), OpType::U32))
+            .go("end")
+            .op(Op::Nop)
+            .label("end")
+            .op(Op::End(Operand::Loc(0)))
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            f.program,
+            [
+                Op::Inc(UnOp::new(Operand::Loc(0)), OpType::U32),
+                Op::Ifl(BinOp::new(Operand::Loc(0), Operand::Val(3)), OpType::U32),
+                Op::Go(Operand::Val(0)),
+                Op::Ift(UnOp::new(Operand::Loc(0)), OpType::U32),
+                Op::Go(Operand::Val(6)),
+                Op::Nop,
+                Op::End(Operand::Loc(0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn builder_errors() {
+        let res = FunctionBuilder::new(0).go("end").build();
+        assert_eq!(res, Err(BuildError::UndefinedLabel("end".to_string())));
+
+        let res = FunctionBuilder::new(0)
+            .label("a")
+            .op(Op::Nop)
+            .label("a")
+            .build();
+        assert_eq!(res, Err(BuildError::DuplicateLabel("a".to_string())));
+    }
+}
@@ -0,0 +1,46 @@
+/// Costs of the ops charged by the executor.
+///
+/// The memory ops `Cpy`, `Cmp`, `Zer`, `Fil` and `Scn` and the byte I/O ops
+/// `Inb` and `Outb` are charged additionally for each byte of their size
+/// operand. `Scn` is charged for its maximal length.
+#[derive(Clone, Debug)]
+pub struct CostTable {
+    ops: [u64; CostTable::OP_CODES],
+    per_byte: u64,
+}
+
+impl CostTable {
+    pub const OP_CODES: usize = u8::MAX as usize + 1;
+
+    /// Creates the table where every op costs 1.
+    pub fn new() -> Self {
+        Self {
+            ops: [1; Self::OP_CODES],
+            per_byte: 0,
+        }
+    }
+
+    pub fn set(&mut self, op_code: u8, cost: u64) -> &mut Self {
+        self.ops[op_code as usize] = cost;
+        self
+    }
+
+    pub fn set_per_byte(&mut self, cost: u64) -> &mut Self {
+        self.per_byte = cost;
+        self
+    }
+
+    pub fn cost(&self, op_code: u8) -> u64 {
+        self.ops[op_code as usize]
+    }
+
+    pub fn per_byte(&self) -> u64 {
+        self.per_byte
+    }
+}
+
+impl Default for CostTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -5,8 +5,16 @@ use super::{
     files::{Files, FilesError},
     memory::*,
     primary::*,
+    CostTable, Profile,
 };
 use crate::common::*;
+use alloc::{
+    boxed::Box,
+    collections::{BTreeMap, BTreeSet},
+    string::String,
+    vec::Vec,
+};
+use core::convert::TryFrom;
 
 #[derive(Debug)]
 pub struct Function<'f> {
@@ -14,9 +22,27 @@ pub struct Function<'f> {
     program: &'f [Op],
 }
 
-#[derive(Debug)]
+impl<'f> Function<'f> {
+    pub fn new(frame_size: UWord, program: &'f [Op]) -> Self {
+        Self {
+            frame_size,
+            program,
+        }
+    }
+
+    pub fn frame_size(&self) -> UWord {
+        self.frame_size
+    }
+
+    pub fn program(&self) -> &'f [Op] {
+        self.program
+    }
+}
+
+#[derive(Clone, Debug)]
 pub struct FunctionCall<'f> {
     function: &'f Function<'f>,
+    function_id: UWord,
     base_ptr: UWord,
     ret_val_ptr: UWord,
     ret_program_counter: UWord,
@@ -32,6 +58,80 @@ pub enum ExecutionError {
     OperationOverflow,
     DivisionByZero,
     NullPointerDereference,
+    CallStackOverflow,
+    StepLimitExceeded,
+    /// Fired trap with the assertion code.
+    Trap(UWord),
+    /// The conditional op is not followed by a non-conditional op.
+    MalformedCondition,
+    /// The parameters don't fit in the frame of the called function.
+    ParameterOverflow,
+    /// The `par` is not preceded by `app` or `tap`.
+    NoPreparedCall,
+    /// The value is not a Unicode scalar value.
+    InvalidChar(u32),
+    /// The index is not less than the length of the array.
+    IndexOutOfBounds {
+        index: UWord,
+        len: UWord,
+    },
+    /// The op is not defined for the type. The 128-bit types and `f16` are
+    /// supported by `set`, `add`, `sub`, `mul`, `cnv`, `cnr` and `bit` only.
+    UnsupportedType(OpType),
+    /// The return pointer with the frame size after it is not in the caller
+    /// frames or the heap.
+    InvalidReturnPointer(UWord),
+}
+
+impl ExecutionError {
+    /// Returns the process exit code for a host which runs the program.
+    ///
+    /// The codes follow the shell convention of `128 + signal`:
+    /// * 132 (illegal instruction) for the malformed program: incorrect
+    ///   or unsupported ops, unknown functions, the end of the program;
+    /// * 133 (trap) for the fired `Trap`;
+    /// * 134 (abort) for the memory errors, invalid pointers, indices
+    ///   and parameters and the call stack overflow;
+    /// * 136 (floating point exception) for the division by zero and
+    ///   the operation overflow;
+    /// * 137 (kill) for the exceeded step limit.
+    ///
+    /// The files errors return 74, the I/O error code of `sysexits.h`.
+    pub fn exit_code(&self) -> i32 {
+        use ExecutionError::*;
+
+        match self {
+            EndOfProgram
+            | IncorrectOperation(_)
+            | UnknownFunction(_)
+            | MalformedCondition
+            | NoPreparedCall
+            | InvalidChar(_)
+            | UnsupportedType(_) => 132,
+            Trap(_) => 133,
+            MemoryError(_)
+            | NullPointerDereference
+            | CallStackOverflow
+            | ParameterOverflow
+            | IndexOutOfBounds { .. }
+            | InvalidReturnPointer(_) => 134,
+            DivisionByZero | OperationOverflow => 136,
+            StepLimitExceeded => 137,
+            FilesError(_) => 74,
+        }
+    }
+}
+
+impl OpType {
+    /// Returns `ExecutionError::IncorrectOperation` with the `op` if the type
+    /// is a float. Used by the ops which are defined only for integers.
+    pub fn require_integer(self, op: Op) -> Result<(), ExecutionError> {
+        if self.is_integer() {
+            Ok(())
+        } else {
+            Err(ExecutionError::IncorrectOperation(op))
+        }
+    }
 }
 
 impl From<MemoryError> for ExecutionError {
@@ -50,11 +150,83 @@ impl From<FilesError> for ExecutionError {
 pub enum ExecutionSuccess {
     Ok,
     End(UWord),
-    Sleep(UWord),
+    /// Sleep until the virtual time.
+    Sleep(u64),
+    /// Yield to the host with a status value.
+    Yield(UWord),
+}
+
+impl ExecutionSuccess {
+    /// Returns the process exit code for the end of the program, which is
+    /// the low byte of the value. Other results don't exit.
+    pub fn exit_code(&self) -> Option<i32> {
+        match self {
+            ExecutionSuccess::End(val) => Some((val & 0xFF) as i32),
+            _ => None,
+        }
+    }
 }
 
 pub type Executed = Result<ExecutionSuccess, ExecutionError>;
 
+#[derive(Debug, Eq, PartialEq)]
+pub enum RunOutcome {
+    End(UWord),
+    Sleep(u64),
+    Yield(UWord),
+    Breakpoint {
+        function_id: UWord,
+        pc: UWord,
+    },
+    /// The last op wrote to the range of the watchpoint at `addr`.
+    Watchpoint {
+        addr: UWord,
+    },
+}
+
+/// Information about the active function call.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct FrameInfo {
+    pub function_id: UWord,
+    pub base_ptr: UWord,
+    pub ret_val_ptr: UWord,
+    pub ret_program_counter: UWord,
+}
+
+#[derive(Clone, Debug)]
+struct CallSnapshot {
+    function_id: UWord,
+    base_ptr: UWord,
+    ret_val_ptr: UWord,
+    ret_program_counter: UWord,
+}
+
+/// Owned copy of the executor state.
+///
+/// Opened files are not a part of the snapshot and stay untouched on restore.
+#[derive(Clone, Debug)]
+pub struct ExecutorSnapshot {
+    memory: Memory,
+    program_counter: UWord,
+    call_stack: Vec<CallSnapshot>,
+    prepared_call: bool,
+    tail_call: bool,
+    parameter_ptr: UWord,
+    virtual_time: u64,
+    rng: u64,
+    overflow: bool,
+}
+
+pub type TraceFn<'f> = Box<dyn FnMut(UWord, &Op) + 'f>;
+
+struct Trace<'f>(TraceFn<'f>);
+
+impl core::fmt::Debug for Trace<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "Trace")
+    }
+}
+
 #[derive(Debug)]
 pub struct Executor<'f> {
     functions: &'f [Function<'f>],
@@ -62,27 +234,120 @@ pub struct Executor<'f> {
     program_counter: UWord,
     call_stack: Vec<FunctionCall<'f>>,
     prepared_call: bool,
+    tail_call: bool,
     parameter_ptr: UWord,
     files: Files,
+    checked: bool,
+    canonical_nan: bool,
+    max_call_depth: usize,
+    breakpoints: BTreeSet<(UWord, UWord)>,
+    /// The location of the last reached breakpoint, skipped by the next run.
+    break_hit: Option<(UWord, UWord)>,
+    watchpoints: Vec<(UWord, UWord)>,
+    watch_hit: Option<UWord>,
+    bounds: BTreeMap<UWord, UWord>,
+    trace: Option<Trace<'f>>,
+    profile: Option<Box<Profile>>,
+    virtual_time: u64,
+    cost_table: Option<Box<CostTable>>,
+    consumed_cost: u64,
+    rng: u64,
+    overflow: bool,
 }
 
-macro_rules! impl_cnv {
-    ($t:ty, $obj:ident, $uid:ident, $x:ident, $y:ident) => {
-        match $uid {
-            U8 => $obj.exec_cnv::<$t, u8>($x, $y)?,
-            I8 => $obj.exec_cnv::<$t, i8>($x, $y)?,
-            U16 => $obj.exec_cnv::<$t, u16>($x, $y)?,
-            I16 => $obj.exec_cnv::<$t, i16>($x, $y)?,
-            U32 => $obj.exec_cnv::<$t, u32>($x, $y)?,
-            I32 => $obj.exec_cnv::<$t, i32>($x, $y)?,
-            U64 => $obj.exec_cnv::<$t, u64>($x, $y)?,
-            I64 => $obj.exec_cnv::<$t, i64>($x, $y)?,
-            Uw => $obj.exec_cnv::<$t, UWord>($x, $y)?,
-            Iw => $obj.exec_cnv::<$t, IWord>($x, $y)?,
-            F32 => $obj.exec_cnv::<$t, f32>($x, $y)?,
-            F64 => $obj.exec_cnv::<$t, f64>($x, $y)?,
+/// Expands to the `match` of the `OpType` which evaluates the body with `T`
+/// defined as the corresponding type. The `integer` form covers integer types
+/// only, so the type is checked by `OpType::require_integer` before to return
+/// `ExecutionError::IncorrectOperation` for the float types.
+/// The 128-bit types and `f16` are covered by the `all` form only, other forms
+/// return `ExecutionError::UnsupportedType` for them, as the `integer` form
+/// does for the float types too.
+macro_rules! dispatch_op_type {
+    (all $ot:expr, |$t:ident| $body:expr) => {
+        match $ot {
+            OpType::U128 => {
+                type $t = u128;
+                $body
+            }
+            OpType::I128 => {
+                type $t = i128;
+                $body
+            }
+            OpType::F16 => {
+                type $t = half::f16;
+                $body
+            }
+            ot => dispatch_op_type!(ot, |$t| $body),
+        }
+    };
+    ($ot:expr, |$t:ident| $body:expr) => {
+        match $ot {
+            OpType::F32 => {
+                type $t = f32;
+                $body
+            }
+            OpType::F64 => {
+                type $t = f64;
+                $body
+            }
+            OpType::F16 => return Err(ExecutionError::UnsupportedType($ot)),
+            ot => dispatch_op_type!(integer ot, |$t| $body),
         }
     };
+    (integer $ot:expr, |$t:ident| $body:expr) => {
+        match $ot {
+            OpType::U8 => {
+                type $t = u8;
+                $body
+            }
+            OpType::I8 => {
+                type $t = i8;
+                $body
+            }
+            OpType::U16 => {
+                type $t = u16;
+                $body
+            }
+            OpType::I16 => {
+                type $t = i16;
+                $body
+            }
+            OpType::U32 => {
+                type $t = u32;
+                $body
+            }
+            OpType::I32 => {
+                type $t = i32;
+                $body
+            }
+            OpType::U64 => {
+                type $t = u64;
+                $body
+            }
+            OpType::I64 => {
+                type $t = i64;
+                $body
+            }
+            OpType::Uw => {
+                type $t = UWord;
+                $body
+            }
+            OpType::Iw => {
+                type $t = IWord;
+                $body
+            }
+            OpType::U128 | OpType::I128 => return Err(ExecutionError::UnsupportedType($ot)),
+            OpType::F16 | OpType::F32 | OpType::F64 => {
+                return Err(ExecutionError::UnsupportedType($ot))
+            }
+        }
+    };
+}
+
+macro_rules! impl_cnv {
+    ($t:ty, $obj:ident.$f:ident, $uid:ident, $x:ident, $y:ident) => {
+        dispatch_op_type!(all $uid, |U| $obj.$f::<$t, U>($x, $y)?)
+    };
 }
 
 impl<'f> Executor<'f> {
@@ -94,37 +359,529 @@ impl<'f> Executor<'f> {
     }
 
     pub fn from_limits(functions: &'f [Function], stack_limit: usize, heap_limit: usize) -> Self {
+        Self::with_memory(functions, Memory::from_limits(stack_limit, heap_limit))
+    }
+
+    /// Creates the executor which owns the given memory until `into_memory`.
+    ///
+    /// The memory is used as is: the heap keeps its data and the first
+    /// call is placed after the current end of the stack.
+    pub fn with_memory(functions: &'f [Function], memory: Memory) -> Self {
         Self {
             functions,
-            memory: Memory::from_limits(stack_limit, heap_limit),
+            memory,
             program_counter: 0,
             call_stack: Vec::new(),
             prepared_call: false,
+            tail_call: false,
             parameter_ptr: 0,
             files: Files::new(),
+            checked: false,
+            canonical_nan: false,
+            max_call_depth: usize::MAX,
+            breakpoints: BTreeSet::new(),
+            break_hit: None,
+            watchpoints: Vec::new(),
+            watch_hit: None,
+            bounds: BTreeMap::new(),
+            trace: None,
+            profile: None,
+            virtual_time: 0,
+            cost_table: None,
+            consumed_cost: 0,
+            rng: 0,
+            overflow: false,
+        }
+    }
+
+    /// Sets the state of the random generator used by `rnd`.
+    /// The same seed gives the same sequence on every platform.
+    pub fn seed_rng(&mut self, seed: u64) {
+        self.rng = seed;
+    }
+
+    /// Returns the next value of the SplitMix64 generator.
+    fn next_random(&mut self) -> u64 {
+        self.rng = self.rng.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.rng;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    pub fn files(&self) -> &Files {
+        &self.files
+    }
+
+    /// Opened files of the executor. The host opens the files the program uses.
+    pub fn files_mut(&mut self) -> &mut Files {
+        &mut self.files
+    }
+
+    /// Returns the memory for inspection, see `Memory::stack_bytes`.
+    pub fn memory(&self) -> &Memory {
+        &self.memory
+    }
+
+    /// Returns the memory to reuse it after the run.
+    pub fn into_memory(self) -> Memory {
+        self.memory
+    }
+
+    /// Number of executed ops. The `Slp` op reports the wake time on this clock.
+    pub fn virtual_time(&self) -> u64 {
+        self.virtual_time
+    }
+
+    /// Sets the costs of the ops. Without the table every op costs 1.
+    pub fn set_cost_table(&mut self, table: CostTable) {
+        self.cost_table = Some(Box::new(table));
+    }
+
+    /// Total cost of the executed ops. The failed ops are not charged.
+    pub fn consumed_cost(&self) -> u64 {
+        self.consumed_cost
+    }
+
+    /// Starts counting executed ops. The counters are reset if profiling
+    /// was already enabled.
+    pub fn enable_profiling(&mut self) {
+        self.profile = Some(Box::default());
+    }
+
+    pub fn disable_profiling(&mut self) {
+        self.profile = None;
+    }
+
+    /// Returns the collected counters if profiling is enabled.
+    pub fn profile(&self) -> Option<&Profile> {
+        self.profile.as_deref()
+    }
+
+    /// Sets what the locals of a new call contain, see `StackPolicy`.
+    pub fn set_stack_policy(&mut self, policy: StackPolicy) {
+        self.memory.set_stack_policy(policy);
+    }
+
+    /// Returns the maximum sizes of the stack and the heap reached so far.
+    pub fn peak_memory(&self) -> (usize, usize) {
+        (self.memory.peak_stack(), self.memory.peak_heap())
+    }
+
+    /// Formats the memory from `start` as a hex dump, see `Memory::dump`.
+    pub fn dump_memory(&self, start: UWord, len: UWord) -> Result<String, ExecutionError> {
+        Ok(self.memory.dump(start, len)?)
+    }
+
+    /// Returns the active calls from the outermost to the innermost one.
+    pub fn stack_frames(&self) -> impl Iterator<Item = FrameInfo> + '_ {
+        self.call_stack.iter().map(|call| FrameInfo {
+            function_id: call.function_id,
+            base_ptr: call.base_ptr,
+            ret_val_ptr: call.ret_val_ptr,
+            ret_program_counter: call.ret_program_counter,
+        })
+    }
+
+    /// Forks the executor into an independent one with a copy of the memory,
+    /// the calls and the opened files. Returns `None` if any opened file
+    /// can't be duplicated. The trace callback is not copied.
+    pub fn try_clone(&self) -> Option<Self> {
+        Some(Self {
+            functions: self.functions,
+            memory: self.memory.clone(),
+            program_counter: self.program_counter,
+            call_stack: self.call_stack.clone(),
+            prepared_call: self.prepared_call,
+            tail_call: self.tail_call,
+            parameter_ptr: self.parameter_ptr,
+            files: self.files.try_clone()?,
+            checked: self.checked,
+            canonical_nan: self.canonical_nan,
+            max_call_depth: self.max_call_depth,
+            breakpoints: self.breakpoints.clone(),
+            break_hit: self.break_hit,
+            watchpoints: self.watchpoints.clone(),
+            watch_hit: self.watch_hit,
+            bounds: self.bounds.clone(),
+            trace: None,
+            profile: self.profile.clone(),
+            virtual_time: self.virtual_time,
+            cost_table: self.cost_table.clone(),
+            consumed_cost: self.consumed_cost,
+            rng: self.rng,
+            overflow: self.overflow,
+        })
+    }
+
+    pub fn snapshot(&self) -> ExecutorSnapshot {
+        let call_stack = self
+            .call_stack
+            .iter()
+            .map(|call| CallSnapshot {
+                function_id: call.function_id,
+                base_ptr: call.base_ptr,
+                ret_val_ptr: call.ret_val_ptr,
+                ret_program_counter: call.ret_program_counter,
+            })
+            .collect();
+
+        ExecutorSnapshot {
+            memory: self.memory.clone(),
+            program_counter: self.program_counter,
+            call_stack,
+            prepared_call: self.prepared_call,
+            tail_call: self.tail_call,
+            parameter_ptr: self.parameter_ptr,
+            virtual_time: self.virtual_time,
+            rng: self.rng,
+            overflow: self.overflow,
+        }
+    }
+
+    /// Restores the state captured by `snapshot`. The executor is left
+    /// unchanged if the snapshot refers to an unknown function.
+    pub fn restore(&mut self, snap: &ExecutorSnapshot) -> Result<(), ExecutionError> {
+        let functions = self.functions;
+
+        let call_stack = snap
+            .call_stack
+            .iter()
+            .map(|call| {
+                let function = functions
+                    .get(call.function_id as usize)
+                    .ok_or(ExecutionError::UnknownFunction(call.function_id))?;
+
+                Ok(FunctionCall {
+                    function,
+                    function_id: call.function_id,
+                    base_ptr: call.base_ptr,
+                    ret_val_ptr: call.ret_val_ptr,
+                    ret_program_counter: call.ret_program_counter,
+                })
+            })
+            .collect::<Result<_, ExecutionError>>()?;
+
+        self.memory = snap.memory.clone();
+        self.program_counter = snap.program_counter;
+        self.call_stack = call_stack;
+        self.prepared_call = snap.prepared_call;
+        self.tail_call = snap.tail_call;
+        self.parameter_ptr = snap.parameter_ptr;
+        self.virtual_time = snap.virtual_time;
+        self.rng = snap.rng;
+        self.overflow = snap.overflow;
+        self.break_hit = None;
+
+        Ok(())
+    }
+
+    /// Installs a callback which is called before each executed op
+    /// with the program counter and the op.
+    ///
+    /// Ops skipped by a failed condition are not traced.
+    pub fn set_trace(&mut self, trace: TraceFn<'f>) {
+        self.trace = Some(Trace(trace));
+    }
+
+    pub fn clear_trace(&mut self) {
+        self.trace = None;
+    }
+
+    /// Enables overflow checks for `Add`, `Sub`, `Mul`, `Div` and `Mod` ops.
+    ///
+    /// When set, an overflowing integer operation returns
+    /// `ExecutionError::OperationOverflow` instead of wrapping around.
+    pub fn set_checked(&mut self, checked: bool) {
+        self.checked = checked;
+    }
+
+    /// Enables replacing any NaN written by an op with the single NaN bit
+    /// pattern, so float memory compares equal bitwise after equal ops.
+    pub fn set_canonical_nan(&mut self, canonical: bool) {
+        self.canonical_nan = canonical;
+    }
+
+    /// Sets the maximum number of nested calls. A call beyond the limit
+    /// returns `ExecutionError::CallStackOverflow`.
+    pub fn set_max_call_depth(&mut self, depth: usize) {
+        self.max_call_depth = depth;
+    }
+
+    /// Reads `len` bytes of memory starting from `addr`.
+    ///
+    /// The address space is the same as the `glb` operand uses.
+    pub fn read_bytes(&self, addr: UWord, len: UWord) -> Result<&[u8], MemoryError> {
+        self.memory.slice(addr, len)
+    }
+
+    /// Reads a value of type `T` from memory at `addr`.
+    pub fn read_word<T>(&self, addr: UWord) -> Result<T, MemoryError>
+    where
+        T: Primary,
+    {
+        self.memory.get(addr)
+    }
+
+    /// Reads the value returned to `ret_val_ptr` by a finished call.
+    pub fn return_value<T>(&self, ret_val_ptr: UWord) -> Result<T, MemoryError>
+    where
+        T: Primary,
+    {
+        self.memory.get(ret_val_ptr)
+    }
+
+    /// Writes `bytes` into memory starting from `addr`.
+    #[cfg(feature = "debug")]
+    pub fn write_bytes(&mut self, addr: UWord, bytes: &[u8]) -> Result<(), MemoryError> {
+        let dest = self.memory.slice_mut(addr, bytes.len() as UWord)?;
+        dest.copy_from_slice(bytes);
+        Ok(())
+    }
+
+    /// Loads `bytes` to memory at `addr` before execution. The heap grows
+    /// to fit the bytes, the stack addresses must be allocated already.
+    pub fn load_data(&mut self, addr: UWord, bytes: &[u8]) -> Result<(), MemoryError> {
+        let len = bytes.len() as UWord;
+
+        if self.memory.region_of(addr) == Region::Heap {
+            let end = (addr - self.memory.heap_base())
+                .checked_add(len)
+                .ok_or(MemoryError::PageOverflow("heap"))?;
+
+            if end > self.memory.heap.len() {
+                self.memory.heap.expand(end - self.memory.heap.len())?;
+            }
+        }
+
+        self.memory.slice_mut(addr, len)?.copy_from_slice(bytes);
+        Ok(())
+    }
+
+    /// Loads the data section of a program, marking read-only segments.
+    pub fn load_segments(&mut self, data: &[DataSegment]) -> Result<(), MemoryError> {
+        for segment in data {
+            self.load_data(segment.addr, &segment.bytes)?;
+
+            if segment.readonly {
+                self.memory
+                    .mark_readonly(segment.addr, segment.bytes.len() as UWord);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Sets a breakpoint before the op `pc` of the function `function_id`.
+    pub fn set_breakpoint(&mut self, function_id: UWord, pc: UWord) {
+        self.breakpoints.insert((function_id, pc));
+    }
+
+    pub fn clear_breakpoint(&mut self, function_id: UWord, pc: UWord) {
+        self.breakpoints.remove(&(function_id, pc));
+    }
+
+    /// Adds a watchpoint on `len` bytes from `addr`. `run_until_break` stops
+    /// right after an op which writes to the range.
+    pub fn add_watchpoint(&mut self, addr: UWord, len: UWord) {
+        self.watchpoints.push((addr, len));
+    }
+
+    pub fn remove_watchpoint(&mut self, addr: UWord) {
+        self.watchpoints.retain(|&(a, _)| a != addr);
+    }
+
+    fn watch(&mut self, ptr: UWord, size: UWord) {
+        if self.watchpoints.is_empty() || size == 0 {
+            return;
+        }
+
+        // Compare the last bytes of the ranges, which can't overflow
+        let last = ptr.saturating_add(size - 1);
+        let hit = self
+            .watchpoints
+            .iter()
+            .find(|&&(addr, len)| len != 0 && addr <= last && ptr <= addr.saturating_add(len - 1));
+
+        if let Some(&(addr, _)) = hit {
+            self.watch_hit = Some(addr);
+        }
+    }
+
+    /// Sets the length of the array at `base` for `bnd` with an empty length operand.
+    pub fn set_bounds(&mut self, base: UWord, len: UWord) {
+        self.bounds.insert(base, len);
+    }
+
+    pub fn clear_bounds(&mut self, base: UWord) {
+        self.bounds.remove(&base);
+    }
+
+    /// Executes ops until the program ends, sleeps or reaches a breakpoint.
+    ///
+    /// The executor stops before the op at the breakpoint, so the next call
+    /// continues from this op.
+    pub fn run_until_break(&mut self) -> Result<RunOutcome, ExecutionError> {
+        loop {
+            if !self.breakpoints.is_empty() {
+                let location = (self.current_function_id()?, self.program_counter);
+
+                if self.break_hit != Some(location) && self.breakpoints.contains(&location) {
+                    self.break_hit = Some(location);
+                    let (function_id, pc) = location;
+                    return Ok(RunOutcome::Breakpoint { function_id, pc });
+                }
+            }
+
+            self.break_hit = None;
+            self.watch_hit = None;
+
+            match self.execute()? {
+                ExecutionSuccess::Ok => {
+                    if let Some(addr) = self.watch_hit.take() {
+                        return Ok(RunOutcome::Watchpoint { addr });
+                    }
+                }
+                ExecutionSuccess::End(val) => return Ok(RunOutcome::End(val)),
+                ExecutionSuccess::Sleep(val) => return Ok(RunOutcome::Sleep(val)),
+                ExecutionSuccess::Yield(val) => return Ok(RunOutcome::Yield(val)),
+            }
         }
     }
 
+    /// Moves past the current op, usually the op which returned an error.
+    pub fn skip_current_op(&mut self) -> Result<(), ExecutionError> {
+        self.current_op()?;
+        self.program_counter += 1;
+        Ok(())
+    }
+
+    /// Same as `run_until_break`, but an op which returns an error is skipped
+    /// and the error is pushed to `errors`. Stops with the error only if the
+    /// op can't be skipped, for example at the end of the program.
+    pub fn run_skipping_errors(
+        &mut self,
+        errors: &mut Vec<ExecutionError>,
+    ) -> Result<RunOutcome, ExecutionError> {
+        loop {
+            match self.run_until_break() {
+                Ok(outcome) => return Ok(outcome),
+                Err(ExecutionError::EndOfProgram) => return Err(ExecutionError::EndOfProgram),
+                Err(e) => {
+                    self.skip_current_op()?;
+                    errors.push(e);
+                }
+            }
+        }
+    }
+
+    /// Runs until the end or sleep, charging the cost of each op from the
+    /// `budget`. An op which costs more than the rest of the budget is not
+    /// executed and `ExecutionError::StepLimitExceeded` is returned.
+    pub fn run_with_limit(&mut self, budget: u64) -> Result<RunOutcome, ExecutionError> {
+        let limit = self.consumed_cost.saturating_add(budget);
+
+        loop {
+            let &op = self.current_op()?;
+
+            if self.consumed_cost.saturating_add(self.op_cost(op)?) > limit {
+                return Err(ExecutionError::StepLimitExceeded);
+            }
+
+            match self.execute()? {
+                ExecutionSuccess::Ok => (),
+                ExecutionSuccess::End(val) => return Ok(RunOutcome::End(val)),
+                ExecutionSuccess::Sleep(val) => return Ok(RunOutcome::Sleep(val)),
+                ExecutionSuccess::Yield(val) => return Ok(RunOutcome::Yield(val)),
+            }
+        }
+    }
+
+    fn op_cost(&self, op: Op) -> Result<u64, ExecutionError> {
+        let table = match &self.cost_table {
+            Some(table) => table,
+            None => return Ok(1),
+        };
+
+        let size: UWord = match op {
+            Op::Cpy(_, _, size)
+            | Op::Cmp(_, _, size)
+            | Op::Zer(_, size)
+            | Op::Fil(_, _, size)
+            | Op::Inb(_, size)
+            | Op::Outb(_, size)
+            | Op::Scn(_, _, _, size) => self.get_val(size)?,
+            _ => 0,
+        };
+
+        let bytes_cost = table.per_byte().saturating_mul(size as u64);
+        Ok(table.cost(op.op_code()).saturating_add(bytes_cost))
+    }
+
     fn app(&mut self, function_id: UWord) -> Result<(), ExecutionError> {
+        self.prepare_call(function_id, false)
+    }
+
+    /// Prepares a tail call. The parameters are passed by `par` as usual,
+    /// then `clf` replaces the current call with the prepared one.
+    /// The new call returns to the caller of the current call and
+    /// writes the return value to its return slot, so the `clf` operand
+    /// is not used.
+    fn tap(&mut self, function_id: UWord) -> Result<(), ExecutionError> {
+        self.prepare_call(function_id, true)
+    }
+
+    fn prepare_call(&mut self, function_id: UWord, tail: bool) -> Result<(), ExecutionError> {
         let f = self
             .functions
             .get(function_id as usize)
             .ok_or(ExecutionError::UnknownFunction(function_id))?;
 
+        // The tail call doesn't grow the call stack after `clf`
+        if !tail && self.call_stack.len() >= self.max_call_depth {
+            return Err(ExecutionError::CallStackOverflow);
+        }
+
+        let base_ptr = self.memory.stack.len();
+        self.memory.stack.expand(f.frame_size)?;
+
         self.call_stack.push(FunctionCall {
             function: f,
-            base_ptr: self.memory.stack.len(),
+            function_id,
+            base_ptr,
             ret_val_ptr: 0,
             ret_program_counter: 0,
         });
 
         self.prepared_call = true;
-        self.memory.stack.expand(f.frame_size)?;
+        self.tail_call = tail;
+        self.parameter_ptr = 0;
+
+        Ok(())
+    }
+
+    /// Drops the call prepared by `app` but not yet started by `clf`, for
+    /// example after an error while passing the parameters. Does nothing if
+    /// there is no prepared call.
+    pub fn abort_prepared_call(&mut self) -> Result<(), ExecutionError> {
+        if !self.prepared_call {
+            return Ok(());
+        }
+
+        let call = self.call_stack.pop().ok_or(ExecutionError::EndOfProgram)?;
+        self.memory.stack.narrow(call.function.frame_size)?;
+        self.prepared_call = false;
+        self.tail_call = false;
+        self.parameter_ptr = 0;
 
         Ok(())
     }
 
     fn clf(&mut self, ret_val_ptr: UWord) -> Result<(), ExecutionError> {
+        if self.tail_call {
+            return self.replace_call();
+        }
+
         let current_fn = self
             .call_stack
             .last_mut()
@@ -139,6 +896,75 @@ impl<'f> Executor<'f> {
         Ok(())
     }
 
+    /// Calls the function with the arguments from the host.
+    ///
+    /// Each argument is written as a little endian word to the frame of the
+    /// function, the first one at `loc(0)`, the next one at `loc(word size)`
+    /// and so on. Returns `ParameterOverflow` if the arguments don't fit in
+    /// the frame. The return value is written at `ret_val_ptr`.
+    pub fn invoke(
+        &mut self,
+        function_id: UWord,
+        args: &[UWord],
+        ret_val_ptr: UWord,
+    ) -> Result<(), ExecutionError> {
+        const WORD_SIZE: UWord = core::mem::size_of::<UWord>() as UWord;
+
+        let f = self
+            .functions
+            .get(function_id as usize)
+            .ok_or(ExecutionError::UnknownFunction(function_id))?;
+
+        let args_size = args.len().checked_mul(WORD_SIZE as usize);
+        let frame_size = usize::try_from(f.frame_size).unwrap_or(usize::MAX);
+        if !matches!(args_size, Some(size) if size <= frame_size) {
+            return Err(ExecutionError::ParameterOverflow);
+        }
+
+        self.app(function_id)?;
+        let base_ptr = self.call_stack.last().unwrap().base_ptr;
+
+        for (i, &arg) in args.iter().enumerate() {
+            let ptr = base_ptr.wrapping_add(i as UWord * WORD_SIZE);
+
+            if let Err(e) = self.memory.set(ptr, arg) {
+                self.abort_prepared_call()?;
+                return Err(e.into());
+            }
+        }
+
+        self.clf(ret_val_ptr)
+    }
+
+    /// Moves the prepared frame in place of the current one.
+    fn replace_call(&mut self) -> Result<(), ExecutionError> {
+        if self.call_stack.len() < 2 {
+            return Err(ExecutionError::EndOfProgram);
+        }
+
+        let callee = self.call_stack.pop().unwrap();
+        let caller = self.call_stack.pop().unwrap();
+
+        self.memory
+            .stack
+            .memmove(caller.base_ptr, callee.base_ptr, callee.function.frame_size)?;
+        self.memory.stack.narrow(caller.function.frame_size)?;
+
+        self.call_stack.push(FunctionCall {
+            base_ptr: caller.base_ptr,
+            ret_val_ptr: caller.ret_val_ptr,
+            ret_program_counter: caller.ret_program_counter,
+            ..callee
+        });
+
+        self.prepared_call = false;
+        self.tail_call = false;
+        self.program_counter = 0;
+        self.parameter_ptr = 0;
+
+        Ok(())
+    }
+
     pub fn call(&mut self, function_id: UWord, ret_val_ptr: UWord) -> Result<(), ExecutionError> {
         self.app(function_id)?;
         self.clf(ret_val_ptr)
@@ -153,7 +979,7 @@ impl<'f> Executor<'f> {
         Ok(())
     }
 
-    fn current_call(&self) -> Result<&FunctionCall, ExecutionError> {
+    fn current_call(&self) -> Result<&FunctionCall<'_>, ExecutionError> {
         let call = if self.prepared_call {
             self.call_stack.get(self.call_stack.len().wrapping_sub(2))
         } else {
@@ -163,6 +989,22 @@ impl<'f> Executor<'f> {
         call.ok_or(ExecutionError::EndOfProgram)
     }
 
+    fn current_call_mut(&mut self) -> Result<&mut FunctionCall<'f>, ExecutionError> {
+        let idx = if self.prepared_call {
+            self.call_stack.len().wrapping_sub(2)
+        } else {
+            self.call_stack.len().wrapping_sub(1)
+        };
+
+        self.call_stack
+            .get_mut(idx)
+            .ok_or(ExecutionError::EndOfProgram)
+    }
+
+    fn current_function_id(&self) -> Result<UWord, ExecutionError> {
+        Ok(self.current_call()?.function_id)
+    }
+
     fn current_op(&self) -> Result<&Op, ExecutionError> {
         self.current_call()?
             .function
@@ -171,12 +1013,23 @@ impl<'f> Executor<'f> {
             .ok_or(ExecutionError::EndOfProgram)
     }
 
+    /// Skips the rest of the condition chain and the guarded op.
     fn pass_condition(&mut self) -> Result<(), ExecutionError> {
         loop {
             self.program_counter += 1;
-            let op = self.current_op()?;
+            let op = match self.current_op() {
+                Err(ExecutionError::EndOfProgram) => {
+                    return Err(ExecutionError::MalformedCondition)
+                }
+                op => op?,
+            };
+            let conditional = op.is_conditional();
+
+            if let Some(profile) = &mut self.profile {
+                profile.count_skipped();
+            }
 
-            if !op.is_conditional() {
+            if !conditional {
                 self.program_counter += 1;
                 break Ok(());
             }
@@ -207,37 +1060,50 @@ impl<'f> Executor<'f> {
             Operand::Val(val) => T::from_word(val),
             Operand::Ref(var) => T::from_word(self.current_call()?.base_ptr.wrapping_add(var)),
             Operand::Glb(ptr) => self.memory.get(ptr)?,
+            Operand::GlbInd(ptr) => self.memory.get(self.global_ptr(ptr)?)?,
             Operand::Emp => return Err(ExecutionError::IncorrectOperation(*self.current_op()?)),
         })
     }
 
+    fn global_ptr(&self, ptr: UWord) -> Result<UWord, ExecutionError> {
+        match self.memory.get(ptr)? {
+            0 => Err(ExecutionError::NullPointerDereference),
+            ptr => Ok(ptr),
+        }
+    }
+
     fn set_val<T>(&mut self, operand: Operand, val: T) -> Result<(), ExecutionError>
     where
         T: Primary,
     {
-        Ok(match operand {
-            Operand::Loc(loc) => self
-                .memory
-                .set(self.current_call()?.base_ptr.wrapping_add(loc), val)?,
+        let val = if self.canonical_nan {
+            val.canonical()
+        } else {
+            val
+        };
+
+        let ptr = match operand {
+            Operand::Loc(loc) => self.current_call()?.base_ptr.wrapping_add(loc),
             Operand::Ind(ptr) => {
                 if ptr == 0 {
                     return Err(ExecutionError::NullPointerDereference);
                 } else {
-                    self.memory.set(
-                        self.memory
-                            .get(self.current_call()?.base_ptr.wrapping_add(ptr))?,
-                        val,
-                    )?
+                    self.memory
+                        .get(self.current_call()?.base_ptr.wrapping_add(ptr))?
                 }
             }
-            Operand::Ret(ret) => self
-                .memory
-                .set(self.current_call()?.ret_val_ptr.wrapping_add(ret), val)?,
+            Operand::Ret(ret) => self.current_call()?.ret_val_ptr.wrapping_add(ret),
             Operand::Val(_) => return Err(ExecutionError::IncorrectOperation(*self.current_op()?)),
             Operand::Ref(_) => return Err(ExecutionError::IncorrectOperation(*self.current_op()?)),
-            Operand::Glb(ptr) => self.memory.set(ptr, val)?,
+            Operand::Glb(ptr) => ptr,
+            Operand::GlbInd(ptr) => self.global_ptr(ptr)?,
             Operand::Emp => return Err(ExecutionError::IncorrectOperation(*self.current_op()?)),
-        })
+        };
+
+        self.memory.set(ptr, val)?;
+        self.watch(ptr, T::SIZE as UWord);
+
+        Ok(())
     }
 
     fn read_un_operand(&self, un: UnOp) -> Result<Operand, ExecutionError> {
@@ -289,24 +1155,43 @@ impl<'f> Executor<'f> {
     fn update_bin_division<T, F>(&mut self, bin: BinOp, f: F) -> Result<(), ExecutionError>
     where
         T: Primary + PartialEq,
-        F: FnOnce(T, T) -> T,
+        F: FnOnce(T, T) -> Option<T>,
     {
-        let mut div_by_zero = false;
-
-        let res = self.update_bin::<T, T, _>(bin, |x, y| {
-            if y == T::zero() {
-                div_by_zero = true;
-                T::zero()
-            } else {
-                f(x, y)
-            }
-        });
+        let (left, right) = self.read_bin_operands(bin)?;
+        let y = self.get_val(right)?;
 
-        if div_by_zero {
+        if y == T::zero() {
             return Err(ExecutionError::DivisionByZero);
         }
 
-        res
+        let val = f(self.get_val(left)?, y).ok_or(ExecutionError::OperationOverflow)?;
+        self.set_val(left, val)
+    }
+
+    /// Updates the left operand by the wrapping op and sets the overflow flag
+    /// if the checked op fails. In the checked mode the overflow is an error.
+    fn update_bin_overflowing<T>(
+        &mut self,
+        bin: BinOp,
+        wrapping: fn(T, T) -> T,
+        checked: fn(T, T) -> Option<T>,
+    ) -> Result<(), ExecutionError>
+    where
+        T: Primary + Copy,
+    {
+        let (left, right) = self.read_bin_operands(bin)?;
+        let x = self.get_val(left)?;
+        let y = self.get_val(right)?;
+
+        let (val, overflow) = match checked(x, y) {
+            Some(val) => (val, false),
+            None if self.checked => return Err(ExecutionError::OperationOverflow),
+            None => (wrapping(x, y), true),
+        };
+
+        self.set_val(left, val)?;
+        self.overflow = overflow;
+        Ok(())
     }
 
     fn make_offset(&self, a: Operand, offset: Operand) -> Result<Operand, ExecutionError> {
@@ -329,85 +1214,332 @@ impl<'f> Executor<'f> {
         self.set_val(left, U::convert(self.get_val(right)?))
     }
 
+    fn exec_bit<T, U>(&mut self, left: Operand, right: Operand) -> Result<(), ExecutionError>
+    where
+        T: Primary,
+        U: Primary,
+    {
+        let mut buf = [0; 16];
+        self.get_val::<T>(right)?.write_le(&mut buf);
+        self.set_val(left, U::read_le(&buf))
+    }
+
+    fn exec_cnr<T, U>(&mut self, left: Operand, right: Operand) -> Result<(), ExecutionError>
+    where
+        T: Primary,
+        U: Convert<T>,
+    {
+        self.set_val(left, U::convert_round(self.get_val(right)?))
+    }
+
     fn exec_add<T>(&mut self, bin: BinOp) -> Result<(), ExecutionError>
     where
-        T: Add,
+        T: Add + Copy,
     {
-        self.update_bin::<T, T, _>(bin, |x, y| x.wrapping(y))
+        self.update_bin_overflowing::<T>(bin, T::wrapping, T::checked)
     }
 
     fn exec_sub<T>(&mut self, bin: BinOp) -> Result<(), ExecutionError>
     where
-        T: Sub,
+        T: Sub + Copy,
     {
-        self.update_bin::<T, T, _>(bin, |x, y| x.wrapping(y))
+        self.update_bin_overflowing::<T>(bin, T::wrapping, T::checked)
     }
 
     fn exec_mul<T>(&mut self, bin: BinOp) -> Result<(), ExecutionError>
+    where
+        T: Mul + Copy,
+    {
+        self.update_bin_overflowing::<T>(bin, T::wrapping, T::checked)
+    }
+
+    /// Adds with the overflow flag as the carry in and sets the flag
+    /// to the carry out. The carry is never an error in the checked mode.
+    fn exec_adc<T>(&mut self, bin: BinOp) -> Result<(), ExecutionError>
+    where
+        T: Carry,
+    {
+        let (left, right) = self.read_bin_operands(bin)?;
+        let (val, carry) = T::add_carry(self.get_val(left)?, self.get_val(right)?, self.overflow);
+
+        self.set_val(left, val)?;
+        self.overflow = carry;
+        Ok(())
+    }
+
+    /// Same as `exec_adc`, but subtracts with the flag as the borrow.
+    fn exec_sbb<T>(&mut self, bin: BinOp) -> Result<(), ExecutionError>
+    where
+        T: Carry,
+    {
+        let (left, right) = self.read_bin_operands(bin)?;
+        let (val, borrow) = T::sub_borrow(self.get_val(left)?, self.get_val(right)?, self.overflow);
+
+        self.set_val(left, val)?;
+        self.overflow = borrow;
+        Ok(())
+    }
+
+    fn exec_abs<T>(&mut self, un: UnOp) -> Result<(), ExecutionError>
+    where
+        T: Abs,
+    {
+        self.update_un::<T, T, _>(un, |x| x.abs())
+    }
+
+    fn exec_min<T>(&mut self, bin: BinOp) -> Result<(), ExecutionError>
+    where
+        T: MinMax,
+    {
+        self.update_bin::<T, T, _>(bin, |x, y| x.minimum(y))
+    }
+
+    fn exec_max<T>(&mut self, bin: BinOp) -> Result<(), ExecutionError>
+    where
+        T: MinMax,
+    {
+        self.update_bin::<T, T, _>(bin, |x, y| x.maximum(y))
+    }
+
+    fn exec_ads<T>(&mut self, bin: BinOp) -> Result<(), ExecutionError>
+    where
+        T: Add,
+    {
+        self.update_bin::<T, T, _>(bin, |x, y| x.saturating(y))
+    }
+
+    fn exec_sbs<T>(&mut self, bin: BinOp) -> Result<(), ExecutionError>
+    where
+        T: Sub,
+    {
+        self.update_bin::<T, T, _>(bin, |x, y| x.saturating(y))
+    }
+
+    fn exec_mls<T>(&mut self, bin: BinOp) -> Result<(), ExecutionError>
     where
         T: Mul,
     {
-        self.update_bin::<T, T, _>(bin, |x, y| x.wrapping(y))
+        self.update_bin::<T, T, _>(bin, |x, y| x.saturating(y))
     }
 
     fn exec_div<T>(&mut self, bin: BinOp) -> Result<(), ExecutionError>
     where
         T: Div + PartialEq,
     {
-        self.update_bin_division::<T, _>(bin, |x, y| x.wrapping(y))
+        // The signed `MIN / -1` case wraps around unless the overflow check is set.
+        if self.checked {
+            self.update_bin_division::<T, _>(bin, |x, y| x.checked(y))
+        } else {
+            self.update_bin_division::<T, _>(bin, |x, y| Some(x.wrapping(y)))
+        }
     }
 
     fn exec_mod<T>(&mut self, bin: BinOp) -> Result<(), ExecutionError>
     where
         T: Rem + PartialEq,
     {
-        self.update_bin_division::<T, _>(bin, |x, y| x.wrapping(y))
+        // The signed `MIN / -1` case wraps around unless the overflow check is set.
+        if self.checked {
+            self.update_bin_division::<T, _>(bin, |x, y| x.checked(y))
+        } else {
+            self.update_bin_division::<T, _>(bin, |x, y| Some(x.wrapping(y)))
+        }
     }
 
-    fn exec_shl<T>(&mut self, x: Operand, y: Operand) -> Result<(), ExecutionError>
+    fn exec_shl<T>(&mut self, bin: BinOp) -> Result<(), ExecutionError>
     where
         T: Shl,
     {
+        let (x, y) = self.read_bin_operands(bin)?;
         let x_val: T = self.get_val(x)?;
         let y_val: u8 = self.get_val(y)?;
         self.set_val(x, x_val.wrapping(y_val))
     }
 
-    fn exec_shr<T>(&mut self, x: Operand, y: Operand) -> Result<(), ExecutionError>
+    fn exec_shr<T>(&mut self, bin: BinOp) -> Result<(), ExecutionError>
     where
         T: Shr,
     {
+        let (x, y) = self.read_bin_operands(bin)?;
         let x_val: T = self.get_val(x)?;
         let y_val: u8 = self.get_val(y)?;
         self.set_val(x, x_val.wrapping(y_val))
     }
 
+    fn exec_sel<T>(
+        &mut self,
+        x: Operand,
+        y: Operand,
+        z: Operand,
+        w: Operand,
+    ) -> Result<(), ExecutionError>
+    where
+        T: Primary + PartialEq,
+    {
+        let cond: T = self.get_val(y)?;
+        let a: T = self.get_val(z)?;
+        let b: T = self.get_val(w)?;
+        self.set_val(x, if cond != T::zero() { a } else { b })
+    }
+
+    /// Writes the value in decimal to the current file.
+    fn exec_out_fmt<T>(&mut self, x: Operand) -> Result<(), ExecutionError>
+    where
+        T: Primary + core::fmt::Display,
+    {
+        use core::fmt::Write;
+
+        // Enough for any value, the longest is a negative subnormal f64 of 327 bytes
+        const BUF_SIZE: usize = 512;
+
+        struct Cursor {
+            buf: [u8; BUF_SIZE],
+            len: usize,
+        }
+
+        impl Write for Cursor {
+            fn write_str(&mut self, s: &str) -> core::fmt::Result {
+                let end = self.len + s.len();
+                self.buf
+                    .get_mut(self.len..end)
+                    .ok_or(core::fmt::Error)?
+                    .copy_from_slice(s.as_bytes());
+                self.len = end;
+                Ok(())
+            }
+        }
+
+        let val: T = self.get_val(x)?;
+        let mut cursor = Cursor {
+            buf: [0; BUF_SIZE],
+            len: 0,
+        };
+        let res = write!(cursor, "{}", val);
+        debug_assert!(res.is_ok(), "the formatted value doesn't fit the buffer");
+
+        self.files.write_all(&cursor.buf[..cursor.len])?;
+        Ok(())
+    }
+
+    fn exec_rnd<T>(&mut self, x: Operand) -> Result<(), ExecutionError>
+    where
+        T: Random,
+    {
+        let val = T::from_random(self.next_random());
+        self.set_val(x, val)
+    }
+
+    fn exec_rol<T>(&mut self, x: Operand, y: Operand) -> Result<(), ExecutionError>
+    where
+        T: Rol,
+    {
+        let x_val: T = self.get_val(x)?;
+        let y_val: u8 = self.get_val(y)?;
+        self.set_val(x, x_val.rotate(y_val))
+    }
+
+    fn exec_ror<T>(&mut self, x: Operand, y: Operand) -> Result<(), ExecutionError>
+    where
+        T: Ror,
+    {
+        let x_val: T = self.get_val(x)?;
+        let y_val: u8 = self.get_val(y)?;
+        self.set_val(x, x_val.rotate(y_val))
+    }
+
+    fn exec_swp<T>(&mut self, x: Operand, y: Operand) -> Result<(), ExecutionError>
+    where
+        T: Primary,
+    {
+        // Check both destinations before writing to leave them untouched on error
+        if let Operand::Val(_) | Operand::Ref(_) | Operand::Emp = y {
+            return Err(ExecutionError::IncorrectOperation(*self.current_op()?));
+        }
+
+        let x_val: T = self.get_val(x)?;
+        let y_val: T = self.get_val(y)?;
+        self.set_val(x, y_val)?;
+        self.set_val(y, x_val)
+    }
+
+    fn exec_cas<T>(
+        &mut self,
+        dest: Operand,
+        cmp: Operand,
+        new: Operand,
+    ) -> Result<(), ExecutionError>
+    where
+        T: Primary + PartialEq,
+    {
+        // The success flag is written to the compared value, so it must be
+        // writable and must not overwrite the swapped one
+        if let Operand::Val(_) | Operand::Ref(_) | Operand::Emp = cmp {
+            return Err(ExecutionError::IncorrectOperation(*self.current_op()?));
+        }
+
+        if dest == cmp {
+            return Err(ExecutionError::IncorrectOperation(*self.current_op()?));
+        }
+
+        let equal = self.exec_ife::<T>(BinOp::new(dest, cmp))?;
+
+        if equal {
+            let val: T = self.get_val(new)?;
+            self.set_val(dest, val)?;
+        }
+
+        // The success flag of the op type replaces the compared value
+        let flag = if equal { T::one() } else { T::zero() };
+        self.set_val(cmp, flag)
+    }
+
     fn exec_and<T>(&mut self, bin: BinOp) -> Result<(), ExecutionError>
     where
-        T: Primary + std::ops::BitAnd<Output = T>,
+        T: Primary + core::ops::BitAnd<Output = T>,
     {
         self.update_bin::<T, T, _>(bin, |x, y| x & y)
     }
 
-    fn exec_or<T>(&mut self, bin: BinOp) -> Result<(), ExecutionError>
+    fn exec_or<T>(&mut self, bin: BinOp) -> Result<(), ExecutionError>
+    where
+        T: Primary + core::ops::BitOr<Output = T>,
+    {
+        self.update_bin::<T, T, _>(bin, |x, y| x | y)
+    }
+
+    fn exec_xor<T>(&mut self, bin: BinOp) -> Result<(), ExecutionError>
+    where
+        T: Primary + core::ops::BitXor<Output = T>,
+    {
+        self.update_bin::<T, T, _>(bin, |x, y| x ^ y)
+    }
+
+    fn exec_not<T>(&mut self, un: UnOp) -> Result<(), ExecutionError>
+    where
+        T: Primary + core::ops::Not<Output = T>,
+    {
+        self.update_un::<T, T, _>(un, |y| !y)
+    }
+
+    fn exec_cnt<T>(&mut self, un: UnOp) -> Result<(), ExecutionError>
     where
-        T: Primary + std::ops::BitOr<Output = T>,
+        T: Count,
     {
-        self.update_bin::<T, T, _>(bin, |x, y| x | y)
+        self.update_un::<T, T, _>(un, |x| x.count_ones())
     }
 
-    fn exec_xor<T>(&mut self, bin: BinOp) -> Result<(), ExecutionError>
+    fn exec_clz<T>(&mut self, un: UnOp) -> Result<(), ExecutionError>
     where
-        T: Primary + std::ops::BitXor<Output = T>,
+        T: Count,
     {
-        self.update_bin::<T, T, _>(bin, |x, y| x ^ y)
+        self.update_un::<T, T, _>(un, |x| x.leading_zeros())
     }
 
-    fn exec_not<T>(&mut self, un: UnOp) -> Result<(), ExecutionError>
+    fn exec_ctz<T>(&mut self, un: UnOp) -> Result<(), ExecutionError>
     where
-        T: Primary + std::ops::Not<Output = T>,
+        T: Count,
     {
-        self.update_un::<T, T, _>(un, |y| !y)
+        self.update_un::<T, T, _>(un, |x| x.trailing_zeros())
     }
 
     fn exec_neg<T>(&mut self, un: UnOp) -> Result<(), ExecutionError>
@@ -431,6 +1563,25 @@ impl<'f> Executor<'f> {
         self.update_un::<T, T, _>(un, |x| x.wrapping())
     }
 
+    fn exec_sqr<T>(&mut self, un: UnOp) -> Result<(), ExecutionError>
+    where
+        T: Float,
+    {
+        self.update_un::<T, T, _>(un, |x| x.sqrt())
+    }
+
+    fn exec_fun<T>(&mut self, un: UnOp, math_fn: MathFn) -> Result<(), ExecutionError>
+    where
+        T: Float,
+    {
+        self.update_un::<T, T, _>(un, |x| match math_fn {
+            MathFn::Sin => x.sin(),
+            MathFn::Cos => x.cos(),
+            MathFn::Ln => x.ln(),
+            MathFn::Exp => x.exp(),
+        })
+    }
+
     fn exec_ife<T>(&self, bin: BinOp) -> Result<bool, ExecutionError>
     where
         T: Primary + PartialEq,
@@ -439,6 +1590,14 @@ impl<'f> Executor<'f> {
         Ok(self.get_val::<T>(left)? == self.get_val::<T>(right)?)
     }
 
+    fn exec_ifn<T>(&mut self, un: UnOp) -> Result<bool, ExecutionError>
+    where
+        T: Float,
+    {
+        Ok(self.get_un::<T>(un)?.is_nan())
+    }
+
+    /// Any comparison with NaN is false, so the condition is not passed.
     fn exec_ifl<T>(&self, bin: BinOp) -> Result<bool, ExecutionError>
     where
         T: Primary + PartialOrd,
@@ -447,6 +1606,7 @@ impl<'f> Executor<'f> {
         Ok(self.get_val::<T>(left)? < self.get_val::<T>(right)?)
     }
 
+    /// Any comparison with NaN is false, so the condition is not passed.
     fn exec_ifg<T>(&self, bin: BinOp) -> Result<bool, ExecutionError>
     where
         T: Primary + PartialOrd,
@@ -481,7 +1641,7 @@ impl<'f> Executor<'f> {
 
     fn exec_ifa<T>(&self, bin: BinOp) -> Result<bool, ExecutionError>
     where
-        T: Primary + PartialEq + std::ops::BitAnd<Output = T>,
+        T: Primary + PartialEq + core::ops::BitAnd<Output = T>,
     {
         let (left, right) = self.read_bin_operands(bin)?;
         Ok(self.get_val::<T>(left)? & self.get_val::<T>(right)? != T::zero())
@@ -489,7 +1649,7 @@ impl<'f> Executor<'f> {
 
     fn exec_ifo<T>(&self, bin: BinOp) -> Result<bool, ExecutionError>
     where
-        T: Primary + PartialEq + std::ops::BitOr<Output = T>,
+        T: Primary + PartialEq + core::ops::BitOr<Output = T>,
     {
         let (left, right) = self.read_bin_operands(bin)?;
         Ok(self.get_val::<T>(left)? | self.get_val::<T>(right)? != T::zero())
@@ -497,7 +1657,7 @@ impl<'f> Executor<'f> {
 
     fn exec_ifx<T>(&self, bin: BinOp) -> Result<bool, ExecutionError>
     where
-        T: Primary + PartialEq + std::ops::BitXor<Output = T>,
+        T: Primary + PartialEq + core::ops::BitXor<Output = T>,
     {
         let (left, right) = self.read_bin_operands(bin)?;
         Ok(self.get_val::<T>(left)? ^ self.get_val::<T>(right)? != T::zero())
@@ -505,7 +1665,7 @@ impl<'f> Executor<'f> {
 
     fn exec_ina<T>(&self, bin: BinOp) -> Result<bool, ExecutionError>
     where
-        T: Primary + PartialEq + std::ops::BitAnd<Output = T>,
+        T: Primary + PartialEq + core::ops::BitAnd<Output = T>,
     {
         let (left, right) = self.read_bin_operands(bin)?;
         Ok(self.get_val::<T>(left)? & self.get_val::<T>(right)? == T::zero())
@@ -513,7 +1673,7 @@ impl<'f> Executor<'f> {
 
     fn exec_ino<T>(&self, bin: BinOp) -> Result<bool, ExecutionError>
     where
-        T: Primary + PartialEq + std::ops::BitOr<Output = T>,
+        T: Primary + PartialEq + core::ops::BitOr<Output = T>,
     {
         let (left, right) = self.read_bin_operands(bin)?;
         Ok(self.get_val::<T>(left)? | self.get_val::<T>(right)? == T::zero())
@@ -521,7 +1681,7 @@ impl<'f> Executor<'f> {
 
     fn exec_inx<T>(&self, bin: BinOp) -> Result<bool, ExecutionError>
     where
-        T: Primary + PartialEq + std::ops::BitXor<Output = T>,
+        T: Primary + PartialEq + core::ops::BitXor<Output = T>,
     {
         let (left, right) = self.read_bin_operands(bin)?;
         Ok(self.get_val::<T>(left)? ^ self.get_val::<T>(right)? == T::zero())
@@ -533,11 +1693,25 @@ impl<'f> Executor<'f> {
     {
         let frame_size = self.current_call()?.function.frame_size;
         let parameter_loc = self.parameter_ptr.wrapping_add(frame_size);
-        self.parameter_ptr = self.parameter_ptr.wrapping_add(T::SIZE as UWord);
+
+        if !self.prepared_call {
+            return Err(ExecutionError::NoPreparedCall);
+        }
+
+        // The parameters must fit in the frame of the prepared call
+        let callee_frame_size = self.call_stack.last().unwrap().function.frame_size;
+
+        let end = self.parameter_ptr.checked_add(T::SIZE as UWord);
+        if !matches!(end, Some(end) if end <= callee_frame_size) {
+            return Err(ExecutionError::ParameterOverflow);
+        }
 
         let val = self.get_un(un)?;
         self.set_val::<T>(Operand::Loc(parameter_loc), val)?;
 
+        // Advance only after success, so a failed `par` can be retried
+        self.parameter_ptr = self.parameter_ptr.wrapping_add(T::SIZE as UWord);
+
         Ok(())
     }
 
@@ -549,12 +1723,38 @@ impl<'f> Executor<'f> {
         self.set_val::<T>(Operand::Ret(0), self.get_val(right)?)
     }
 
+    /// Executes the current op. The op is charged and advances the virtual
+    /// time only if it succeeds.
     pub fn execute(&mut self) -> Executed {
+        let &op = self.current_op()?;
+
+        if let Some(Trace(trace)) = &mut self.trace {
+            trace(self.program_counter, &op);
+        }
+
+        if let Some(profile) = &mut self.profile {
+            profile.count_op(op.op_code());
+        }
+
+        // The cost is computed before the op changes its size operand
+        let cost = self.op_cost(op)?;
+        let (virtual_time, consumed_cost) = (self.virtual_time, self.consumed_cost);
+        self.virtual_time += 1;
+        self.consumed_cost = self.consumed_cost.saturating_add(cost);
+
+        let res = self.execute_op(op);
+        if res.is_err() {
+            self.virtual_time = virtual_time;
+            self.consumed_cost = consumed_cost;
+        }
+
+        res
+    }
+
+    fn execute_op(&mut self, op: Op) -> Executed {
         use Op::*;
         use OpType::*;
 
-        let &op = self.current_op()?;
-
         let res = match op {
             Nop => Ok(ExecutionSuccess::Ok),
             End(x) => {
@@ -562,294 +1762,114 @@ impl<'f> Executor<'f> {
                 Ok(ExecutionSuccess::End(val))
             }
             Slp(x) => {
+                let val: UWord = self.get_val(x)?;
+                Ok(ExecutionSuccess::Sleep(
+                    self.virtual_time.saturating_add(val as u64),
+                ))
+            }
+            Yld(x) => {
                 let val = self.get_val(x)?;
-                Ok(ExecutionSuccess::Sleep(val))
+                Ok(ExecutionSuccess::Yield(val))
             }
             Set(bin, ot) => {
-                match ot {
-                    U8 => self.exec_set::<u8>(bin)?,
-                    I8 => self.exec_set::<i8>(bin)?,
-                    U16 => self.exec_set::<u16>(bin)?,
-                    I16 => self.exec_set::<i16>(bin)?,
-                    U32 => self.exec_set::<u32>(bin)?,
-                    I32 => self.exec_set::<i32>(bin)?,
-                    U64 => self.exec_set::<u64>(bin)?,
-                    I64 => self.exec_set::<i64>(bin)?,
-                    Uw => self.exec_set::<UWord>(bin)?,
-                    Iw => self.exec_set::<IWord>(bin)?,
-                    F32 => self.exec_set::<f32>(bin)?,
-                    F64 => self.exec_set::<f64>(bin)?,
-                }
+                dispatch_op_type!(all ot, |T| self.exec_set::<T>(bin)?);
 
                 Ok(ExecutionSuccess::Ok)
             }
             Cnv(x, y, t, u) => {
-                match t {
-                    U8 => impl_cnv!(u8, self, u, x, y),
-                    I8 => impl_cnv!(i8, self, u, x, y),
-                    U16 => impl_cnv!(u16, self, u, x, y),
-                    I16 => impl_cnv!(i16, self, u, x, y),
-                    U32 => impl_cnv!(u32, self, u, x, y),
-                    I32 => impl_cnv!(i32, self, u, x, y),
-                    U64 => impl_cnv!(u64, self, u, x, y),
-                    I64 => impl_cnv!(i64, self, u, x, y),
-                    Uw => impl_cnv!(UWord, self, u, x, y),
-                    Iw => impl_cnv!(IWord, self, u, x, y),
-                    F32 => impl_cnv!(f32, self, u, x, y),
-                    F64 => impl_cnv!(f64, self, u, x, y),
-                }
+                dispatch_op_type!(all t, |T| impl_cnv!(T, self.exec_cnv, u, x, y));
+
+                Ok(ExecutionSuccess::Ok)
+            }
+            Cnr(x, y, t, u) => {
+                dispatch_op_type!(all t, |T| impl_cnv!(T, self.exec_cnr, u, x, y));
+
+                Ok(ExecutionSuccess::Ok)
+            }
+            Bit(x, y, t, u) => {
+                dispatch_op_type!(all t, |T| impl_cnv!(T, self.exec_bit, u, x, y));
 
                 Ok(ExecutionSuccess::Ok)
             }
             Add(bin, ot) => {
-                match ot {
-                    U8 => self.exec_add::<u8>(bin)?,
-                    I8 => self.exec_add::<i8>(bin)?,
-                    U16 => self.exec_add::<u16>(bin)?,
-                    I16 => self.exec_add::<i16>(bin)?,
-                    U32 => self.exec_add::<u32>(bin)?,
-                    I32 => self.exec_add::<i32>(bin)?,
-                    U64 => self.exec_add::<u64>(bin)?,
-                    I64 => self.exec_add::<i64>(bin)?,
-                    Uw => self.exec_add::<UWord>(bin)?,
-                    Iw => self.exec_add::<IWord>(bin)?,
-                    F32 => self.exec_add::<f32>(bin)?,
-                    F64 => self.exec_add::<f64>(bin)?,
-                }
+                dispatch_op_type!(all ot, |T| self.exec_add::<T>(bin)?);
 
                 Ok(ExecutionSuccess::Ok)
             }
             Sub(bin, ot) => {
-                match ot {
-                    U8 => self.exec_sub::<u8>(bin)?,
-                    I8 => self.exec_sub::<i8>(bin)?,
-                    U16 => self.exec_sub::<u16>(bin)?,
-                    I16 => self.exec_sub::<i16>(bin)?,
-                    U32 => self.exec_sub::<u32>(bin)?,
-                    I32 => self.exec_sub::<i32>(bin)?,
-                    U64 => self.exec_sub::<u64>(bin)?,
-                    I64 => self.exec_sub::<i64>(bin)?,
-                    Uw => self.exec_sub::<UWord>(bin)?,
-                    Iw => self.exec_sub::<IWord>(bin)?,
-                    F32 => self.exec_sub::<f32>(bin)?,
-                    F64 => self.exec_sub::<f64>(bin)?,
-                }
+                dispatch_op_type!(all ot, |T| self.exec_sub::<T>(bin)?);
 
                 Ok(ExecutionSuccess::Ok)
             }
             Mul(bin, ot) => {
-                match ot {
-                    U8 => self.exec_mul::<u8>(bin)?,
-                    I8 => self.exec_mul::<i8>(bin)?,
-                    U16 => self.exec_mul::<u16>(bin)?,
-                    I16 => self.exec_mul::<i16>(bin)?,
-                    U32 => self.exec_mul::<u32>(bin)?,
-                    I32 => self.exec_mul::<i32>(bin)?,
-                    U64 => self.exec_mul::<u64>(bin)?,
-                    I64 => self.exec_mul::<i64>(bin)?,
-                    Uw => self.exec_mul::<UWord>(bin)?,
-                    Iw => self.exec_mul::<IWord>(bin)?,
-                    F32 => self.exec_mul::<f32>(bin)?,
-                    F64 => self.exec_mul::<f64>(bin)?,
-                }
+                dispatch_op_type!(all ot, |T| self.exec_mul::<T>(bin)?);
 
                 Ok(ExecutionSuccess::Ok)
             }
             Div(bin, ot) => {
-                match ot {
-                    U8 => self.exec_div::<u8>(bin)?,
-                    I8 => self.exec_div::<i8>(bin)?,
-                    U16 => self.exec_div::<u16>(bin)?,
-                    I16 => self.exec_div::<i16>(bin)?,
-                    U32 => self.exec_div::<u32>(bin)?,
-                    I32 => self.exec_div::<i32>(bin)?,
-                    U64 => self.exec_div::<u64>(bin)?,
-                    I64 => self.exec_div::<i64>(bin)?,
-                    Uw => self.exec_div::<UWord>(bin)?,
-                    Iw => self.exec_div::<IWord>(bin)?,
-                    F32 => self.exec_div::<f32>(bin)?,
-                    F64 => self.exec_div::<f64>(bin)?,
-                }
+                dispatch_op_type!(ot, |T| self.exec_div::<T>(bin)?);
 
                 Ok(ExecutionSuccess::Ok)
             }
             Mod(bin, ot) => {
-                match ot {
-                    U8 => self.exec_mod::<u8>(bin)?,
-                    I8 => self.exec_mod::<i8>(bin)?,
-                    U16 => self.exec_mod::<u16>(bin)?,
-                    I16 => self.exec_mod::<i16>(bin)?,
-                    U32 => self.exec_mod::<u32>(bin)?,
-                    I32 => self.exec_mod::<i32>(bin)?,
-                    U64 => self.exec_mod::<u64>(bin)?,
-                    I64 => self.exec_mod::<i64>(bin)?,
-                    Uw => self.exec_mod::<UWord>(bin)?,
-                    Iw => self.exec_mod::<IWord>(bin)?,
-                    F32 => self.exec_mod::<f32>(bin)?,
-                    F64 => self.exec_mod::<f64>(bin)?,
-                }
+                dispatch_op_type!(ot, |T| self.exec_mod::<T>(bin)?);
 
                 Ok(ExecutionSuccess::Ok)
             }
-            Shl(x, y, ot) => {
-                match ot {
-                    U8 => self.exec_shl::<u8>(x, y)?,
-                    I8 => self.exec_shl::<i8>(x, y)?,
-                    U16 => self.exec_shl::<u16>(x, y)?,
-                    I16 => self.exec_shl::<i16>(x, y)?,
-                    U32 => self.exec_shl::<u32>(x, y)?,
-                    I32 => self.exec_shl::<i32>(x, y)?,
-                    U64 => self.exec_shl::<u64>(x, y)?,
-                    I64 => self.exec_shl::<i64>(x, y)?,
-                    Uw => self.exec_shl::<UWord>(x, y)?,
-                    Iw => self.exec_shl::<IWord>(x, y)?,
-                    F32 => return Err(ExecutionError::IncorrectOperation(*self.current_op()?)),
-                    F64 => return Err(ExecutionError::IncorrectOperation(*self.current_op()?)),
-                }
+            Shl(bin, ot) => {
+                ot.require_integer(op)?;
+
+                dispatch_op_type!(integer ot, |T| self.exec_shl::<T>(bin)?);
 
                 Ok(ExecutionSuccess::Ok)
             }
-            Shr(x, y, ot) => {
-                match ot {
-                    U8 => self.exec_shr::<u8>(x, y)?,
-                    I8 => self.exec_shr::<i8>(x, y)?,
-                    U16 => self.exec_shr::<u16>(x, y)?,
-                    I16 => self.exec_shr::<i16>(x, y)?,
-                    U32 => self.exec_shr::<u32>(x, y)?,
-                    I32 => self.exec_shr::<i32>(x, y)?,
-                    U64 => self.exec_shr::<u64>(x, y)?,
-                    I64 => self.exec_shr::<i64>(x, y)?,
-                    Uw => self.exec_shr::<UWord>(x, y)?,
-                    Iw => self.exec_shr::<IWord>(x, y)?,
-                    F32 => return Err(ExecutionError::IncorrectOperation(*self.current_op()?)),
-                    F64 => return Err(ExecutionError::IncorrectOperation(*self.current_op()?)),
-                }
+            Shr(bin, ot) => {
+                ot.require_integer(op)?;
+
+                dispatch_op_type!(integer ot, |T| self.exec_shr::<T>(bin)?);
 
                 Ok(ExecutionSuccess::Ok)
             }
             And(bin, ot) => {
-                match ot {
-                    U8 => self.exec_and::<u8>(bin)?,
-                    I8 => self.exec_and::<i8>(bin)?,
-                    U16 => self.exec_and::<u16>(bin)?,
-                    I16 => self.exec_and::<i16>(bin)?,
-                    U32 => self.exec_and::<u32>(bin)?,
-                    I32 => self.exec_and::<i32>(bin)?,
-                    U64 => self.exec_and::<u64>(bin)?,
-                    I64 => self.exec_and::<i64>(bin)?,
-                    Uw => self.exec_and::<UWord>(bin)?,
-                    Iw => self.exec_and::<IWord>(bin)?,
-                    F32 => return Err(ExecutionError::IncorrectOperation(*self.current_op()?)),
-                    F64 => return Err(ExecutionError::IncorrectOperation(*self.current_op()?)),
-                }
+                ot.require_integer(op)?;
+
+                dispatch_op_type!(integer ot, |T| self.exec_and::<T>(bin)?);
 
                 Ok(ExecutionSuccess::Ok)
             }
             Or(bin, ot) => {
-                match ot {
-                    U8 => self.exec_or::<u8>(bin)?,
-                    I8 => self.exec_or::<i8>(bin)?,
-                    U16 => self.exec_or::<u16>(bin)?,
-                    I16 => self.exec_or::<i16>(bin)?,
-                    U32 => self.exec_or::<u32>(bin)?,
-                    I32 => self.exec_or::<i32>(bin)?,
-                    U64 => self.exec_or::<u64>(bin)?,
-                    I64 => self.exec_or::<i64>(bin)?,
-                    Uw => self.exec_or::<UWord>(bin)?,
-                    Iw => self.exec_or::<IWord>(bin)?,
-                    F32 => return Err(ExecutionError::IncorrectOperation(*self.current_op()?)),
-                    F64 => return Err(ExecutionError::IncorrectOperation(*self.current_op()?)),
-                }
+                ot.require_integer(op)?;
+
+                dispatch_op_type!(integer ot, |T| self.exec_or::<T>(bin)?);
 
                 Ok(ExecutionSuccess::Ok)
             }
             Xor(bin, ot) => {
-                match ot {
-                    U8 => self.exec_xor::<u8>(bin)?,
-                    I8 => self.exec_xor::<i8>(bin)?,
-                    U16 => self.exec_xor::<u16>(bin)?,
-                    I16 => self.exec_xor::<i16>(bin)?,
-                    U32 => self.exec_xor::<u32>(bin)?,
-                    I32 => self.exec_xor::<i32>(bin)?,
-                    U64 => self.exec_xor::<u64>(bin)?,
-                    I64 => self.exec_xor::<i64>(bin)?,
-                    Uw => self.exec_xor::<UWord>(bin)?,
-                    Iw => self.exec_xor::<IWord>(bin)?,
-                    F32 => return Err(ExecutionError::IncorrectOperation(*self.current_op()?)),
-                    F64 => return Err(ExecutionError::IncorrectOperation(*self.current_op()?)),
-                }
+                ot.require_integer(op)?;
+
+                dispatch_op_type!(integer ot, |T| self.exec_xor::<T>(bin)?);
 
                 Ok(ExecutionSuccess::Ok)
             }
             Not(un, ot) => {
-                match ot {
-                    U8 => self.exec_not::<u8>(un)?,
-                    I8 => self.exec_not::<i8>(un)?,
-                    U16 => self.exec_not::<u16>(un)?,
-                    I16 => self.exec_not::<i16>(un)?,
-                    U32 => self.exec_not::<u32>(un)?,
-                    I32 => self.exec_not::<i32>(un)?,
-                    U64 => self.exec_not::<u64>(un)?,
-                    I64 => self.exec_not::<i64>(un)?,
-                    Uw => self.exec_not::<UWord>(un)?,
-                    Iw => self.exec_not::<IWord>(un)?,
-                    F32 => return Err(ExecutionError::IncorrectOperation(*self.current_op()?)),
-                    F64 => return Err(ExecutionError::IncorrectOperation(*self.current_op()?)),
-                }
+                ot.require_integer(op)?;
+
+                dispatch_op_type!(integer ot, |T| self.exec_not::<T>(un)?);
 
                 Ok(ExecutionSuccess::Ok)
             }
             Neg(un, ot) => {
-                match ot {
-                    U8 => self.exec_neg::<u8>(un)?,
-                    I8 => self.exec_neg::<i8>(un)?,
-                    U16 => self.exec_neg::<u16>(un)?,
-                    I16 => self.exec_neg::<i16>(un)?,
-                    U32 => self.exec_neg::<u32>(un)?,
-                    I32 => self.exec_neg::<i32>(un)?,
-                    U64 => self.exec_neg::<u64>(un)?,
-                    I64 => self.exec_neg::<i64>(un)?,
-                    Uw => self.exec_neg::<UWord>(un)?,
-                    Iw => self.exec_neg::<IWord>(un)?,
-                    F32 => self.exec_neg::<f32>(un)?,
-                    F64 => self.exec_neg::<f64>(un)?,
-                }
+                dispatch_op_type!(ot, |T| self.exec_neg::<T>(un)?);
 
                 Ok(ExecutionSuccess::Ok)
             }
             Inc(un, ot) => {
-                match ot {
-                    U8 => self.exec_inc::<u8>(un)?,
-                    I8 => self.exec_inc::<i8>(un)?,
-                    U16 => self.exec_inc::<u16>(un)?,
-                    I16 => self.exec_inc::<i16>(un)?,
-                    U32 => self.exec_inc::<u32>(un)?,
-                    I32 => self.exec_inc::<i32>(un)?,
-                    U64 => self.exec_inc::<u64>(un)?,
-                    I64 => self.exec_inc::<i64>(un)?,
-                    Uw => self.exec_inc::<UWord>(un)?,
-                    Iw => self.exec_inc::<IWord>(un)?,
-                    F32 => self.exec_inc::<f32>(un)?,
-                    F64 => self.exec_inc::<f64>(un)?,
-                }
+                dispatch_op_type!(ot, |T| self.exec_inc::<T>(un)?);
 
                 Ok(ExecutionSuccess::Ok)
             }
             Dec(un, ot) => {
-                match ot {
-                    U8 => self.exec_dec::<u8>(un)?,
-                    I8 => self.exec_dec::<i8>(un)?,
-                    U16 => self.exec_dec::<u16>(un)?,
-                    I16 => self.exec_dec::<i16>(un)?,
-                    U32 => self.exec_dec::<u32>(un)?,
-                    I32 => self.exec_dec::<i32>(un)?,
-                    U64 => self.exec_dec::<u64>(un)?,
-                    I64 => self.exec_dec::<i64>(un)?,
-                    Uw => self.exec_dec::<UWord>(un)?,
-                    Iw => self.exec_dec::<IWord>(un)?,
-                    F32 => self.exec_dec::<f32>(un)?,
-                    F64 => self.exec_dec::<f64>(un)?,
-                }
+                dispatch_op_type!(ot, |T| self.exec_dec::<T>(un)?);
 
                 Ok(ExecutionSuccess::Ok)
             }
@@ -857,20 +1877,32 @@ impl<'f> Executor<'f> {
                 self.program_counter = self.get_val(x)?;
                 return Ok(ExecutionSuccess::Ok);
             }
+            Gor(x) => {
+                let offset: IWord = self.get_val(x)?;
+                self.program_counter = self.program_counter.wrapping_add(offset as UWord);
+                return Ok(ExecutionSuccess::Ok);
+            }
+            Skp(x) => {
+                let n: UWord = self.get_val(x)?;
+                self.program_counter = self.program_counter.wrapping_add(n);
+                Ok(ExecutionSuccess::Ok)
+            }
             Ift(un, ot) => {
+                let res = dispatch_op_type!(ot, |T| self.get_un::<T>(un)? != T::zero());
+
+                if res {
+                    Ok(ExecutionSuccess::Ok)
+                } else {
+                    self.pass_condition()?;
+                    return Ok(ExecutionSuccess::Ok);
+                }
+            }
+            Ifn(un, ot) => {
                 let res = match ot {
-                    U8 => self.get_un::<u8>(un)? != 0,
-                    I8 => self.get_un::<i8>(un)? != 0,
-                    U16 => self.get_un::<u16>(un)? != 0,
-                    I16 => self.get_un::<i16>(un)? != 0,
-                    U32 => self.get_un::<u32>(un)? != 0,
-                    I32 => self.get_un::<i32>(un)? != 0,
-                    U64 => self.get_un::<u64>(un)? != 0,
-                    I64 => self.get_un::<i64>(un)? != 0,
-                    Uw => self.get_un::<UWord>(un)? != 0,
-                    Iw => self.get_un::<IWord>(un)? != 0,
-                    F32 => self.get_un::<f32>(un)? != 0.0,
-                    F64 => self.get_un::<f64>(un)? != 0.0,
+                    F32 => self.exec_ifn::<f32>(un)?,
+                    F64 => self.exec_ifn::<f64>(un)?,
+                    F16 => return Err(ExecutionError::UnsupportedType(ot)),
+                    _ => return Err(ExecutionError::IncorrectOperation(op)),
                 };
 
                 if res {
@@ -881,20 +1913,7 @@ impl<'f> Executor<'f> {
                 }
             }
             Iff(un, ot) => {
-                let res = match ot {
-                    U8 => self.get_un::<u8>(un)? == 0,
-                    I8 => self.get_un::<i8>(un)? == 0,
-                    U16 => self.get_un::<u16>(un)? == 0,
-                    I16 => self.get_un::<i16>(un)? == 0,
-                    U32 => self.get_un::<u32>(un)? == 0,
-                    I32 => self.get_un::<i32>(un)? == 0,
-                    U64 => self.get_un::<u64>(un)? == 0,
-                    I64 => self.get_un::<i64>(un)? == 0,
-                    Uw => self.get_un::<UWord>(un)? == 0,
-                    Iw => self.get_un::<IWord>(un)? == 0,
-                    F32 => self.get_un::<f32>(un)? == 0.0,
-                    F64 => self.get_un::<f64>(un)? == 0.0,
-                };
+                let res = dispatch_op_type!(ot, |T| self.get_un::<T>(un)? == T::zero());
 
                 if res {
                     Ok(ExecutionSuccess::Ok)
@@ -904,20 +1923,7 @@ impl<'f> Executor<'f> {
                 }
             }
             Ife(bin, ot) => {
-                let res = match ot {
-                    U8 => self.exec_ife::<u8>(bin)?,
-                    I8 => self.exec_ife::<i8>(bin)?,
-                    U16 => self.exec_ife::<u16>(bin)?,
-                    I16 => self.exec_ife::<i16>(bin)?,
-                    U32 => self.exec_ife::<u32>(bin)?,
-                    I32 => self.exec_ife::<i32>(bin)?,
-                    U64 => self.exec_ife::<u64>(bin)?,
-                    I64 => self.exec_ife::<i64>(bin)?,
-                    Uw => self.exec_ife::<UWord>(bin)?,
-                    Iw => self.exec_ife::<IWord>(bin)?,
-                    F32 => self.exec_ife::<f32>(bin)?,
-                    F64 => self.exec_ife::<f64>(bin)?,
-                };
+                let res = dispatch_op_type!(ot, |T| self.exec_ife::<T>(bin)?);
 
                 if res {
                     Ok(ExecutionSuccess::Ok)
@@ -927,20 +1933,7 @@ impl<'f> Executor<'f> {
                 }
             }
             Ifl(bin, ot) => {
-                let res = match ot {
-                    U8 => self.exec_ifl::<u8>(bin)?,
-                    I8 => self.exec_ifl::<i8>(bin)?,
-                    U16 => self.exec_ifl::<u16>(bin)?,
-                    I16 => self.exec_ifl::<i16>(bin)?,
-                    U32 => self.exec_ifl::<u32>(bin)?,
-                    I32 => self.exec_ifl::<i32>(bin)?,
-                    U64 => self.exec_ifl::<u64>(bin)?,
-                    I64 => self.exec_ifl::<i64>(bin)?,
-                    Uw => self.exec_ifl::<UWord>(bin)?,
-                    Iw => self.exec_ifl::<IWord>(bin)?,
-                    F32 => self.exec_ifl::<f32>(bin)?,
-                    F64 => self.exec_ifl::<f64>(bin)?,
-                };
+                let res = dispatch_op_type!(ot, |T| self.exec_ifl::<T>(bin)?);
 
                 if res {
                     Ok(ExecutionSuccess::Ok)
@@ -950,20 +1943,7 @@ impl<'f> Executor<'f> {
                 }
             }
             Ifg(bin, ot) => {
-                let res = match ot {
-                    U8 => self.exec_ifg::<u8>(bin)?,
-                    I8 => self.exec_ifg::<i8>(bin)?,
-                    U16 => self.exec_ifg::<u16>(bin)?,
-                    I16 => self.exec_ifg::<i16>(bin)?,
-                    U32 => self.exec_ifg::<u32>(bin)?,
-                    I32 => self.exec_ifg::<i32>(bin)?,
-                    U64 => self.exec_ifg::<u64>(bin)?,
-                    I64 => self.exec_ifg::<i64>(bin)?,
-                    Uw => self.exec_ifg::<UWord>(bin)?,
-                    Iw => self.exec_ifg::<IWord>(bin)?,
-                    F32 => self.exec_ifg::<f32>(bin)?,
-                    F64 => self.exec_ifg::<f64>(bin)?,
-                };
+                let res = dispatch_op_type!(ot, |T| self.exec_ifg::<T>(bin)?);
 
                 if res {
                     Ok(ExecutionSuccess::Ok)
@@ -973,20 +1953,7 @@ impl<'f> Executor<'f> {
                 }
             }
             Ine(bin, ot) => {
-                let res = match ot {
-                    U8 => self.exec_ine::<u8>(bin)?,
-                    I8 => self.exec_ine::<i8>(bin)?,
-                    U16 => self.exec_ine::<u16>(bin)?,
-                    I16 => self.exec_ine::<i16>(bin)?,
-                    U32 => self.exec_ine::<u32>(bin)?,
-                    I32 => self.exec_ine::<i32>(bin)?,
-                    U64 => self.exec_ine::<u64>(bin)?,
-                    I64 => self.exec_ine::<i64>(bin)?,
-                    Uw => self.exec_ine::<UWord>(bin)?,
-                    Iw => self.exec_ine::<IWord>(bin)?,
-                    F32 => self.exec_ine::<f32>(bin)?,
-                    F64 => self.exec_ine::<f64>(bin)?,
-                };
+                let res = dispatch_op_type!(ot, |T| self.exec_ine::<T>(bin)?);
 
                 if res {
                     Ok(ExecutionSuccess::Ok)
@@ -996,20 +1963,7 @@ impl<'f> Executor<'f> {
                 }
             }
             Inl(bin, ot) => {
-                let res = match ot {
-                    U8 => self.exec_inl::<u8>(bin)?,
-                    I8 => self.exec_inl::<i8>(bin)?,
-                    U16 => self.exec_inl::<u16>(bin)?,
-                    I16 => self.exec_inl::<i16>(bin)?,
-                    U32 => self.exec_inl::<u32>(bin)?,
-                    I32 => self.exec_inl::<i32>(bin)?,
-                    U64 => self.exec_inl::<u64>(bin)?,
-                    I64 => self.exec_inl::<i64>(bin)?,
-                    Uw => self.exec_inl::<UWord>(bin)?,
-                    Iw => self.exec_inl::<IWord>(bin)?,
-                    F32 => self.exec_inl::<f32>(bin)?,
-                    F64 => self.exec_inl::<f64>(bin)?,
-                };
+                let res = dispatch_op_type!(ot, |T| self.exec_inl::<T>(bin)?);
 
                 if res {
                     Ok(ExecutionSuccess::Ok)
@@ -1019,20 +1973,7 @@ impl<'f> Executor<'f> {
                 }
             }
             Ing(bin, ot) => {
-                let res = match ot {
-                    U8 => self.exec_ing::<u8>(bin)?,
-                    I8 => self.exec_ing::<i8>(bin)?,
-                    U16 => self.exec_ing::<u16>(bin)?,
-                    I16 => self.exec_ing::<i16>(bin)?,
-                    U32 => self.exec_ing::<u32>(bin)?,
-                    I32 => self.exec_ing::<i32>(bin)?,
-                    U64 => self.exec_ing::<u64>(bin)?,
-                    I64 => self.exec_ing::<i64>(bin)?,
-                    Uw => self.exec_ing::<UWord>(bin)?,
-                    Iw => self.exec_ing::<IWord>(bin)?,
-                    F32 => self.exec_ing::<f32>(bin)?,
-                    F64 => self.exec_ing::<f64>(bin)?,
-                };
+                let res = dispatch_op_type!(ot, |T| self.exec_ing::<T>(bin)?);
 
                 if res {
                     Ok(ExecutionSuccess::Ok)
@@ -1042,20 +1983,9 @@ impl<'f> Executor<'f> {
                 }
             }
             Ifa(bin, ot) => {
-                let res = match ot {
-                    U8 => self.exec_ifa::<u8>(bin)?,
-                    I8 => self.exec_ifa::<i8>(bin)?,
-                    U16 => self.exec_ifa::<u16>(bin)?,
-                    I16 => self.exec_ifa::<i16>(bin)?,
-                    U32 => self.exec_ifa::<u32>(bin)?,
-                    I32 => self.exec_ifa::<i32>(bin)?,
-                    U64 => self.exec_ifa::<u64>(bin)?,
-                    I64 => self.exec_ifa::<i64>(bin)?,
-                    Uw => self.exec_ifa::<UWord>(bin)?,
-                    Iw => self.exec_ifa::<IWord>(bin)?,
-                    F32 => return Err(ExecutionError::IncorrectOperation(*self.current_op()?)),
-                    F64 => return Err(ExecutionError::IncorrectOperation(*self.current_op()?)),
-                };
+                ot.require_integer(op)?;
+
+                let res = dispatch_op_type!(integer ot, |T| self.exec_ifa::<T>(bin)?);
 
                 if res {
                     Ok(ExecutionSuccess::Ok)
@@ -1065,20 +1995,9 @@ impl<'f> Executor<'f> {
                 }
             }
             Ifo(bin, ot) => {
-                let res = match ot {
-                    U8 => self.exec_ifo::<u8>(bin)?,
-                    I8 => self.exec_ifo::<i8>(bin)?,
-                    U16 => self.exec_ifo::<u16>(bin)?,
-                    I16 => self.exec_ifo::<i16>(bin)?,
-                    U32 => self.exec_ifo::<u32>(bin)?,
-                    I32 => self.exec_ifo::<i32>(bin)?,
-                    U64 => self.exec_ifo::<u64>(bin)?,
-                    I64 => self.exec_ifo::<i64>(bin)?,
-                    Uw => self.exec_ifo::<UWord>(bin)?,
-                    Iw => self.exec_ifo::<IWord>(bin)?,
-                    F32 => return Err(ExecutionError::IncorrectOperation(*self.current_op()?)),
-                    F64 => return Err(ExecutionError::IncorrectOperation(*self.current_op()?)),
-                };
+                ot.require_integer(op)?;
+
+                let res = dispatch_op_type!(integer ot, |T| self.exec_ifo::<T>(bin)?);
 
                 if res {
                     Ok(ExecutionSuccess::Ok)
@@ -1088,20 +2007,9 @@ impl<'f> Executor<'f> {
                 }
             }
             Ifx(bin, ot) => {
-                let res = match ot {
-                    U8 => self.exec_ifx::<u8>(bin)?,
-                    I8 => self.exec_ifx::<i8>(bin)?,
-                    U16 => self.exec_ifx::<u16>(bin)?,
-                    I16 => self.exec_ifx::<i16>(bin)?,
-                    U32 => self.exec_ifx::<u32>(bin)?,
-                    I32 => self.exec_ifx::<i32>(bin)?,
-                    U64 => self.exec_ifx::<u64>(bin)?,
-                    I64 => self.exec_ifx::<i64>(bin)?,
-                    Uw => self.exec_ifx::<UWord>(bin)?,
-                    Iw => self.exec_ifx::<IWord>(bin)?,
-                    F32 => return Err(ExecutionError::IncorrectOperation(*self.current_op()?)),
-                    F64 => return Err(ExecutionError::IncorrectOperation(*self.current_op()?)),
-                };
+                ot.require_integer(op)?;
+
+                let res = dispatch_op_type!(integer ot, |T| self.exec_ifx::<T>(bin)?);
 
                 if res {
                     Ok(ExecutionSuccess::Ok)
@@ -1111,20 +2019,9 @@ impl<'f> Executor<'f> {
                 }
             }
             Ina(bin, ot) => {
-                let res = match ot {
-                    U8 => self.exec_ina::<u8>(bin)?,
-                    I8 => self.exec_ina::<i8>(bin)?,
-                    U16 => self.exec_ina::<u16>(bin)?,
-                    I16 => self.exec_ina::<i16>(bin)?,
-                    U32 => self.exec_ina::<u32>(bin)?,
-                    I32 => self.exec_ina::<i32>(bin)?,
-                    U64 => self.exec_ina::<u64>(bin)?,
-                    I64 => self.exec_ina::<i64>(bin)?,
-                    Uw => self.exec_ina::<UWord>(bin)?,
-                    Iw => self.exec_ina::<IWord>(bin)?,
-                    F32 => return Err(ExecutionError::IncorrectOperation(*self.current_op()?)),
-                    F64 => return Err(ExecutionError::IncorrectOperation(*self.current_op()?)),
-                };
+                ot.require_integer(op)?;
+
+                let res = dispatch_op_type!(integer ot, |T| self.exec_ina::<T>(bin)?);
 
                 if res {
                     Ok(ExecutionSuccess::Ok)
@@ -1134,20 +2031,9 @@ impl<'f> Executor<'f> {
                 }
             }
             Ino(bin, ot) => {
-                let res = match ot {
-                    U8 => self.exec_ino::<u8>(bin)?,
-                    I8 => self.exec_ino::<i8>(bin)?,
-                    U16 => self.exec_ino::<u16>(bin)?,
-                    I16 => self.exec_ino::<i16>(bin)?,
-                    U32 => self.exec_ino::<u32>(bin)?,
-                    I32 => self.exec_ino::<i32>(bin)?,
-                    U64 => self.exec_ino::<u64>(bin)?,
-                    I64 => self.exec_ino::<i64>(bin)?,
-                    Uw => self.exec_ino::<UWord>(bin)?,
-                    Iw => self.exec_ino::<IWord>(bin)?,
-                    F32 => return Err(ExecutionError::IncorrectOperation(*self.current_op()?)),
-                    F64 => return Err(ExecutionError::IncorrectOperation(*self.current_op()?)),
-                };
+                ot.require_integer(op)?;
+
+                let res = dispatch_op_type!(integer ot, |T| self.exec_ino::<T>(bin)?);
 
                 if res {
                     Ok(ExecutionSuccess::Ok)
@@ -1157,20 +2043,9 @@ impl<'f> Executor<'f> {
                 }
             }
             Inx(bin, ot) => {
-                let res = match ot {
-                    U8 => self.exec_inx::<u8>(bin)?,
-                    I8 => self.exec_inx::<i8>(bin)?,
-                    U16 => self.exec_inx::<u16>(bin)?,
-                    I16 => self.exec_inx::<i16>(bin)?,
-                    U32 => self.exec_inx::<u32>(bin)?,
-                    I32 => self.exec_inx::<i32>(bin)?,
-                    U64 => self.exec_inx::<u64>(bin)?,
-                    I64 => self.exec_inx::<i64>(bin)?,
-                    Uw => self.exec_inx::<UWord>(bin)?,
-                    Iw => self.exec_inx::<IWord>(bin)?,
-                    F32 => return Err(ExecutionError::IncorrectOperation(*self.current_op()?)),
-                    F64 => return Err(ExecutionError::IncorrectOperation(*self.current_op()?)),
-                };
+                ot.require_integer(op)?;
+
+                let res = dispatch_op_type!(integer ot, |T| self.exec_inx::<T>(bin)?);
 
                 if res {
                     Ok(ExecutionSuccess::Ok)
@@ -1183,21 +2058,12 @@ impl<'f> Executor<'f> {
                 self.app(self.get_val(x)?)?;
                 Ok(ExecutionSuccess::Ok)
             }
+            Tap(x) => {
+                self.tap(self.get_val(x)?)?;
+                Ok(ExecutionSuccess::Ok)
+            }
             Par(un, ot) => {
-                match ot {
-                    U8 => self.exec_par::<u8>(un)?,
-                    I8 => self.exec_par::<i8>(un)?,
-                    U16 => self.exec_par::<u16>(un)?,
-                    I16 => self.exec_par::<i16>(un)?,
-                    U32 => self.exec_par::<u32>(un)?,
-                    I32 => self.exec_par::<i32>(un)?,
-                    U64 => self.exec_par::<u64>(un)?,
-                    I64 => self.exec_par::<i64>(un)?,
-                    Uw => self.exec_par::<UWord>(un)?,
-                    Iw => self.exec_par::<IWord>(un)?,
-                    F32 => self.exec_par::<f32>(un)?,
-                    F64 => self.exec_par::<f64>(un)?,
-                }
+                dispatch_op_type!(ot, |T| self.exec_par::<T>(un)?);
 
                 Ok(ExecutionSuccess::Ok)
             }
@@ -1207,20 +2073,7 @@ impl<'f> Executor<'f> {
             }
             Ret(un, ot) => {
                 if un.x() != Operand::Emp {
-                    match ot {
-                        U8 => self.set_ret::<u8>(un)?,
-                        I8 => self.set_ret::<i8>(un)?,
-                        U16 => self.set_ret::<u16>(un)?,
-                        I16 => self.set_ret::<i16>(un)?,
-                        U32 => self.set_ret::<u32>(un)?,
-                        I32 => self.set_ret::<i32>(un)?,
-                        U64 => self.set_ret::<u64>(un)?,
-                        I64 => self.set_ret::<i64>(un)?,
-                        Uw => self.set_ret::<UWord>(un)?,
-                        Iw => self.set_ret::<IWord>(un)?,
-                        F32 => self.set_ret::<f32>(un)?,
-                        F64 => self.set_ret::<f64>(un)?,
-                    }
+                    dispatch_op_type!(ot, |T| self.set_ret::<T>(un)?);
                 }
 
                 self.ret()?;
@@ -1242,6 +2095,35 @@ impl<'f> Executor<'f> {
                 self.files.write(val)?;
                 Ok(ExecutionSuccess::Ok)
             }
+            Oui(x, ot) => {
+                ot.require_integer(op)?;
+
+                dispatch_op_type!(integer ot, |T| self.exec_out_fmt::<T>(x)?);
+
+                Ok(ExecutionSuccess::Ok)
+            }
+            Ouf(x, ot) => {
+                match ot {
+                    F32 => self.exec_out_fmt::<f32>(x)?,
+                    F64 => self.exec_out_fmt::<f64>(x)?,
+                    F16 => return Err(ExecutionError::UnsupportedType(ot)),
+                    _ => return Err(ExecutionError::IncorrectOperation(op)),
+                }
+
+                Ok(ExecutionSuccess::Ok)
+            }
+            Ouc(x) => {
+                let code: u32 = self.get_val(x)?;
+                let ch = core::char::from_u32(code).ok_or(ExecutionError::InvalidChar(code))?;
+                let mut buf = [0; 4];
+                self.files.write_all(ch.encode_utf8(&mut buf).as_bytes())?;
+                Ok(ExecutionSuccess::Ok)
+            }
+            Rnd(x, ot) => {
+                dispatch_op_type!(ot, |T| self.exec_rnd::<T>(x)?);
+
+                Ok(ExecutionSuccess::Ok)
+            }
             Fls => {
                 self.files.flush()?;
                 Ok(ExecutionSuccess::Ok)
@@ -1254,10 +2136,70 @@ impl<'f> Executor<'f> {
                 self.set_val(x, self.files.current()?)?;
                 Ok(ExecutionSuccess::Ok)
             }
+            Cfd(x) => {
+                self.files.close(self.get_val(x)?)?;
+                Ok(ExecutionSuccess::Ok)
+            }
+            Ovf(x) => {
+                self.set_val::<u8>(x, self.overflow as u8)?;
+                Ok(ExecutionSuccess::Ok)
+            }
+            Adc(bin, ot) => {
+                ot.require_integer(op)?;
+
+                dispatch_op_type!(integer ot, |T| self.exec_adc::<T>(bin)?);
+
+                Ok(ExecutionSuccess::Ok)
+            }
+            Sbb(bin, ot) => {
+                ot.require_integer(op)?;
+
+                dispatch_op_type!(integer ot, |T| self.exec_sbb::<T>(bin)?);
+
+                Ok(ExecutionSuccess::Ok)
+            }
             Zer(x, y) => {
                 let dest = self.get_val(x)?;
                 let size = self.get_val(y)?;
                 self.memory.set_zeros(dest, size)?;
+                self.watch(dest, size);
+                Ok(ExecutionSuccess::Ok)
+            }
+            Rem(x, y) => {
+                let stack = self.memory.stack.remaining() as UWord;
+                let heap = self.memory.heap.remaining() as UWord;
+                self.set_val(x, stack)?;
+                self.set_val(y, heap)?;
+                Ok(ExecutionSuccess::Ok)
+            }
+            Bnd(x, y, z) => {
+                let base: UWord = self.get_val(x)?;
+                let index = self.get_val(y)?;
+                let len = match z {
+                    Operand::Emp => self.bounds.get(&base).copied().unwrap_or(0),
+                    _ => self.get_val(z)?,
+                };
+
+                if index >= len {
+                    return Err(ExecutionError::IndexOutOfBounds { index, len });
+                }
+
+                Ok(ExecutionSuccess::Ok)
+            }
+            Fil(x, y, z) => {
+                let dest = self.get_val(x)?;
+                let byte = self.get_val(y)?;
+                let size = self.get_val(z)?;
+                self.memory.fill(dest, byte, size)?;
+                self.watch(dest, size);
+                Ok(ExecutionSuccess::Ok)
+            }
+            Scn(x, y, z, w) => {
+                let start = self.get_val(y)?;
+                let needle = self.get_val(z)?;
+                let max = self.get_val(w)?;
+                let offset = self.memory.find(start, needle, max)?;
+                self.set_val(x, offset)?;
                 Ok(ExecutionSuccess::Ok)
             }
             Cmp(x, y, z) => {
@@ -1277,6 +2219,187 @@ impl<'f> Executor<'f> {
                 let src = self.get_val(y)?;
                 let size = self.get_val(z)?;
                 self.memory.copy(dest, src, size)?;
+                self.watch(dest, size);
+                Ok(ExecutionSuccess::Ok)
+            }
+            Ads(bin, ot) => {
+                dispatch_op_type!(ot, |T| self.exec_ads::<T>(bin)?);
+
+                Ok(ExecutionSuccess::Ok)
+            }
+            Sbs(bin, ot) => {
+                dispatch_op_type!(ot, |T| self.exec_sbs::<T>(bin)?);
+
+                Ok(ExecutionSuccess::Ok)
+            }
+            Mls(bin, ot) => {
+                dispatch_op_type!(ot, |T| self.exec_mls::<T>(bin)?);
+
+                Ok(ExecutionSuccess::Ok)
+            }
+            Sqr(un, ot) => {
+                match ot {
+                    U8 | I8 | U16 | I16 | U32 | I32 | U64 | I64 | Uw | Iw | U128 | I128 => {
+                        return Err(ExecutionError::IncorrectOperation(*self.current_op()?))
+                    }
+                    F16 => return Err(ExecutionError::UnsupportedType(ot)),
+                    F32 => self.exec_sqr::<f32>(un)?,
+                    F64 => self.exec_sqr::<f64>(un)?,
+                }
+
+                Ok(ExecutionSuccess::Ok)
+            }
+            Fun(un, ot, math_fn) => {
+                match ot {
+                    U8 | I8 | U16 | I16 | U32 | I32 | U64 | I64 | Uw | Iw | U128 | I128 => {
+                        return Err(ExecutionError::IncorrectOperation(*self.current_op()?))
+                    }
+                    F16 => return Err(ExecutionError::UnsupportedType(ot)),
+                    F32 => self.exec_fun::<f32>(un, math_fn)?,
+                    F64 => self.exec_fun::<f64>(un, math_fn)?,
+                }
+
+                Ok(ExecutionSuccess::Ok)
+            }
+            Rol(x, y, ot) => {
+                ot.require_integer(op)?;
+
+                dispatch_op_type!(integer ot, |T| self.exec_rol::<T>(x, y)?);
+
+                Ok(ExecutionSuccess::Ok)
+            }
+            Ror(x, y, ot) => {
+                ot.require_integer(op)?;
+
+                dispatch_op_type!(integer ot, |T| self.exec_ror::<T>(x, y)?);
+
+                Ok(ExecutionSuccess::Ok)
+            }
+            Cnt(un, ot) => {
+                ot.require_integer(op)?;
+
+                dispatch_op_type!(integer ot, |T| self.exec_cnt::<T>(un)?);
+
+                Ok(ExecutionSuccess::Ok)
+            }
+            Clz(un, ot) => {
+                ot.require_integer(op)?;
+
+                dispatch_op_type!(integer ot, |T| self.exec_clz::<T>(un)?);
+
+                Ok(ExecutionSuccess::Ok)
+            }
+            Ctz(un, ot) => {
+                ot.require_integer(op)?;
+
+                dispatch_op_type!(integer ot, |T| self.exec_ctz::<T>(un)?);
+
+                Ok(ExecutionSuccess::Ok)
+            }
+            // Memory is not shared between executors yet,
+            // so the atomic ops are a plain load and store.
+            Lda(x, y) => {
+                let addr = self.get_val(y)?;
+                let val: UWord = self.memory.get(addr)?;
+                self.set_val(x, val)?;
+                Ok(ExecutionSuccess::Ok)
+            }
+            Sta(x, y) => {
+                let addr = self.get_val(x)?;
+                let val: UWord = self.get_val(y)?;
+                self.memory.set(addr, val)?;
+                self.watch(addr, UWord::SIZE as UWord);
+                Ok(ExecutionSuccess::Ok)
+            }
+            Swp(x, y, ot) => {
+                dispatch_op_type!(ot, |T| self.exec_swp::<T>(x, y)?);
+
+                Ok(ExecutionSuccess::Ok)
+            }
+            Cas(x, y, z, ot) => {
+                dispatch_op_type!(ot, |T| self.exec_cas::<T>(x, y, z)?);
+
+                Ok(ExecutionSuccess::Ok)
+            }
+            Min(bin, ot) => {
+                dispatch_op_type!(ot, |T| self.exec_min::<T>(bin)?);
+
+                Ok(ExecutionSuccess::Ok)
+            }
+            Max(bin, ot) => {
+                dispatch_op_type!(ot, |T| self.exec_max::<T>(bin)?);
+
+                Ok(ExecutionSuccess::Ok)
+            }
+            Abs(un, ot) => {
+                dispatch_op_type!(ot, |T| self.exec_abs::<T>(un)?);
+
+                Ok(ExecutionSuccess::Ok)
+            }
+            Eof(x) => {
+                let eof = self.files.at_eof()?;
+                self.set_val::<u8>(x, eof as u8)?;
+                Ok(ExecutionSuccess::Ok)
+            }
+            Inb(x, y) => {
+                let ptr = self.get_val(x)?;
+                let len = self.get_val(y)?;
+                let buf = self.memory.slice_mut(ptr, len)?;
+                let n = self.files.read_into(buf)? as UWord;
+                self.watch(ptr, n);
+
+                // The count of read bytes replaces the length if it is writable
+                match y {
+                    Operand::Val(_) | Operand::Ref(_) | Operand::Emp => (),
+                    _ => self.set_val(y, n)?,
+                }
+
+                Ok(ExecutionSuccess::Ok)
+            }
+            Outb(x, y) => {
+                let ptr = self.get_val(x)?;
+                let len = self.get_val(y)?;
+                let buf = self.memory.slice(ptr, len)?;
+                self.files.write_all(buf)?;
+                Ok(ExecutionSuccess::Ok)
+            }
+            Trp(x) => match self.get_val(x)? {
+                0 => Ok(ExecutionSuccess::Ok),
+                code => Err(ExecutionError::Trap(code)),
+            },
+            Sel(x, y, z, w, ot) => {
+                dispatch_op_type!(ot, |T| self.exec_sel::<T>(x, y, z, w)?);
+
+                Ok(ExecutionSuccess::Ok)
+            }
+            Gip(x) => {
+                self.set_val(x, self.program_counter)?;
+                Ok(ExecutionSuccess::Ok)
+            }
+            Grp(x) => {
+                self.set_val(x, self.current_call()?.ret_val_ptr)?;
+                Ok(ExecutionSuccess::Ok)
+            }
+            Srp(x) => {
+                let ptr: UWord = self.get_val(x)?;
+
+                // The current frame is freed on return, so the value of up
+                // to the frame size must be returned below it or to the heap
+                let call = self.current_call()?;
+                let end = ptr.checked_add(call.function.frame_size);
+                let valid = match (self.memory.region_of(ptr), end) {
+                    (_, None) => false,
+                    (Region::Stack, Some(end)) => end <= call.base_ptr,
+                    (Region::Heap, Some(end)) => {
+                        end - self.memory.heap_base() <= self.memory.heap.len()
+                    }
+                };
+
+                if !valid {
+                    return Err(ExecutionError::InvalidReturnPointer(ptr));
+                }
+
+                self.current_call_mut()?.ret_val_ptr = ptr;
                 Ok(ExecutionSuccess::Ok)
             }
         };
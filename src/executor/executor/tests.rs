@@ -82,7 +82,7 @@ fn executor_set() {
             OpType::I32
         )))
     );
-    exe.program_counter += 1; // Move manually after incorrect operation
+    exe.skip_current_op().unwrap();
 
     assert_eq!(
         exe.execute(),
@@ -91,7 +91,7 @@ fn executor_set() {
             OpType::I32
         )))
     );
-    exe.program_counter += 1; // Move manually after incorrect operation
+    exe.skip_current_op().unwrap();
 
     assert_eq!(exe.execute(), Executed::Ok(ExecutionSuccess::Ok));
     assert_eq!(exe.get_val::<i8>(Operand::Loc(1)), Ok(32));
@@ -121,13 +121,46 @@ fn executor_cnv() {
     assert_eq!(exe.get_val::<u8>(Operand::Loc(0)), Ok(2));
 }
 
+#[test]
+fn executor_cnv_float_modes() {
+    use OpType::*;
+
+    let functions = [Function {
+        frame_size: 32,
+        program: &[
+            Op::Cnv(Operand::Loc(16), Operand::Loc(0), F64, I32),
+            Op::Cnr(Operand::Loc(20), Operand::Loc(0), F64, I32),
+            Op::Cnv(Operand::Loc(24), Operand::Loc(8), F64, I32),
+            Op::Cnr(Operand::Loc(28), Operand::Loc(8), F64, I32),
+        ],
+    }];
+
+    let mut exe = Executor::new(&functions);
+    exe.call(0, 0).unwrap();
+    exe.set_val(Operand::Loc(0), 2.7_f64).unwrap();
+    exe.set_val(Operand::Loc(8), 1e30_f64).unwrap();
+
+    for _ in 0..4 {
+        assert_eq!(exe.execute(), Ok(ExecutionSuccess::Ok));
+    }
+
+    assert_eq!(exe.get_val::<i32>(Operand::Loc(16)), Ok(2));
+    assert_eq!(exe.get_val::<i32>(Operand::Loc(20)), Ok(3));
+    assert_eq!(exe.get_val::<i32>(Operand::Loc(24)), Ok(i32::MAX));
+    assert_eq!(exe.get_val::<i32>(Operand::Loc(28)), Ok(i32::MAX));
+
+    assert_eq!(<i32 as Convert<f64>>::convert_round(-2.5), -3);
+    assert_eq!(<i32 as Convert<f64>>::convert(-2.5), -2);
+    assert_eq!(<u8 as Convert<f32>>::convert_round(-1.0), 0);
+}
+
 #[test]
 fn executor_shl() {
     let functions = [Function {
         frame_size: 8,
         program: &[
             Op::Set(BinOp::new(Operand::Loc(0), Operand::Val(2)), OpType::U32),
-            Op::Shl(Operand::Loc(0), Operand::Val(1), OpType::U32),
+            Op::Shl(BinOp::new(Operand::Loc(0), Operand::Val(1)), OpType::U32),
         ],
     }];
 
@@ -146,7 +179,7 @@ fn executor_shr() {
         program: &[
             Op::Set(BinOp::new(Operand::Loc(0), Operand::Val(2)), OpType::U32),
             Op::Set(BinOp::new(Operand::Loc(8), Operand::Val(1)), OpType::U8),
-            Op::Shr(Operand::Loc(0), Operand::Loc(8), OpType::U32),
+            Op::Shr(BinOp::new(Operand::Loc(0), Operand::Loc(8)), OpType::U32),
         ],
     }];
 
@@ -526,6 +559,7 @@ fn executor_gcd() {
 
     assert_eq!(executed, Executed::Ok(ExecutionSuccess::End(0)));
     assert_eq!(exe.get_val::<u32>(Operand::Loc(0)), Ok(13));
+    assert_eq!(exe.peak_memory(), (24, 0));
 }
 
 #[test]
@@ -596,6 +630,45 @@ fn executor_hello() {
     assert_eq!(hello, "Hello!");
 }
 
+#[test]
+fn executor_hello_data() {
+    let hello = Memory::HEAP_BASE;
+    let program = [
+        Op::Set(BinOp::new(Operand::Loc(0), Operand::Val(0)), OpType::Uw),
+        Op::Out(UnOp::new(Operand::Glb(hello)).with_first(Operand::Loc(0))),
+        Op::Inc(UnOp::new(Operand::Loc(0)), OpType::Uw),
+        Op::Ifl(BinOp::new(Operand::Loc(0), Operand::Val(6)), OpType::Uw),
+        Op::Go(Operand::Val(1)),
+        Op::Set(BinOp::new(Operand::Glb(hello), Operand::Val(0)), OpType::U8),
+    ];
+    let functions = [Function::new(
+        std::mem::size_of::<UWord>() as UWord,
+        &program,
+    )];
+    let data = [DataSegment {
+        addr: hello,
+        bytes: b"Hello!".to_vec(),
+        readonly: true,
+    }];
+
+    let mut exe = Executor::new(&functions);
+    exe.load_segments(&data).unwrap();
+    assert_eq!(exe.files.open(Vec::<u8>::new()), Ok(0));
+    exe.files.set_current(0).unwrap();
+    exe.call(0, 0).unwrap();
+
+    assert_eq!(
+        exe.run_until_break(),
+        Err(ExecutionError::MemoryError(MemoryError::WriteToReadOnly(
+            hello
+        )))
+    );
+
+    let file = exe.files.close(0).unwrap();
+    let slice = file.as_any().downcast_ref::<Vec<u8>>().unwrap().as_slice();
+    assert_eq!(slice, b"Hello!");
+}
+
 #[test]
 fn executor_mul_from_in() {
     use std::collections::vec_deque::VecDeque;
@@ -712,3 +785,2591 @@ fn executor_cpy() {
     assert_eq!(exe.get_val::<u32>(Operand::Loc(0)), Ok(0x10EF));
     assert_eq!(exe.get_val::<u32>(Operand::Loc(4)), Ok(0x10EF));
 }
+
+#[test]
+fn executor_ads() {
+    let functions = [Function {
+        frame_size: 5,
+        program: &[
+            Op::Set(BinOp::new(Operand::Loc(0), Operand::Val(250)), OpType::U8),
+            Op::Ads(BinOp::new(Operand::Loc(0), Operand::Val(10)), OpType::U8),
+            Op::Set(
+                BinOp::new(Operand::Loc(1), Operand::Val(i32::MAX as UWord)),
+                OpType::I32,
+            ),
+            Op::Ads(BinOp::new(Operand::Loc(1), Operand::Val(1)), OpType::I32),
+        ],
+    }];
+
+    let mut exe = Executor::new(&functions);
+    exe.call(0, 0).unwrap();
+
+    assert_eq!(exe.execute(), Executed::Ok(ExecutionSuccess::Ok));
+    assert_eq!(exe.execute(), Executed::Ok(ExecutionSuccess::Ok));
+    assert_eq!(exe.get_val::<u8>(Operand::Loc(0)), Ok(u8::MAX));
+
+    assert_eq!(exe.execute(), Executed::Ok(ExecutionSuccess::Ok));
+    assert_eq!(exe.execute(), Executed::Ok(ExecutionSuccess::Ok));
+    assert_eq!(exe.get_val::<i32>(Operand::Loc(1)), Ok(i32::MAX));
+}
+
+#[test]
+fn executor_sbs_mls() {
+    let functions = [Function {
+        frame_size: 8,
+        program: &[
+            Op::Set(BinOp::new(Operand::Loc(0), Operand::Val(3)), OpType::U32),
+            Op::Sbs(BinOp::new(Operand::Loc(0), Operand::Val(5)), OpType::U32),
+            Op::Set(
+                BinOp::new(Operand::Loc(4), Operand::Val(i32::MIN as UWord)),
+                OpType::I32,
+            ),
+            Op::Mls(BinOp::new(Operand::Loc(4), Operand::Val(2)), OpType::I32),
+        ],
+    }];
+
+    let mut exe = Executor::new(&functions);
+    exe.call(0, 0).unwrap();
+
+    assert_eq!(exe.execute(), Executed::Ok(ExecutionSuccess::Ok));
+    assert_eq!(exe.execute(), Executed::Ok(ExecutionSuccess::Ok));
+    assert_eq!(exe.get_val::<u32>(Operand::Loc(0)), Ok(0));
+
+    assert_eq!(exe.execute(), Executed::Ok(ExecutionSuccess::Ok));
+    assert_eq!(exe.execute(), Executed::Ok(ExecutionSuccess::Ok));
+    assert_eq!(exe.get_val::<i32>(Operand::Loc(4)), Ok(i32::MIN));
+}
+
+#[test]
+fn executor_add_checked() {
+    let functions = [Function {
+        frame_size: 4,
+        program: &[
+            Op::Set(
+                BinOp::new(Operand::Loc(0), Operand::Val(i32::MAX as UWord)),
+                OpType::I32,
+            ),
+            Op::Add(BinOp::new(Operand::Loc(0), Operand::Val(1)), OpType::I32),
+        ],
+    }];
+
+    let mut exe = Executor::new(&functions);
+    exe.set_checked(true);
+    exe.call(0, 0).unwrap();
+
+    assert_eq!(exe.execute(), Executed::Ok(ExecutionSuccess::Ok));
+    assert_eq!(
+        exe.execute(),
+        Executed::Err(ExecutionError::OperationOverflow)
+    );
+    assert_eq!(exe.get_val::<i32>(Operand::Loc(0)), Ok(i32::MAX));
+}
+
+#[test]
+fn executor_sub_mul_checked() {
+    let functions = [Function {
+        frame_size: 8,
+        program: &[
+            Op::Sub(BinOp::new(Operand::Loc(0), Operand::Val(1)), OpType::U32),
+            Op::Set(BinOp::new(Operand::Loc(4), Operand::Val(3)), OpType::U32),
+            Op::Mul(BinOp::new(Operand::Loc(4), Operand::Val(5)), OpType::U32),
+            Op::Mul(
+                BinOp::new(Operand::Loc(4), Operand::Val(u32::MAX as UWord)),
+                OpType::U32,
+            ),
+        ],
+    }];
+
+    let mut exe = Executor::new(&functions);
+    exe.set_checked(true);
+    exe.call(0, 0).unwrap();
+
+    assert_eq!(
+        exe.execute(),
+        Executed::Err(ExecutionError::OperationOverflow)
+    );
+    exe.program_counter += 1; // Move manually after overflow
+
+    assert_eq!(exe.execute(), Executed::Ok(ExecutionSuccess::Ok));
+    assert_eq!(exe.execute(), Executed::Ok(ExecutionSuccess::Ok));
+    assert_eq!(exe.get_val::<u32>(Operand::Loc(4)), Ok(15));
+    assert_eq!(
+        exe.execute(),
+        Executed::Err(ExecutionError::OperationOverflow)
+    );
+}
+
+#[test]
+fn executor_sqr() {
+    let fb = f32::to_le_bytes(16.0);
+    let float = UWord::from_slice(fb.as_ref());
+
+    let functions = [Function {
+        frame_size: 4,
+        program: &[
+            Op::Set(
+                BinOp::new(Operand::Loc(0), Operand::Val(float)),
+                OpType::F32,
+            ),
+            Op::Sqr(UnOp::new(Operand::Loc(0)), OpType::F32),
+            Op::Sqr(UnOp::new(Operand::Loc(0)), OpType::U32),
+        ],
+    }];
+
+    let mut exe = Executor::new(&functions);
+    exe.call(0, 0).unwrap();
+
+    assert_eq!(exe.execute(), Executed::Ok(ExecutionSuccess::Ok));
+    assert_eq!(exe.execute(), Executed::Ok(ExecutionSuccess::Ok));
+    assert_eq!(exe.get_val::<f32>(Operand::Loc(0)), Ok(4.0));
+    assert_eq!(
+        exe.execute(),
+        Executed::Err(ExecutionError::IncorrectOperation(Op::Sqr(
+            UnOp::new(Operand::Loc(0)),
+            OpType::U32
+        )))
+    );
+}
+
+#[test]
+fn executor_fun() {
+    let functions = [Function {
+        frame_size: 8,
+        program: &[
+            Op::Fun(UnOp::new(Operand::Loc(0)), OpType::F64, MathFn::Exp),
+            Op::Fun(UnOp::new(Operand::Loc(0)), OpType::F64, MathFn::Ln),
+            Op::Fun(UnOp::new(Operand::Loc(0)), OpType::F64, MathFn::Cos),
+        ],
+    }];
+
+    let mut exe = Executor::new(&functions);
+    exe.call(0, 0).unwrap();
+
+    assert_eq!(exe.execute(), Executed::Ok(ExecutionSuccess::Ok));
+    assert_eq!(exe.get_val::<f64>(Operand::Loc(0)), Ok(1.0));
+    assert_eq!(exe.execute(), Executed::Ok(ExecutionSuccess::Ok));
+    assert_eq!(exe.get_val::<f64>(Operand::Loc(0)), Ok(0.0));
+    assert_eq!(exe.execute(), Executed::Ok(ExecutionSuccess::Ok));
+    assert_eq!(exe.get_val::<f64>(Operand::Loc(0)), Ok(1.0));
+}
+
+#[test]
+fn executor_div_min_by_minus_one() {
+    let functions = [Function {
+        frame_size: 32,
+        program: &[
+            // i32 a = i32::MIN
+            Op::Set(
+                BinOp::new(Operand::Loc(0), Operand::Val(i32::MIN as UWord)),
+                OpType::I32,
+            ),
+            Op::Set(BinOp::new(Operand::Loc(4), Operand::Loc(0)), OpType::I32),
+            Op::Div(
+                BinOp::new(Operand::Loc(0), Operand::Val(-1_i32 as UWord)),
+                OpType::I32,
+            ),
+            Op::Mod(
+                BinOp::new(Operand::Loc(4), Operand::Val(-1_i32 as UWord)),
+                OpType::I32,
+            ),
+            // i64 b = i64::MIN
+            Op::Set(BinOp::new(Operand::Loc(8), Operand::Val(1)), OpType::I64),
+            Op::Shl(BinOp::new(Operand::Loc(8), Operand::Val(63)), OpType::I64),
+            Op::Set(BinOp::new(Operand::Loc(16), Operand::Loc(8)), OpType::I64),
+            // i64 c = -1
+            Op::Dec(UnOp::new(Operand::Loc(24)), OpType::I64),
+            Op::Div(BinOp::new(Operand::Loc(8), Operand::Loc(24)), OpType::I64),
+            Op::Mod(BinOp::new(Operand::Loc(16), Operand::Loc(24)), OpType::I64),
+        ],
+    }];
+
+    let mut exe = Executor::new(&functions);
+    exe.call(0, 0).unwrap();
+
+    for _ in 0..4 {
+        assert_eq!(exe.execute(), Executed::Ok(ExecutionSuccess::Ok));
+    }
+
+    assert_eq!(exe.get_val::<i32>(Operand::Loc(0)), Ok(i32::MIN));
+    assert_eq!(exe.get_val::<i32>(Operand::Loc(4)), Ok(0));
+
+    for _ in 0..6 {
+        assert_eq!(exe.execute(), Executed::Ok(ExecutionSuccess::Ok));
+    }
+
+    assert_eq!(exe.get_val::<i64>(Operand::Loc(8)), Ok(i64::MIN));
+    assert_eq!(exe.get_val::<i64>(Operand::Loc(16)), Ok(0));
+}
+
+#[test]
+fn executor_div_min_by_minus_one_checked() {
+    let functions = [Function {
+        frame_size: 4,
+        program: &[
+            Op::Set(
+                BinOp::new(Operand::Loc(0), Operand::Val(i32::MIN as UWord)),
+                OpType::I32,
+            ),
+            Op::Div(
+                BinOp::new(Operand::Loc(0), Operand::Val(-1_i32 as UWord)),
+                OpType::I32,
+            ),
+            Op::Mod(
+                BinOp::new(Operand::Loc(0), Operand::Val(-1_i32 as UWord)),
+                OpType::I32,
+            ),
+        ],
+    }];
+
+    let mut exe = Executor::new(&functions);
+    exe.set_checked(true);
+    exe.call(0, 0).unwrap();
+
+    assert_eq!(exe.execute(), Executed::Ok(ExecutionSuccess::Ok));
+    assert_eq!(
+        exe.execute(),
+        Executed::Err(ExecutionError::OperationOverflow)
+    );
+    exe.program_counter += 1; // Move manually after overflow
+
+    assert_eq!(
+        exe.execute(),
+        Executed::Err(ExecutionError::OperationOverflow)
+    );
+    assert_eq!(exe.get_val::<i32>(Operand::Loc(0)), Ok(i32::MIN));
+}
+
+#[test]
+fn executor_rol_ror() {
+    let functions = [Function {
+        frame_size: 4,
+        program: &[
+            Op::Set(
+                BinOp::new(Operand::Loc(0), Operand::Val(0x8000_0001)),
+                OpType::U32,
+            ),
+            Op::Rol(Operand::Loc(0), Operand::Val(4), OpType::U32),
+            Op::Ror(Operand::Loc(0), Operand::Val(8), OpType::U32),
+            Op::Rol(Operand::Loc(0), Operand::Val(1), OpType::F32),
+        ],
+    }];
+
+    let mut exe = Executor::new(&functions);
+    exe.call(0, 0).unwrap();
+
+    assert_eq!(exe.execute(), Executed::Ok(ExecutionSuccess::Ok));
+    assert_eq!(exe.execute(), Executed::Ok(ExecutionSuccess::Ok));
+    assert_eq!(exe.get_val::<u32>(Operand::Loc(0)), Ok(0x0000_0018));
+    assert_eq!(exe.execute(), Executed::Ok(ExecutionSuccess::Ok));
+    assert_eq!(exe.get_val::<u32>(Operand::Loc(0)), Ok(0x1800_0000));
+    assert_eq!(
+        exe.execute(),
+        Executed::Err(ExecutionError::IncorrectOperation(Op::Rol(
+            Operand::Loc(0),
+            Operand::Val(1),
+            OpType::F32
+        )))
+    );
+}
+
+#[test]
+fn executor_cnt_clz_ctz() {
+    let functions = [Function {
+        frame_size: 12,
+        program: &[
+            Op::Set(
+                BinOp::new(Operand::Loc(0), Operand::Val(0x00F0_0F80)),
+                OpType::U32,
+            ),
+            Op::Set(BinOp::new(Operand::Loc(4), Operand::Loc(0)), OpType::U32),
+            Op::Set(BinOp::new(Operand::Loc(8), Operand::Loc(0)), OpType::U32),
+            Op::Cnt(UnOp::new(Operand::Loc(0)), OpType::U32),
+            Op::Clz(UnOp::new(Operand::Loc(4)), OpType::U32),
+            Op::Ctz(UnOp::new(Operand::Loc(8)), OpType::U32),
+            Op::Cnt(UnOp::new(Operand::Loc(0)), OpType::F32),
+        ],
+    }];
+
+    let mut exe = Executor::new(&functions);
+    exe.call(0, 0).unwrap();
+
+    for _ in 0..6 {
+        assert_eq!(exe.execute(), Executed::Ok(ExecutionSuccess::Ok));
+    }
+
+    assert_eq!(exe.get_val::<u32>(Operand::Loc(0)), Ok(9));
+    assert_eq!(exe.get_val::<u32>(Operand::Loc(4)), Ok(8));
+    assert_eq!(exe.get_val::<u32>(Operand::Loc(8)), Ok(7));
+    assert_eq!(
+        exe.execute(),
+        Executed::Err(ExecutionError::IncorrectOperation(Op::Cnt(
+            UnOp::new(Operand::Loc(0)),
+            OpType::F32
+        )))
+    );
+}
+
+#[test]
+fn executor_read_bytes() {
+    let functions = [Function {
+        frame_size: 8,
+        program: &[Op::Set(
+            BinOp::new(Operand::Loc(4), Operand::Val(0x0A0B_0C0D)),
+            OpType::U32,
+        )],
+    }];
+
+    let mut exe = Executor::new(&functions);
+    exe.call(0, 0).unwrap();
+
+    assert_eq!(exe.execute(), Executed::Ok(ExecutionSuccess::Ok));
+    assert_eq!(exe.read_bytes(4, 4), Ok([0x0D, 0x0C, 0x0B, 0x0A].as_ref()));
+    assert_eq!(exe.read_word::<u16>(6), Ok(0x0A0B));
+    assert_eq!(
+        exe.read_bytes(4, 8),
+        Err(MemoryError::OutOfBounds {
+            addr: 4,
+            size: 8,
+            region: Region::Stack,
+        })
+    );
+    assert_eq!(
+        exe.read_word::<u32>(UWord::MAX),
+        Err(MemoryError::OutOfBounds {
+            addr: UWord::MAX,
+            size: 4,
+            region: Region::Heap,
+        })
+    );
+}
+
+#[test]
+#[cfg(feature = "debug")]
+fn executor_write_bytes() {
+    let functions = [Function {
+        frame_size: 4,
+        program: &[Op::Nop],
+    }];
+
+    let mut exe = Executor::new(&functions);
+    exe.call(0, 0).unwrap();
+
+    assert_eq!(exe.write_bytes(1, &[1, 2, 3]), Ok(()));
+    assert_eq!(exe.read_bytes(0, 4), Ok([0, 1, 2, 3].as_ref()));
+    assert_eq!(
+        exe.write_bytes(2, &[1, 2, 3]),
+        Err(MemoryError::OutOfBounds {
+            addr: 2,
+            size: 3,
+            region: Region::Stack,
+        })
+    );
+}
+
+#[test]
+fn executor_breakpoint() {
+    let functions = [
+        Function {
+            frame_size: 12,
+            program: &[
+                Op::Set(BinOp::new(Operand::Loc(4), Operand::Val(234)), OpType::U32),
+                Op::Set(BinOp::new(Operand::Loc(8), Operand::Val(533)), OpType::U32),
+                Op::App(Operand::Val(1)),
+                Op::Par(UnOp::new(Operand::Loc(4)), OpType::U32),
+                Op::Par(UnOp::new(Operand::Loc(8)), OpType::U32),
+                Op::Clf(Operand::Val(0)),
+                Op::Slp(Operand::Val(1)),
+                Op::End(Operand::Val(0)),
+            ],
+        },
+        Function {
+            // fn gcd
+            frame_size: 12,
+            program: &[
+                Op::Set(BinOp::new(Operand::Loc(8), Operand::Loc(0)), OpType::U32),
+                Op::Mod(BinOp::new(Operand::Loc(8), Operand::Loc(4)), OpType::U32),
+                Op::Set(BinOp::new(Operand::Loc(0), Operand::Loc(4)), OpType::U32),
+                Op::Set(BinOp::new(Operand::Loc(4), Operand::Loc(8)), OpType::U32),
+                Op::Ift(UnOp::new(Operand::Loc(4)), OpType::U32),
+                Op::Go(Operand::Val(0)),
+                Op::Ret(UnOp::new(Operand::Loc(0)), OpType::U32),
+            ],
+        },
+    ];
+
+    let mut exe = Executor::new(&functions);
+    exe.set_breakpoint(1, 1);
+    exe.set_breakpoint(0, 7);
+    exe.call(0, 0).unwrap();
+
+    assert_eq!(
+        exe.run_until_break(),
+        Ok(RunOutcome::Breakpoint {
+            function_id: 1,
+            pc: 1
+        })
+    );
+    assert_eq!(exe.get_val::<u32>(Operand::Loc(8)), Ok(234));
+
+    assert_eq!(
+        exe.run_until_break(),
+        Ok(RunOutcome::Breakpoint {
+            function_id: 1,
+            pc: 1
+        })
+    );
+    assert_eq!(exe.get_val::<u32>(Operand::Loc(8)), Ok(533));
+
+    exe.clear_breakpoint(1, 1);
+    let outcome = exe.run_until_break();
+    assert_eq!(outcome, Ok(RunOutcome::Sleep(exe.virtual_time() + 1)));
+    assert_eq!(
+        exe.run_until_break(),
+        Ok(RunOutcome::Breakpoint {
+            function_id: 0,
+            pc: 7
+        })
+    );
+    assert_eq!(exe.run_until_break(), Ok(RunOutcome::End(0)));
+    assert_eq!(exe.get_val::<u32>(Operand::Loc(0)), Ok(13));
+}
+
+#[test]
+fn executor_breakpoint_after_step() {
+    let functions = [Function::new(
+        0,
+        &[Op::Nop, Op::Nop, Op::Nop, Op::End(Operand::Val(0))],
+    )];
+
+    let mut exe = Executor::new(&functions);
+    exe.set_breakpoint(0, 1);
+    exe.set_breakpoint(0, 2);
+    exe.call(0, 0).unwrap();
+
+    assert_eq!(
+        exe.run_until_break(),
+        Ok(RunOutcome::Breakpoint {
+            function_id: 0,
+            pc: 1
+        })
+    );
+    assert_eq!(exe.execute(), Ok(ExecutionSuccess::Ok));
+    assert_eq!(
+        exe.run_until_break(),
+        Ok(RunOutcome::Breakpoint {
+            function_id: 0,
+            pc: 2
+        })
+    );
+    assert_eq!(exe.run_until_break(), Ok(RunOutcome::End(0)));
+}
+
+#[test]
+fn executor_trace() {
+    let functions = [Function {
+        frame_size: 1,
+        program: &[
+            Op::Iff(UnOp::new(Operand::Loc(0)), OpType::U8),
+            Op::Go(Operand::Val(3)),
+            Op::Nop,
+            Op::Ift(UnOp::new(Operand::Loc(0)), OpType::U8),
+            Op::Nop,
+            Op::Ret(UnOp::new(Operand::Emp), OpType::U8),
+        ],
+    }];
+
+    let mut traced = Vec::new();
+
+    {
+        let mut exe = Executor::new(&functions);
+        exe.set_trace(Box::new(|pc, &op| traced.push((pc, op))));
+        exe.call(0, 0).unwrap();
+
+        for _ in 0..4 {
+            assert_eq!(exe.execute(), Executed::Ok(ExecutionSuccess::Ok));
+        }
+
+        assert!(exe.call_stack.is_empty());
+    }
+
+    assert_eq!(
+        traced,
+        [
+            (0, functions[0].program[0]),
+            (1, functions[0].program[1]),
+            (3, functions[0].program[3]),
+            (5, functions[0].program[5]),
+        ]
+    );
+}
+
+#[test]
+fn executor_profile() {
+    let functions = [Function {
+        frame_size: std::mem::size_of::<UWord>() as UWord,
+        program: &[
+            Op::Inc(UnOp::new(Operand::Loc(0)), OpType::Uw),
+            Op::Ifl(BinOp::new(Operand::Loc(0), Operand::Val(3)), OpType::Uw),
+            Op::Go(Operand::Val(0)),
+            Op::End(Operand::Loc(0)),
+        ],
+    }];
+
+    let mut exe = Executor::new(&functions);
+    assert!(exe.profile().is_none());
+
+    exe.enable_profiling();
+    exe.call(0, 0).unwrap();
+
+    let mut executed = Executed::Ok(ExecutionSuccess::Ok);
+    while let Executed::Ok(ExecutionSuccess::Ok) = executed {
+        executed = exe.execute();
+    }
+
+    assert_eq!(executed, Executed::Ok(ExecutionSuccess::End(3)));
+
+    let profile = exe.profile().unwrap();
+    assert_eq!(profile.op_count(op_codes::INC), 3);
+    assert_eq!(profile.op_count(op_codes::IFL), 3);
+    assert_eq!(profile.op_count(op_codes::GO), 2);
+    assert_eq!(profile.op_count(op_codes::END), 1);
+    assert_eq!(profile.steps(), 9);
+    assert_eq!(profile.skipped(), 1);
+}
+
+#[test]
+fn executor_snapshot_restore() {
+    let size = std::mem::size_of::<UWord>() as UWord;
+    let functions = [
+        Function {
+            frame_size: size,
+            program: &[
+                Op::App(Operand::Val(1)),
+                Op::Clf(Operand::Loc(0)),
+                Op::Inc(UnOp::new(Operand::Loc(0)), OpType::Uw),
+                Op::End(Operand::Loc(0)),
+            ],
+        },
+        Function {
+            frame_size: size,
+            program: &[
+                Op::Set(BinOp::new(Operand::Loc(0), Operand::Val(5)), OpType::Uw),
+                Op::Ret(UnOp::new(Operand::Loc(0)), OpType::Uw),
+            ],
+        },
+    ];
+
+    let mut exe = Executor::new(&functions);
+    exe.call(0, 0).unwrap();
+    assert_eq!(exe.execute(), Ok(ExecutionSuccess::Ok));
+    assert_eq!(exe.execute(), Ok(ExecutionSuccess::Ok));
+
+    let snap = exe.snapshot();
+
+    let run = |exe: &mut Executor| loop {
+        match exe.execute() {
+            Ok(ExecutionSuccess::Ok) => continue,
+            executed => break executed,
+        }
+    };
+
+    assert_eq!(run(&mut exe), Ok(ExecutionSuccess::End(6)));
+    assert_eq!(run(&mut exe), Err(ExecutionError::EndOfProgram));
+
+    exe.restore(&snap).unwrap();
+    let ids: Vec<_> = exe.stack_frames().map(|f| f.function_id).collect();
+    assert_eq!(ids, [0, 1]);
+    assert_eq!(run(&mut exe), Ok(ExecutionSuccess::End(6)));
+
+    let other = [Function {
+        frame_size: 0,
+        program: &[],
+    }];
+
+    let mut exe = Executor::new(&other);
+    assert_eq!(exe.restore(&snap), Err(ExecutionError::UnknownFunction(1)));
+}
+
+#[test]
+fn executor_gcd_builder() {
+    use crate::executor::FunctionBuilder;
+
+    let main = FunctionBuilder::new(12)
+        .op(Op::Set(
+            BinOp::new(Operand::Loc(4), Operand::Val(234)),
+            OpType::U32,
+        ))
+        .op(Op::Set(
+            BinOp::new(Operand::Loc(8), Operand::Val(533)),
+            OpType::U32,
+        ))
+        .op(Op::App(Operand::Val(1)))
+        .op(Op::Par(UnOp::new(Operand::Loc(4)), OpType::U32))
+        .op(Op::Par(UnOp::new(Operand::Loc(8)), OpType::U32))
+        .op(Op::Clf(Operand::Val(0)))
+        .op(Op::End(Operand::Val(0)))
+        .build()
+        .unwrap();
+
+    let gcd = FunctionBuilder::new(12)
+        .label("loop")
+        .op(Op::Set(
+            BinOp::new(Operand::Loc(8), Operand::Loc(0)),
+            OpType::U32,
+        ))
+        .op(Op::Mod(
+            BinOp::new(Operand::Loc(8), Operand::Loc(4)),
+            OpType::U32,
+        ))
+        .op(Op::Set(
+            BinOp::new(Operand::Loc(0), Operand::Loc(4)),
+            OpType::U32,
+        ))
+        .op(Op::Set(
+            BinOp::new(Operand::Loc(4), Operand::Loc(8)),
+            OpType::U32,
+        ))
+        .op(Op::Ift(UnOp::new(Operand::Loc(4)), OpType::U32))
+        .go("loop")
+        .op(Op::Ret(UnOp::new(Operand::Loc(0)), OpType::U32))
+        .build()
+        .unwrap();
+
+    let functions = [main.function(), gcd.function()];
+
+    let mut exe = Executor::new(&functions);
+    exe.call(0, 0).unwrap();
+
+    let mut executed = Executed::Ok(ExecutionSuccess::Ok);
+    while let Executed::Ok(ExecutionSuccess::Ok) = executed {
+        executed = exe.execute();
+    }
+
+    assert_eq!(executed, Executed::Ok(ExecutionSuccess::End(0)));
+    assert_eq!(exe.get_val::<u32>(Operand::Loc(0)), Ok(13));
+}
+
+#[test]
+fn executor_call_stack_overflow() {
+    let functions = [Function {
+        frame_size: 4,
+        program: &[Op::App(Operand::Val(0)), Op::Clf(Operand::Loc(0))],
+    }];
+
+    let mut exe = Executor::new(&functions);
+    exe.set_max_call_depth(8);
+    exe.call(0, 0).unwrap();
+
+    let mut executed = Executed::Ok(ExecutionSuccess::Ok);
+    while let Executed::Ok(ExecutionSuccess::Ok) = executed {
+        executed = exe.execute();
+    }
+
+    assert_eq!(executed, Err(ExecutionError::CallStackOverflow));
+    assert_eq!(exe.memory.stack.len(), 8 * 4);
+}
+
+#[test]
+fn executor_lda_sta() {
+    let size = std::mem::size_of::<UWord>() as UWord;
+    let program = [
+        Op::Set(
+            BinOp::new(Operand::Loc(0), Operand::Ref(2 * size)),
+            OpType::Uw,
+        ),
+        Op::Sta(Operand::Loc(0), Operand::Val(0xABCD)),
+        Op::Lda(Operand::Loc(size), Operand::Loc(0)),
+    ];
+    let functions = [Function {
+        frame_size: 3 * size,
+        program: &program,
+    }];
+
+    let mut exe = Executor::new(&functions);
+    exe.call(0, 0).unwrap();
+
+    for _ in 0..3 {
+        assert_eq!(exe.execute(), Ok(ExecutionSuccess::Ok));
+    }
+
+    assert_eq!(exe.get_val::<UWord>(Operand::Loc(2 * size)), Ok(0xABCD));
+    assert_eq!(exe.get_val::<UWord>(Operand::Loc(size)), Ok(0xABCD));
+}
+
+#[test]
+fn executor_virtual_time() {
+    let functions = [Function {
+        frame_size: 0,
+        program: &[
+            Op::Nop,
+            Op::Nop,
+            Op::Slp(Operand::Val(10)),
+            Op::Slp(Operand::Val(0)),
+            Op::Slp(Operand::Val(UWord::MAX)),
+        ],
+    }];
+
+    let mut exe = Executor::new(&functions);
+    exe.call(0, 0).unwrap();
+    assert_eq!(exe.virtual_time(), 0);
+
+    assert_eq!(exe.execute(), Ok(ExecutionSuccess::Ok));
+    assert_eq!(exe.execute(), Ok(ExecutionSuccess::Ok));
+    assert_eq!(exe.virtual_time(), 2);
+
+    assert_eq!(exe.execute(), Ok(ExecutionSuccess::Sleep(13)));
+    assert_eq!(exe.execute(), Ok(ExecutionSuccess::Sleep(4)));
+    assert_eq!(exe.virtual_time(), 4);
+
+    exe.virtual_time = u64::MAX - 1;
+    assert_eq!(exe.execute(), Ok(ExecutionSuccess::Sleep(u64::MAX)));
+}
+
+#[test]
+fn executor_cost_table() {
+    let functions = [Function {
+        frame_size: 16,
+        program: &[
+            Op::Set(BinOp::new(Operand::Loc(0), Operand::Val(7)), OpType::U32),
+            Op::Div(BinOp::new(Operand::Loc(0), Operand::Val(2)), OpType::U32),
+            Op::Zer(Operand::Ref(4), Operand::Val(8)),
+            Op::End(Operand::Loc(0)),
+        ],
+    }];
+
+    let mut exe = Executor::new(&functions);
+    exe.call(0, 0).unwrap();
+    assert_eq!(exe.run_with_limit(4), Ok(RunOutcome::End(3)));
+    assert_eq!(exe.consumed_cost(), 4);
+
+    let mut table = CostTable::new();
+    table.set(op_codes::DIV, 10).set_per_byte(2);
+
+    let mut exe = Executor::new(&functions);
+    exe.set_cost_table(table);
+    exe.call(0, 0).unwrap();
+    assert_eq!(
+        exe.run_with_limit(20),
+        Err(ExecutionError::StepLimitExceeded)
+    );
+    assert_eq!(exe.consumed_cost(), 11);
+    assert_eq!(exe.run_with_limit(18), Ok(RunOutcome::End(3)));
+    assert_eq!(exe.consumed_cost(), 29);
+}
+
+#[test]
+fn executor_memory_op_cost() {
+    let functions = [Function {
+        frame_size: 16,
+        program: &[
+            Op::Fil(Operand::Ref(0), Operand::Val(1), Operand::Val(16)),
+            Op::Outb(Operand::Ref(0), Operand::Val(8)),
+            Op::Scn(
+                Operand::Loc(0),
+                Operand::Ref(0),
+                Operand::Val(1),
+                Operand::Val(4),
+            ),
+            Op::End(Operand::Val(0)),
+        ],
+    }];
+
+    let mut table = CostTable::new();
+    table.set_per_byte(2);
+
+    let mut exe = Executor::new(&functions);
+    exe.set_cost_table(table);
+    assert_eq!(exe.files.open(Vec::<u8>::new()), Ok(0));
+    exe.files.set_current(0).unwrap();
+    exe.call(0, 0).unwrap();
+    assert_eq!(exe.run_with_limit(60), Ok(RunOutcome::End(0)));
+    assert_eq!(exe.consumed_cost(), 60);
+}
+
+#[test]
+fn executor_swp() {
+    let functions = [Function {
+        frame_size: 8,
+        program: &[
+            Op::Set(BinOp::new(Operand::Loc(0), Operand::Val(12)), OpType::U32),
+            Op::Set(BinOp::new(Operand::Loc(4), Operand::Val(34)), OpType::U32),
+            Op::Swp(Operand::Loc(0), Operand::Loc(4), OpType::U32),
+            Op::Swp(Operand::Loc(0), Operand::Val(1), OpType::U32),
+        ],
+    }];
+
+    let mut exe = Executor::new(&functions);
+    exe.call(0, 0).unwrap();
+
+    for _ in 0..3 {
+        assert_eq!(exe.execute(), Ok(ExecutionSuccess::Ok));
+    }
+
+    assert_eq!(exe.get_val::<u32>(Operand::Loc(0)), Ok(34));
+    assert_eq!(exe.get_val::<u32>(Operand::Loc(4)), Ok(12));
+
+    assert_eq!(
+        exe.execute(),
+        Err(ExecutionError::IncorrectOperation(Op::Swp(
+            Operand::Loc(0),
+            Operand::Val(1),
+            OpType::U32
+        )))
+    );
+    assert_eq!(exe.get_val::<u32>(Operand::Loc(0)), Ok(34));
+}
+
+#[test]
+fn executor_cas() {
+    let functions = [Function {
+        frame_size: 8,
+        program: &[
+            Op::Set(BinOp::new(Operand::Loc(0), Operand::Val(5)), OpType::U32),
+            Op::Set(BinOp::new(Operand::Loc(4), Operand::Val(5)), OpType::U32),
+            Op::Cas(
+                Operand::Loc(0),
+                Operand::Loc(4),
+                Operand::Val(9),
+                OpType::U32,
+            ),
+            Op::Set(BinOp::new(Operand::Loc(4), Operand::Val(5)), OpType::U32),
+            Op::Cas(
+                Operand::Loc(0),
+                Operand::Loc(4),
+                Operand::Val(7),
+                OpType::U32,
+            ),
+        ],
+    }];
+
+    let mut exe = Executor::new(&functions);
+    exe.call(0, 0).unwrap();
+
+    for _ in 0..3 {
+        assert_eq!(exe.execute(), Ok(ExecutionSuccess::Ok));
+    }
+
+    assert_eq!(exe.get_val::<u32>(Operand::Loc(0)), Ok(9));
+    assert_eq!(exe.get_val::<u32>(Operand::Loc(4)), Ok(1));
+
+    for _ in 0..2 {
+        assert_eq!(exe.execute(), Ok(ExecutionSuccess::Ok));
+    }
+
+    assert_eq!(exe.get_val::<u32>(Operand::Loc(0)), Ok(9));
+    assert_eq!(exe.get_val::<u32>(Operand::Loc(4)), Ok(0));
+}
+
+#[test]
+fn executor_cas_incorrect_cmp() {
+    let ops = [
+        Op::Cas(
+            Operand::Loc(0),
+            Operand::Loc(0),
+            Operand::Val(9),
+            OpType::U32,
+        ),
+        Op::Cas(
+            Operand::Loc(0),
+            Operand::Val(5),
+            Operand::Val(9),
+            OpType::U32,
+        ),
+    ];
+
+    for &op in ops.iter() {
+        let program = [
+            Op::Set(BinOp::new(Operand::Loc(0), Operand::Val(5)), OpType::U32),
+            op,
+        ];
+        let functions = [Function::new(4, &program)];
+
+        let mut exe = Executor::new(&functions);
+        exe.call(0, 0).unwrap();
+
+        assert_eq!(exe.execute(), Ok(ExecutionSuccess::Ok));
+        assert_eq!(exe.execute(), Err(ExecutionError::IncorrectOperation(op)));
+        assert_eq!(exe.get_val::<u32>(Operand::Loc(0)), Ok(5));
+    }
+}
+
+#[test]
+fn executor_cas_wide_flag() {
+    let program = [
+        Op::Cas(
+            Operand::Loc(0),
+            Operand::Loc(8),
+            Operand::Loc(16),
+            OpType::U64,
+        ),
+        Op::Cas(
+            Operand::Loc(24),
+            Operand::Loc(28),
+            Operand::Loc(16),
+            OpType::U32,
+        ),
+    ];
+    let functions = [Function::new(32, &program)];
+
+    let mut exe = Executor::new(&functions);
+    exe.call(0, 0).unwrap();
+    exe.set_val::<u64>(Operand::Loc(0), 0xAB00_0000_0000_0005)
+        .unwrap();
+    exe.set_val::<u64>(Operand::Loc(8), 0xAB00_0000_0000_0005)
+        .unwrap();
+    exe.set_val::<u64>(Operand::Loc(16), 7).unwrap();
+    exe.set_val::<u32>(Operand::Loc(24), 0xCD00_0005).unwrap();
+    exe.set_val::<u32>(Operand::Loc(28), 0xEF00_0005).unwrap();
+
+    assert_eq!(exe.execute(), Ok(ExecutionSuccess::Ok));
+    assert_eq!(exe.get_val::<u64>(Operand::Loc(0)), Ok(7));
+    assert_eq!(exe.get_val::<u64>(Operand::Loc(8)), Ok(1));
+
+    assert_eq!(exe.execute(), Ok(ExecutionSuccess::Ok));
+    assert_eq!(exe.get_val::<u32>(Operand::Loc(24)), Ok(0xCD00_0005));
+    assert_eq!(exe.get_val::<u32>(Operand::Loc(28)), Ok(0));
+}
+
+#[test]
+fn executor_glb_ind() {
+    let size = std::mem::size_of::<UWord>() as UWord;
+    let functions = [Function {
+        frame_size: size + 4,
+        program: &[
+            Op::Set(BinOp::new(Operand::Loc(0), Operand::Ref(size)), OpType::Uw),
+            Op::Set(
+                BinOp::new(Operand::GlbInd(0), Operand::Val(77)),
+                OpType::U32,
+            ),
+            Op::Set(BinOp::new(Operand::Loc(0), Operand::GlbInd(0)), OpType::U32),
+            Op::Set(BinOp::new(Operand::GlbInd(0), Operand::Val(1)), OpType::U32),
+        ],
+    }];
+
+    let mut exe = Executor::new(&functions);
+    exe.call(0, 0).unwrap();
+
+    assert_eq!(exe.execute(), Ok(ExecutionSuccess::Ok));
+    assert_eq!(exe.execute(), Ok(ExecutionSuccess::Ok));
+    assert_eq!(exe.get_val::<u32>(Operand::Loc(size)), Ok(77));
+
+    assert_eq!(exe.execute(), Ok(ExecutionSuccess::Ok));
+    assert_eq!(exe.get_val::<u32>(Operand::Loc(0)), Ok(77));
+
+    assert_eq!(exe.set_val(Operand::Loc(0), 0 as UWord), Ok(()));
+    assert_eq!(exe.execute(), Err(ExecutionError::NullPointerDereference));
+}
+
+#[test]
+fn executor_shl_offset() {
+    let functions = [Function {
+        frame_size: 4 + std::mem::size_of::<UWord>() as UWord,
+        program: &[
+            // u8[4] arr
+            // uw i
+            Op::Set(
+                BinOp::new(Operand::Loc(0), Operand::Val(0x0101_0101)),
+                OpType::U32,
+            ),
+            Op::Shl(
+                BinOp::new(Operand::Loc(0), Operand::Loc(4)).with_first(Operand::Loc(4)),
+                OpType::U8,
+            ),
+            Op::Inc(UnOp::new(Operand::Loc(4)), OpType::Uw),
+            Op::Ifl(BinOp::new(Operand::Loc(4), Operand::Val(4)), OpType::Uw),
+            Op::Go(Operand::Val(1)),
+            Op::End(Operand::Emp),
+        ],
+    }];
+
+    let mut exe = Executor::new(&functions);
+    exe.call(0, 0).unwrap();
+
+    let mut executed = Executed::Ok(ExecutionSuccess::Ok);
+    while let Executed::Ok(ExecutionSuccess::Ok) = executed {
+        executed = exe.execute();
+    }
+
+    assert_eq!(exe.read_bytes(0, 4), Ok([1, 2, 4, 8].as_ref()));
+}
+
+#[test]
+fn executor_min_max() {
+    let functions = [Function {
+        frame_size: 24,
+        program: &[
+            Op::Set(BinOp::new(Operand::Loc(0), Operand::Val(3)), OpType::I32),
+            Op::Neg(UnOp::new(Operand::Loc(0)), OpType::I32),
+            Op::Set(BinOp::new(Operand::Loc(4), Operand::Loc(0)), OpType::I32),
+            Op::Min(BinOp::new(Operand::Loc(0), Operand::Val(2)), OpType::I32),
+            Op::Max(BinOp::new(Operand::Loc(4), Operand::Val(2)), OpType::I32),
+            Op::Min(BinOp::new(Operand::Loc(8), Operand::Loc(16)), OpType::F64),
+            Op::Max(BinOp::new(Operand::Loc(8), Operand::Loc(16)), OpType::F64),
+            Op::Min(BinOp::new(Operand::Loc(16), Operand::Loc(8)), OpType::F64),
+        ],
+    }];
+
+    let mut exe = Executor::new(&functions);
+    exe.call(0, 0).unwrap();
+    exe.set_val(Operand::Loc(8), 1.5_f64).unwrap();
+    exe.set_val(Operand::Loc(16), -2.5_f64).unwrap();
+
+    for _ in 0..6 {
+        assert_eq!(exe.execute(), Ok(ExecutionSuccess::Ok));
+    }
+
+    assert_eq!(exe.get_val::<i32>(Operand::Loc(0)), Ok(-3));
+    assert_eq!(exe.get_val::<i32>(Operand::Loc(4)), Ok(2));
+    assert_eq!(exe.get_val::<f64>(Operand::Loc(8)), Ok(-2.5));
+
+    exe.set_val(Operand::Loc(16), f64::NAN).unwrap();
+    assert_eq!(exe.execute(), Ok(ExecutionSuccess::Ok));
+    assert_eq!(exe.get_val::<f64>(Operand::Loc(8)), Ok(-2.5));
+
+    assert_eq!(exe.execute(), Ok(ExecutionSuccess::Ok));
+    assert_eq!(exe.get_val::<f64>(Operand::Loc(16)), Ok(-2.5));
+}
+
+#[test]
+fn executor_abs() {
+    let functions = [Function {
+        frame_size: 16,
+        program: &[
+            Op::Set(BinOp::new(Operand::Loc(0), Operand::Val(5)), OpType::I32),
+            Op::Neg(UnOp::new(Operand::Loc(0)), OpType::I32),
+            Op::Abs(UnOp::new(Operand::Loc(0)), OpType::I32),
+            Op::Abs(UnOp::new(Operand::Loc(8)), OpType::F64),
+            Op::Abs(UnOp::new(Operand::Loc(4)), OpType::I32),
+        ],
+    }];
+
+    let mut exe = Executor::new(&functions);
+    exe.call(0, 0).unwrap();
+    exe.set_val(Operand::Loc(8), -3.5_f64).unwrap();
+    exe.set_val(Operand::Loc(4), i32::MIN).unwrap();
+
+    for _ in 0..5 {
+        assert_eq!(exe.execute(), Ok(ExecutionSuccess::Ok));
+    }
+
+    assert_eq!(exe.get_val::<i32>(Operand::Loc(0)), Ok(5));
+    assert_eq!(exe.get_val::<f64>(Operand::Loc(8)), Ok(3.5));
+    assert_eq!(exe.get_val::<i32>(Operand::Loc(4)), Ok(i32::MIN));
+}
+
+#[test]
+fn executor_stack_frames() {
+    let functions = [
+        Function {
+            frame_size: 4,
+            program: &[Op::App(Operand::Val(1)), Op::Clf(Operand::Loc(0))],
+        },
+        Function {
+            frame_size: 8,
+            program: &[Op::Nop],
+        },
+    ];
+
+    let mut exe = Executor::new(&functions);
+    assert_eq!(exe.stack_frames().count(), 0);
+
+    exe.call(0, 0).unwrap();
+    assert_eq!(exe.execute(), Ok(ExecutionSuccess::Ok));
+    assert_eq!(exe.execute(), Ok(ExecutionSuccess::Ok));
+
+    let frames: Vec<_> = exe.stack_frames().collect();
+    assert_eq!(
+        frames,
+        [
+            FrameInfo {
+                function_id: 0,
+                base_ptr: 0,
+                ret_val_ptr: 0,
+                ret_program_counter: 1,
+            },
+            FrameInfo {
+                function_id: 1,
+                base_ptr: 4,
+                ret_val_ptr: 0,
+                ret_program_counter: 2,
+            },
+        ]
+    );
+}
+
+#[test]
+fn executor_abort_prepared_call() {
+    let size = std::mem::size_of::<UWord>() as UWord;
+    let functions = [
+        Function {
+            frame_size: size,
+            program: &[
+                Op::App(Operand::Val(1)),
+                Op::Par(UnOp::new(Operand::Val(7)), OpType::U32),
+                Op::Par(UnOp::new(Operand::Glb(1000)), OpType::U32),
+                Op::App(Operand::Val(1)),
+                Op::Par(UnOp::new(Operand::Val(9)), OpType::U32),
+                Op::Clf(Operand::Loc(0)),
+                Op::End(Operand::Loc(0)),
+            ],
+        },
+        Function {
+            frame_size: 8,
+            program: &[Op::Ret(UnOp::new(Operand::Loc(0)), OpType::U32)],
+        },
+    ];
+
+    let mut exe = Executor::new(&functions);
+    exe.call(0, 0).unwrap();
+
+    assert_eq!(exe.execute(), Ok(ExecutionSuccess::Ok));
+    assert_eq!(exe.execute(), Ok(ExecutionSuccess::Ok));
+    assert!(matches!(
+        exe.execute(),
+        Err(ExecutionError::MemoryError(MemoryError::OutOfBounds { .. }))
+    ));
+
+    assert_eq!(exe.abort_prepared_call(), Ok(()));
+    assert_eq!(exe.stack_frames().count(), 1);
+    assert_eq!(exe.memory.stack.len(), size);
+
+    exe.program_counter += 1;
+
+    let mut executed = Executed::Ok(ExecutionSuccess::Ok);
+    while let Executed::Ok(ExecutionSuccess::Ok) = executed {
+        executed = exe.execute();
+    }
+
+    assert_eq!(executed, Ok(ExecutionSuccess::End(9)));
+}
+
+#[test]
+fn executor_eof() {
+    use std::collections::vec_deque::VecDeque;
+
+    let functions = [Function {
+        frame_size: 2 + std::mem::size_of::<UWord>() as UWord,
+        program: &[
+            // u8 eof
+            // u8 byte
+            // u8 sum
+            Op::Eof(Operand::Loc(0)),
+            Op::Ift(UnOp::new(Operand::Loc(0)), OpType::U8),
+            Op::Go(Operand::Val(6)),
+            Op::In(BinOp::new(Operand::Loc(1), Operand::Emp)),
+            Op::Add(BinOp::new(Operand::Loc(2), Operand::Loc(1)), OpType::U8),
+            Op::Go(Operand::Val(0)),
+            Op::End(Operand::Loc(2)),
+        ],
+    }];
+
+    let mut exe = Executor::new(&functions);
+    let file: VecDeque<u8> = vec![1, 2, 3, 4].into();
+
+    assert_eq!(exe.files.open(file), Ok(0));
+    exe.files.set_current(0).unwrap();
+    exe.call(0, 0).unwrap();
+
+    let mut executed = Executed::Ok(ExecutionSuccess::Ok);
+    while let Executed::Ok(ExecutionSuccess::Ok) = executed {
+        executed = exe.execute();
+    }
+
+    assert_eq!(executed, Executed::Ok(ExecutionSuccess::End(10)));
+}
+
+#[test]
+fn executor_inb_outb() {
+    use std::collections::vec_deque::VecDeque;
+
+    let size = std::mem::size_of::<UWord>() as UWord;
+    let functions = [Function {
+        frame_size: size + 1024,
+        program: &[
+            // uw len
+            // u8[1024] buf
+            Op::Set(BinOp::new(Operand::Loc(0), Operand::Val(1024)), OpType::Uw),
+            Op::Inb(Operand::Ref(size), Operand::Loc(0)),
+            Op::Sfd(Operand::Val(1)),
+            Op::Outb(Operand::Ref(size), Operand::Loc(0)),
+        ],
+    }];
+
+    let input: Vec<u8> = (0..1024).map(|i| i as u8).collect();
+
+    let mut exe = Executor::new(&functions);
+    let file: VecDeque<u8> = input.clone().into();
+    assert_eq!(exe.files.open(file), Ok(0));
+    assert_eq!(exe.files.open(Vec::<u8>::new()), Ok(1));
+    exe.files.set_current(0).unwrap();
+    exe.call(0, 0).unwrap();
+
+    for _ in 0..4 {
+        assert_eq!(exe.execute(), Ok(ExecutionSuccess::Ok));
+    }
+
+    assert_eq!(exe.get_val::<UWord>(Operand::Loc(0)), Ok(1024));
+
+    let file = exe.files.close(1).unwrap();
+    let output = file.as_any().downcast_ref::<Vec<u8>>().unwrap();
+    assert_eq!(*output, input);
+}
+
+#[test]
+fn executor_trp() {
+    let functions = [Function {
+        frame_size: std::mem::size_of::<UWord>() as UWord,
+        program: &[
+            Op::Trp(Operand::Val(0)),
+            Op::Set(BinOp::new(Operand::Loc(0), Operand::Val(12)), OpType::Uw),
+            Op::Trp(Operand::Loc(0)),
+            Op::End(Operand::Val(0)),
+        ],
+    }];
+
+    let mut exe = Executor::new(&functions);
+    exe.call(0, 0).unwrap();
+
+    assert_eq!(exe.execute(), Ok(ExecutionSuccess::Ok));
+    assert_eq!(exe.execute(), Ok(ExecutionSuccess::Ok));
+    assert_eq!(exe.execute(), Err(ExecutionError::Trap(12)));
+}
+
+#[test]
+fn executor_malformed_condition() {
+    let functions = [
+        Function {
+            frame_size: 1,
+            program: &[Op::Nop, Op::Ift(UnOp::new(Operand::Loc(0)), OpType::U8)],
+        },
+        Function {
+            frame_size: 1,
+            program: &[
+                Op::Ift(UnOp::new(Operand::Loc(0)), OpType::U8),
+                Op::Iff(UnOp::new(Operand::Loc(0)), OpType::U8),
+            ],
+        },
+    ];
+
+    let mut exe = Executor::new(&functions);
+    exe.call(0, 0).unwrap();
+    assert_eq!(exe.execute(), Ok(ExecutionSuccess::Ok));
+    assert_eq!(exe.execute(), Err(ExecutionError::MalformedCondition));
+
+    let mut exe = Executor::new(&functions);
+    exe.call(1, 0).unwrap();
+    assert_eq!(exe.execute(), Err(ExecutionError::MalformedCondition));
+}
+
+#[test]
+fn executor_chained_conditions() {
+    let size = std::mem::size_of::<UWord>() as UWord;
+    let functions = [Function {
+        frame_size: size + 1,
+        program: &[
+            Op::Ifl(BinOp::new(Operand::Loc(size), Operand::Val(10)), OpType::U8),
+            Op::Ifg(BinOp::new(Operand::Loc(size), Operand::Val(5)), OpType::U8),
+            Op::Set(BinOp::new(Operand::Loc(0), Operand::Val(1)), OpType::Uw),
+            Op::End(Operand::Loc(0)),
+        ],
+    }];
+
+    for &(x, res) in [(7_u8, 1), (3, 0), (12, 0)].iter() {
+        let mut exe = Executor::new(&functions);
+        exe.call(0, 0).unwrap();
+        exe.set_val(Operand::Loc(size), x).unwrap();
+
+        assert_eq!(exe.run_until_break(), Ok(RunOutcome::End(res)));
+    }
+
+    let mut exe = Executor::new(&functions);
+    exe.call(0, 0).unwrap();
+    exe.set_val(Operand::Loc(size), 12_u8).unwrap();
+
+    assert_eq!(exe.execute(), Ok(ExecutionSuccess::Ok));
+    assert_eq!(exe.program_counter, 3);
+}
+
+#[test]
+fn executor_skp() {
+    let size = std::mem::size_of::<UWord>() as UWord;
+    let functions = [Function {
+        frame_size: size + 1,
+        program: &[
+            // if x { 1 } else { 2 }
+            Op::Iff(UnOp::new(Operand::Loc(size)), OpType::U8),
+            Op::Skp(Operand::Val(2)),
+            Op::Set(BinOp::new(Operand::Loc(0), Operand::Val(1)), OpType::Uw),
+            Op::Skp(Operand::Val(1)),
+            Op::Set(BinOp::new(Operand::Loc(0), Operand::Val(2)), OpType::Uw),
+            Op::End(Operand::Loc(0)),
+        ],
+    }];
+
+    for &(x, res) in [(1_u8, 1), (0, 2)].iter() {
+        let mut exe = Executor::new(&functions);
+        exe.call(0, 0).unwrap();
+        exe.set_val(Operand::Loc(size), x).unwrap();
+
+        assert_eq!(exe.run_until_break(), Ok(RunOutcome::End(res)));
+    }
+}
+
+#[test]
+fn executor_negative_immediates() {
+    let size = std::mem::size_of::<UWord>() as UWord;
+    let ops = [
+        Op::Ifl(
+            BinOp::new(Operand::Val(-1 as IWord as UWord), Operand::Val(0)),
+            OpType::I8,
+        ),
+        Op::Ifl(BinOp::new(Operand::Val(0xFF), Operand::Val(0)), OpType::I8),
+        Op::Ifl(
+            BinOp::new(Operand::Val(-1 as IWord as UWord), Operand::Val(0)),
+            OpType::I16,
+        ),
+        Op::Ifl(
+            BinOp::new(Operand::Val(0xFFFF), Operand::Val(0)),
+            OpType::I16,
+        ),
+        Op::Ifl(
+            BinOp::new(Operand::Val(-1 as IWord as UWord), Operand::Val(0)),
+            OpType::I32,
+        ),
+        Op::Ifl(
+            BinOp::new(Operand::Val(-1 as IWord as UWord), Operand::Val(0)),
+            OpType::I64,
+        ),
+        Op::Ifl(
+            BinOp::new(Operand::Val(-1 as IWord as UWord), Operand::Val(0)),
+            OpType::Iw,
+        ),
+        Op::Ifg(
+            BinOp::new(Operand::Val(-1 as IWord as UWord), Operand::Val(0)),
+            OpType::U64,
+        ),
+        Op::Ifg(BinOp::new(Operand::Val(0xFF), Operand::Val(0)), OpType::I16),
+    ];
+
+    for &op in ops.iter() {
+        let program = [
+            op,
+            Op::Set(BinOp::new(Operand::Loc(0), Operand::Val(1)), OpType::Uw),
+            Op::End(Operand::Loc(0)),
+        ];
+        let functions = [Function {
+            frame_size: size,
+            program: &program,
+        }];
+
+        let mut exe = Executor::new(&functions);
+        exe.call(0, 0).unwrap();
+
+        assert_eq!(exe.run_until_break(), Ok(RunOutcome::End(1)), "{:?}", op);
+    }
+
+    let functions = [Function {
+        frame_size: 8,
+        program: &[Op::Set(
+            BinOp::new(Operand::Loc(0), Operand::Val(-2 as IWord as UWord)),
+            OpType::I64,
+        )],
+    }];
+
+    let mut exe = Executor::new(&functions);
+    exe.call(0, 0).unwrap();
+    assert_eq!(exe.execute(), Ok(ExecutionSuccess::Ok));
+    assert_eq!(exe.get_val::<i64>(Operand::Loc(0)), Ok(-2));
+}
+
+#[test]
+fn executor_sel() {
+    let functions = [Function {
+        frame_size: 3,
+        program: &[
+            Op::Sel(
+                Operand::Loc(0),
+                Operand::Val(1),
+                Operand::Val(10),
+                Operand::Val(20),
+                OpType::U8,
+            ),
+            Op::Sel(
+                Operand::Loc(1),
+                Operand::Loc(2),
+                Operand::Val(10),
+                Operand::Val(20),
+                OpType::U8,
+            ),
+        ],
+    }];
+
+    let mut exe = Executor::new(&functions);
+    exe.call(0, 0).unwrap();
+
+    assert_eq!(exe.execute(), Ok(ExecutionSuccess::Ok));
+    assert_eq!(exe.execute(), Ok(ExecutionSuccess::Ok));
+    assert_eq!(exe.get_val::<u8>(Operand::Loc(0)), Ok(10));
+    assert_eq!(exe.get_val::<u8>(Operand::Loc(1)), Ok(20));
+}
+
+#[test]
+fn executor_gip() {
+    let size = std::mem::size_of::<UWord>() as UWord;
+    let functions = [Function {
+        frame_size: size * 2,
+        program: &[
+            Op::Nop,
+            Op::Gip(Operand::Loc(0)),
+            Op::Gip(Operand::Loc(size)),
+        ],
+    }];
+
+    let mut exe = Executor::new(&functions);
+    exe.call(0, 0).unwrap();
+
+    for _ in 0..3 {
+        assert_eq!(exe.execute(), Ok(ExecutionSuccess::Ok));
+    }
+
+    assert_eq!(exe.get_val::<UWord>(Operand::Loc(0)), Ok(1));
+    assert_eq!(exe.get_val::<UWord>(Operand::Loc(size)), Ok(2));
+}
+
+#[test]
+fn executor_gor() {
+    let back = |n: IWord| Operand::Val(-n as UWord);
+    let functions = [Function {
+        frame_size: std::mem::size_of::<UWord>() as UWord,
+        program: &[
+            Op::Set(BinOp::new(Operand::Loc(0), Operand::Val(0)), OpType::Uw),
+            Op::Gor(Operand::Val(2)),
+            Op::End(Operand::Loc(0)),
+            Op::Inc(UnOp::new(Operand::Loc(0)), OpType::Uw),
+            // Conditional relative jump
+            Op::Ifl(BinOp::new(Operand::Loc(0), Operand::Val(5)), OpType::Uw),
+            Op::Gor(back(2)),
+            Op::Gor(back(4)),
+        ],
+    }];
+
+    let mut exe = Executor::new(&functions);
+    exe.call(0, 0).unwrap();
+
+    assert_eq!(exe.run_until_break(), Ok(RunOutcome::End(5)));
+}
+
+#[test]
+fn executor_tap() {
+    let size = std::mem::size_of::<UWord>() as UWord;
+    let functions = [Function {
+        frame_size: size * 2,
+        program: &[
+            // sum(n, acc)
+            Op::Ife(BinOp::new(Operand::Loc(0), Operand::Val(0)), OpType::Uw),
+            Op::End(Operand::Loc(size)),
+            Op::Add(BinOp::new(Operand::Loc(size), Operand::Loc(0)), OpType::Uw),
+            Op::Dec(UnOp::new(Operand::Loc(0)), OpType::Uw),
+            Op::Tap(Operand::Val(0)),
+            Op::Par(UnOp::new(Operand::Loc(0)), OpType::Uw),
+            Op::Par(UnOp::new(Operand::Loc(size)), OpType::Uw),
+            Op::Clf(Operand::Val(0)),
+        ],
+    }];
+
+    let mut exe = Executor::new(&functions);
+    exe.set_max_call_depth(1);
+    exe.call(0, 0).unwrap();
+    exe.set_val(Operand::Loc(0), 100 as UWord).unwrap();
+
+    let res = loop {
+        match exe.execute() {
+            Ok(ExecutionSuccess::End(res)) => break res,
+            Ok(ExecutionSuccess::Ok) => {
+                if !exe.prepared_call {
+                    assert_eq!(exe.call_stack.len(), 1);
+                }
+            }
+            err => panic!("{:?}", err),
+        }
+    };
+
+    assert_eq!(res, 5050);
+    assert_eq!(exe.memory.stack.len(), size * 2);
+}
+
+#[test]
+fn executor_invoke() {
+    let size = std::mem::size_of::<UWord>() as UWord;
+    let functions = [Function {
+        frame_size: size * 3,
+        program: &[
+            Op::Add(BinOp::new(Operand::Loc(0), Operand::Loc(size)), OpType::Uw),
+            Op::Add(
+                BinOp::new(Operand::Loc(0), Operand::Loc(size * 2)),
+                OpType::Uw,
+            ),
+            Op::End(Operand::Loc(0)),
+        ],
+    }];
+
+    let mut exe = Executor::new(&functions);
+    assert_eq!(
+        exe.invoke(0, &[1, 2, 3, 4], 0),
+        Err(ExecutionError::ParameterOverflow)
+    );
+    assert_eq!(
+        exe.invoke(1, &[], 0),
+        Err(ExecutionError::UnknownFunction(1))
+    );
+    assert!(exe.call_stack.is_empty());
+
+    exe.invoke(0, &[10, 20, 30], 0).unwrap();
+    assert_eq!(exe.run_until_break(), Ok(RunOutcome::End(60)));
+}
+
+#[test]
+fn executor_return_value() {
+    // The arguments are passed as words
+    let size = std::mem::size_of::<UWord>() as UWord;
+    let (a, b, c) = (Operand::Loc(0), Operand::Loc(size), Operand::Loc(2 * size));
+    let gcd = [
+        Op::Set(BinOp::new(c, a), OpType::U32),
+        Op::Mod(BinOp::new(c, b), OpType::U32),
+        Op::Set(BinOp::new(a, b), OpType::U32),
+        Op::Set(BinOp::new(b, c), OpType::U32),
+        Op::Ift(UnOp::new(b), OpType::U32),
+        Op::Go(Operand::Val(0)),
+        Op::Ret(UnOp::new(a), OpType::U32),
+    ];
+    let functions = [
+        Function {
+            frame_size: 3 * size,
+            program: &gcd,
+        },
+        Function {
+            // fn double(f32)
+            frame_size: size,
+            program: &[
+                Op::Add(BinOp::new(Operand::Loc(0), Operand::Loc(0)), OpType::F32),
+                Op::Ret(UnOp::new(Operand::Loc(0)), OpType::F32),
+            ],
+        },
+    ];
+
+    let run = |exe: &mut Executor| loop {
+        match exe.execute() {
+            Ok(ExecutionSuccess::Ok) => (),
+            Err(ExecutionError::EndOfProgram) => break,
+            res => panic!("{:?}", res),
+        }
+    };
+
+    let mut exe = Executor::new(&functions);
+    // The return slot
+    exe.memory.stack.expand(4).unwrap();
+
+    exe.invoke(0, &[234, 533], 0).unwrap();
+    run(&mut exe);
+    assert_eq!(exe.return_value::<u32>(0), Ok(13));
+
+    exe.invoke(1, &[1.5_f32.to_bits() as UWord], 0).unwrap();
+    run(&mut exe);
+    assert_eq!(exe.return_value::<f32>(0), Ok(3.0));
+}
+
+#[test]
+fn executor_stack_policy() {
+    let size = std::mem::size_of::<UWord>() as UWord;
+    let functions = [
+        Function {
+            frame_size: size * 2,
+            program: &[
+                Op::App(Operand::Val(1)),
+                Op::Clf(Operand::Val(0)),
+                Op::App(Operand::Val(1)),
+                Op::Clf(Operand::Val(size)),
+                Op::End(Operand::Loc(size)),
+            ],
+        },
+        Function {
+            frame_size: size,
+            program: &[
+                Op::Inc(UnOp::new(Operand::Loc(0)), OpType::Uw),
+                Op::Ret(UnOp::new(Operand::Loc(0)), OpType::Uw),
+            ],
+        },
+    ];
+
+    let mut exe = Executor::new(&functions);
+    exe.call(0, 0).unwrap();
+    assert_eq!(exe.run_until_break(), Ok(RunOutcome::End(1)));
+
+    let mut exe = Executor::new(&functions);
+    exe.set_stack_policy(StackPolicy::Uninitialized);
+    exe.call(0, 0).unwrap();
+    assert_eq!(exe.run_until_break(), Ok(RunOutcome::End(2)));
+}
+
+#[test]
+fn executor_scn() {
+    let size = std::mem::size_of::<UWord>() as UWord;
+    let functions = [Function {
+        frame_size: size + 6,
+        program: &[
+            // uw offset
+            // u8[6] buf
+            Op::Set(
+                BinOp::new(Operand::Loc(size), Operand::Val('H' as UWord)),
+                OpType::U8,
+            ),
+            Op::Set(
+                BinOp::new(Operand::Loc(size + 1), Operand::Val('i' as UWord)),
+                OpType::U8,
+            ),
+            Op::Set(
+                BinOp::new(Operand::Loc(size + 2), Operand::Val('!' as UWord)),
+                OpType::U8,
+            ),
+            Op::Scn(
+                Operand::Loc(0),
+                Operand::Ref(size),
+                Operand::Val('!' as UWord),
+                Operand::Val(6),
+            ),
+        ],
+    }];
+
+    let mut exe = Executor::new(&functions);
+    exe.call(0, 0).unwrap();
+
+    for _ in 0..4 {
+        assert_eq!(exe.execute(), Ok(ExecutionSuccess::Ok));
+    }
+
+    assert_eq!(exe.get_val::<UWord>(Operand::Loc(0)), Ok(2));
+}
+
+#[test]
+fn executor_fil() {
+    let functions = [Function {
+        frame_size: 8,
+        program: &[Op::Fil(
+            Operand::Ref(1),
+            Operand::Val(0xAB),
+            Operand::Val(6),
+        )],
+    }];
+
+    let mut exe = Executor::new(&functions);
+    exe.call(0, 0).unwrap();
+
+    assert_eq!(exe.execute(), Ok(ExecutionSuccess::Ok));
+    assert_eq!(
+        exe.read_bytes(0, 8),
+        Ok([0, 0xAB, 0xAB, 0xAB, 0xAB, 0xAB, 0xAB, 0].as_ref())
+    );
+}
+
+#[test]
+fn executor_nan_conditions() {
+    let nan = Operand::Loc(0);
+    let one = Operand::Loc(8);
+    let res = Operand::Loc(16);
+    let cases = [
+        (Op::Ifn(UnOp::new(nan), OpType::F64), 1),
+        (Op::Ifn(UnOp::new(one), OpType::F64), 0),
+        (Op::Ifl(BinOp::new(nan, one), OpType::F64), 0),
+        (Op::Ifl(BinOp::new(one, nan), OpType::F64), 0),
+        (Op::Ifg(BinOp::new(nan, one), OpType::F64), 0),
+        (Op::Ifg(BinOp::new(one, nan), OpType::F64), 0),
+        (Op::Ife(BinOp::new(nan, nan), OpType::F64), 0),
+        (Op::Ine(BinOp::new(nan, nan), OpType::F64), 1),
+    ];
+
+    for &(op, expected) in cases.iter() {
+        let program = [
+            op,
+            Op::Set(BinOp::new(res, Operand::Val(1)), OpType::Uw),
+            Op::End(res),
+        ];
+        let functions = [Function {
+            frame_size: 16 + std::mem::size_of::<UWord>() as UWord,
+            program: &program,
+        }];
+
+        let mut exe = Executor::new(&functions);
+        exe.call(0, 0).unwrap();
+        exe.set_val(nan, f64::NAN).unwrap();
+        exe.set_val(one, 1_f64).unwrap();
+
+        assert_eq!(
+            exe.run_until_break(),
+            Ok(RunOutcome::End(expected)),
+            "{:?}",
+            op
+        );
+    }
+
+    let functions = [Function {
+        frame_size: 1,
+        program: &[Op::Ifn(UnOp::new(Operand::Loc(0)), OpType::U8), Op::Nop],
+    }];
+
+    let mut exe = Executor::new(&functions);
+    exe.call(0, 0).unwrap();
+    assert_eq!(
+        exe.execute(),
+        Err(ExecutionError::IncorrectOperation(functions[0].program[0]))
+    );
+}
+
+#[test]
+fn executor_canonical_nan() {
+    let functions = [Function {
+        frame_size: 16 + std::mem::size_of::<UWord>() as UWord,
+        program: &[
+            Op::Set(BinOp::new(Operand::Loc(8), Operand::Loc(0)), OpType::F32),
+            Op::Set(BinOp::new(Operand::Loc(12), Operand::Loc(4)), OpType::F32),
+            Op::Cmp(Operand::Ref(8), Operand::Ref(12), Operand::Val(4)),
+            Op::Set(BinOp::new(Operand::Loc(16), Operand::Val(1)), OpType::Uw),
+            Op::End(Operand::Loc(16)),
+        ],
+    }];
+
+    for &(canonical, res) in [(false, 0), (true, 1)].iter() {
+        let mut exe = Executor::new(&functions);
+        exe.set_canonical_nan(canonical);
+        exe.call(0, 0).unwrap();
+        exe.memory.set(0, f32::from_bits(0x7FC0_0001)).unwrap();
+        exe.memory.set(4, f32::from_bits(0xFFC0_0000)).unwrap();
+
+        assert_eq!(exe.run_until_break(), Ok(RunOutcome::End(res)));
+    }
+}
+
+#[test]
+fn executor_with_memory() {
+    let functions = [Function {
+        frame_size: 0,
+        program: &[
+            Op::Inc(UnOp::new(Operand::Glb(Memory::HEAP_BASE)), OpType::Uw),
+            Op::End(Operand::Glb(Memory::HEAP_BASE)),
+        ],
+    }];
+
+    let mut memory = Memory::from_limits(64, 64);
+    memory
+        .heap
+        .expand(std::mem::size_of::<UWord>() as UWord)
+        .unwrap();
+    memory.set::<UWord>(Memory::HEAP_BASE, 41).unwrap();
+
+    let mut exe = Executor::with_memory(&functions, memory);
+    exe.call(0, 0).unwrap();
+    assert_eq!(exe.run_until_break(), Ok(RunOutcome::End(42)));
+
+    let memory = exe.into_memory();
+    assert_eq!(memory.get::<UWord>(Memory::HEAP_BASE), Ok(42));
+
+    let mut exe = Executor::with_memory(&functions, memory);
+    exe.call(0, 0).unwrap();
+    assert_eq!(exe.run_until_break(), Ok(RunOutcome::End(43)));
+}
+
+#[test]
+fn executor_rem() {
+    let size = std::mem::size_of::<UWord>() as UWord;
+    let functions = [Function {
+        frame_size: size * 2,
+        program: &[Op::Rem(Operand::Loc(0), Operand::Loc(size))],
+    }];
+
+    let mut exe = Executor::from_limits(&functions, 100, 200);
+    exe.memory.heap.expand(50).unwrap();
+    exe.call(0, 0).unwrap();
+
+    assert_eq!(exe.execute(), Ok(ExecutionSuccess::Ok));
+    assert_eq!(exe.get_val::<UWord>(Operand::Loc(0)), Ok(100 - size * 2));
+    assert_eq!(exe.get_val::<UWord>(Operand::Loc(size)), Ok(150));
+}
+
+#[test]
+fn executor_oui_ouf() {
+    let functions = [Function {
+        frame_size: 12,
+        program: &[
+            Op::Oui(Operand::Loc(0), OpType::I32),
+            Op::Out(UnOp::new(Operand::Val(' ' as UWord))),
+            Op::Oui(Operand::Val(7), OpType::U8),
+            Op::Out(UnOp::new(Operand::Val(' ' as UWord))),
+            Op::Ouf(Operand::Loc(4), OpType::F64),
+            Op::Ouf(Operand::Loc(4), OpType::U8),
+        ],
+    }];
+
+    let mut exe = Executor::new(&functions);
+    assert_eq!(exe.files.open(Vec::<u8>::new()), Ok(0));
+    exe.files.set_current(0).unwrap();
+    exe.call(0, 0).unwrap();
+    exe.set_val(Operand::Loc(0), -42_i32).unwrap();
+    exe.set_val(Operand::Loc(4), 2.5_f64).unwrap();
+
+    for _ in 0..5 {
+        assert_eq!(exe.execute(), Ok(ExecutionSuccess::Ok));
+    }
+
+    assert_eq!(
+        exe.execute(),
+        Err(ExecutionError::IncorrectOperation(functions[0].program[5]))
+    );
+
+    let file = exe.files.close(0).unwrap();
+    let output = file.as_any().downcast_ref::<Vec<u8>>().unwrap();
+    assert_eq!(output.as_slice(), b"-42 7 2.5");
+}
+
+#[test]
+fn executor_ouf_widest() {
+    let functions = [Function {
+        frame_size: 8,
+        program: &[
+            Op::Ouf(Operand::Loc(0), OpType::F64),
+            Op::Ouf(Operand::Loc(0), OpType::F64),
+        ],
+    }];
+
+    let mut exe = Executor::new(&functions);
+    assert_eq!(exe.files.open(Vec::<u8>::new()), Ok(0));
+    exe.files.set_current(0).unwrap();
+    exe.call(0, 0).unwrap();
+
+    let min_subnormal = f64::from_bits(1);
+    exe.set_val(Operand::Loc(0), -min_subnormal).unwrap();
+    assert_eq!(exe.execute(), Ok(ExecutionSuccess::Ok));
+    exe.set_val(Operand::Loc(0), f64::MIN).unwrap();
+    assert_eq!(exe.execute(), Ok(ExecutionSuccess::Ok));
+
+    let file = exe.files.close(0).unwrap();
+    let output = file.as_any().downcast_ref::<Vec<u8>>().unwrap();
+    let expected = format!("{}{}", -min_subnormal, f64::MIN);
+    assert_eq!(output.as_slice(), expected.as_bytes());
+}
+
+#[test]
+fn executor_ouc() {
+    let functions = [Function {
+        frame_size: 0,
+        program: &[
+            Op::Ouc(Operand::Val('a' as UWord)),
+            Op::Ouc(Operand::Val(0x1F600)),
+            Op::Ouc(Operand::Val(0xD800)),
+        ],
+    }];
+
+    let mut exe = Executor::new(&functions);
+    assert_eq!(exe.files.open(Vec::<u8>::new()), Ok(0));
+    exe.files.set_current(0).unwrap();
+    exe.call(0, 0).unwrap();
+
+    assert_eq!(exe.execute(), Ok(ExecutionSuccess::Ok));
+    assert_eq!(exe.execute(), Ok(ExecutionSuccess::Ok));
+    assert_eq!(exe.execute(), Err(ExecutionError::InvalidChar(0xD800)));
+
+    let file = exe.files.close(0).unwrap();
+    let output = file.as_any().downcast_ref::<Vec<u8>>().unwrap();
+    assert_eq!(output.as_slice(), "a\u{1F600}".as_bytes());
+}
+
+#[test]
+fn executor_rnd() {
+    let functions = [Function {
+        frame_size: 8,
+        program: &[
+            Op::Rnd(Operand::Loc(0), OpType::U64),
+            Op::Rnd(Operand::Loc(0), OpType::U64),
+            Op::Rnd(Operand::Loc(0), OpType::F64),
+        ],
+    }];
+
+    let mut exe = Executor::new(&functions);
+    exe.seed_rng(1234567);
+    exe.call(0, 0).unwrap();
+
+    assert_eq!(exe.execute(), Ok(ExecutionSuccess::Ok));
+    assert_eq!(exe.get_val::<u64>(Operand::Loc(0)), Ok(6457827717110365317));
+    assert_eq!(exe.execute(), Ok(ExecutionSuccess::Ok));
+    assert_eq!(exe.get_val::<u64>(Operand::Loc(0)), Ok(3203168211198807973));
+    assert_eq!(exe.execute(), Ok(ExecutionSuccess::Ok));
+    let val = exe.get_val::<f64>(Operand::Loc(0)).unwrap();
+    assert!((0.0..1.0).contains(&val));
+
+    let mut other = Executor::new(&functions);
+    other.seed_rng(1234567);
+    other.call(0, 0).unwrap();
+
+    for _ in 0..3 {
+        assert_eq!(other.execute(), Ok(ExecutionSuccess::Ok));
+    }
+
+    assert_eq!(other.get_val::<f64>(Operand::Loc(0)), Ok(val));
+}
+
+#[test]
+fn executor_yld() {
+    let functions = [Function {
+        frame_size: std::mem::size_of::<UWord>() as UWord,
+        program: &[
+            Op::Add(BinOp::new(Operand::Loc(0), Operand::Val(1)), OpType::Uw),
+            Op::Yld(Operand::Loc(0)),
+            Op::Add(BinOp::new(Operand::Loc(0), Operand::Val(1)), OpType::Uw),
+            Op::Yld(Operand::Loc(0)),
+            Op::End(Operand::Loc(0)),
+        ],
+    }];
+
+    let mut exe = Executor::new(&functions);
+    exe.call(0, 0).unwrap();
+
+    assert_eq!(exe.run_until_break(), Ok(RunOutcome::Yield(1)));
+    assert_eq!(exe.program_counter, 2);
+    assert_eq!(exe.run_until_break(), Ok(RunOutcome::Yield(2)));
+    assert_eq!(exe.run_until_break(), Ok(RunOutcome::End(2)));
+}
+
+#[test]
+fn executor_bnd() {
+    let functions = [Function {
+        frame_size: 0,
+        program: &[
+            Op::Bnd(Operand::Val(16), Operand::Val(2), Operand::Val(3)),
+            Op::Bnd(Operand::Val(16), Operand::Val(3), Operand::Emp),
+            Op::Bnd(Operand::Val(16), Operand::Val(4), Operand::Emp),
+            Op::Bnd(Operand::Val(16), Operand::Val(3), Operand::Val(3)),
+        ],
+    }];
+
+    let mut exe = Executor::new(&functions);
+    exe.set_bounds(16, 4);
+    exe.call(0, 0).unwrap();
+
+    assert_eq!(exe.execute(), Ok(ExecutionSuccess::Ok));
+    assert_eq!(exe.execute(), Ok(ExecutionSuccess::Ok));
+    assert_eq!(
+        exe.execute(),
+        Err(ExecutionError::IndexOutOfBounds { index: 4, len: 4 })
+    );
+
+    exe.skip_current_op().unwrap();
+    assert_eq!(
+        exe.execute(),
+        Err(ExecutionError::IndexOutOfBounds { index: 3, len: 3 })
+    );
+}
+
+#[test]
+fn executor_bit() {
+    let functions = [Function {
+        frame_size: 4 + std::mem::size_of::<UWord>() as UWord,
+        program: &[
+            Op::Set(BinOp::new(Operand::Loc(0), Operand::Val(1)), OpType::U32),
+            Op::Cnv(Operand::Loc(0), Operand::Loc(0), OpType::U32, OpType::F32),
+            Op::Bit(Operand::Loc(4), Operand::Loc(0), OpType::F32, OpType::U32),
+            Op::Bit(Operand::Loc(0), Operand::Loc(4), OpType::U32, OpType::U8),
+            Op::End(Operand::Loc(4)),
+        ],
+    }];
+
+    let mut exe = Executor::new(&functions);
+    exe.call(0, 0).unwrap();
+
+    assert_eq!(exe.run_until_break(), Ok(RunOutcome::End(0x3F80_0000)));
+    assert_eq!(exe.get_val::<u32>(Operand::Loc(0)), Ok(0x3F80_0000));
+}
+
+#[test]
+fn executor_integer_only_ops_reject_floats() {
+    for &ot in &[OpType::F32, OpType::F64] {
+        let bin = BinOp::new(Operand::Loc(0), Operand::Loc(8));
+        let un = UnOp::new(Operand::Loc(0));
+        let ops = [
+            Op::Shl(bin, ot),
+            Op::Shr(bin, ot),
+            Op::And(bin, ot),
+            Op::Or(bin, ot),
+            Op::Xor(bin, ot),
+            Op::Not(un, ot),
+            Op::Ifa(bin, ot),
+            Op::Ifo(bin, ot),
+            Op::Ifx(bin, ot),
+            Op::Ina(bin, ot),
+            Op::Ino(bin, ot),
+            Op::Inx(bin, ot),
+            Op::Oui(Operand::Loc(0), ot),
+            Op::Rol(Operand::Loc(0), Operand::Val(1), ot),
+            Op::Ror(Operand::Loc(0), Operand::Val(1), ot),
+            Op::Cnt(un, ot),
+            Op::Clz(un, ot),
+            Op::Ctz(un, ot),
+        ];
+
+        for &op in &ops {
+            let program = [op, Op::Nop];
+            let functions = [Function::new(16, &program)];
+            let mut exe = Executor::new(&functions);
+            exe.call(0, 0).unwrap();
+
+            assert_eq!(exe.execute(), Err(ExecutionError::IncorrectOperation(op)));
+        }
+    }
+}
+
+#[test]
+fn executor_wide_types() {
+    let functions = [Function {
+        frame_size: 40,
+        program: &[
+            Op::Set(BinOp::new(Operand::Loc(0), Operand::Val(1)), OpType::U128),
+            Op::Set(BinOp::new(Operand::Loc(0), Operand::Val(0)), OpType::U64),
+            Op::Set(BinOp::new(Operand::Loc(8), Operand::Val(3)), OpType::U64),
+            Op::Mul(BinOp::new(Operand::Loc(0), Operand::Val(5)), OpType::U128),
+            Op::Sub(BinOp::new(Operand::Loc(0), Operand::Val(1)), OpType::U128),
+            Op::Set(BinOp::new(Operand::Loc(16), Operand::Val(0)), OpType::I128),
+            Op::Sub(BinOp::new(Operand::Loc(16), Operand::Val(2)), OpType::I128),
+            Op::Add(BinOp::new(Operand::Loc(16), Operand::Loc(0)), OpType::I128),
+            Op::Cnv(
+                Operand::Loc(32),
+                Operand::Loc(16),
+                OpType::I128,
+                OpType::U64,
+            ),
+            Op::Div(BinOp::new(Operand::Loc(0), Operand::Val(2)), OpType::U128),
+        ],
+    }];
+
+    let mut exe = Executor::new(&functions);
+    exe.call(0, 0).unwrap();
+
+    for _ in 0..9 {
+        assert_eq!(exe.execute(), Ok(ExecutionSuccess::Ok));
+    }
+
+    assert_eq!(exe.get_val::<u128>(Operand::Loc(0)), Ok((15 << 64) - 1));
+    assert_eq!(exe.get_val::<i128>(Operand::Loc(16)), Ok((15 << 64) - 3));
+    assert_eq!(exe.get_val::<u64>(Operand::Loc(32)), Ok(u64::MAX - 2));
+    assert_eq!(
+        exe.execute(),
+        Err(ExecutionError::UnsupportedType(OpType::U128))
+    );
+}
+
+#[test]
+fn executor_f16() {
+    use half::f16;
+
+    let functions = [Function {
+        frame_size: 12,
+        program: &[
+            Op::Set(
+                BinOp::new(Operand::Loc(0), Operand::Val(1.5_f32.to_bits() as UWord)),
+                OpType::F32,
+            ),
+            Op::Cnv(Operand::Loc(4), Operand::Loc(0), OpType::F32, OpType::F16),
+            Op::Set(
+                BinOp::new(
+                    Operand::Loc(6),
+                    Operand::Val(f16::from_f32(2.25).to_bits() as UWord),
+                ),
+                OpType::F16,
+            ),
+            Op::Add(BinOp::new(Operand::Loc(4), Operand::Loc(6)), OpType::F16),
+            Op::Cnv(Operand::Loc(8), Operand::Loc(4), OpType::F16, OpType::F32),
+            Op::Sqr(UnOp::new(Operand::Loc(4)), OpType::F16),
+        ],
+    }];
+
+    let mut exe = Executor::new(&functions);
+    exe.call(0, 0).unwrap();
+
+    for _ in 0..5 {
+        assert_eq!(exe.execute(), Ok(ExecutionSuccess::Ok));
+    }
+
+    assert_eq!(exe.get_val::<f16>(Operand::Loc(4)), Ok(f16::from_f32(3.75)));
+    assert_eq!(exe.memory.get::<u16>(4), Ok(0x4380));
+    assert_eq!(exe.get_val::<f32>(Operand::Loc(8)), Ok(3.75));
+    assert_eq!(
+        exe.execute(),
+        Err(ExecutionError::UnsupportedType(OpType::F16))
+    );
+}
+
+#[test]
+fn executor_run_skipping_errors() {
+    let functions = [Function {
+        frame_size: std::mem::size_of::<UWord>() as UWord,
+        program: &[
+            Op::Set(BinOp::new(Operand::Loc(0), Operand::Val(6)), OpType::U32),
+            Op::Shl(BinOp::new(Operand::Loc(0), Operand::Val(1)), OpType::F32),
+            Op::Div(BinOp::new(Operand::Loc(0), Operand::Val(0)), OpType::U32),
+            Op::Inc(UnOp::new(Operand::Loc(0)), OpType::U32),
+            Op::End(Operand::Loc(0)),
+        ],
+    }];
+
+    let mut exe = Executor::new(&functions);
+    exe.call(0, 0).unwrap();
+
+    let mut errors = Vec::new();
+    assert_eq!(exe.run_skipping_errors(&mut errors), Ok(RunOutcome::End(7)));
+    assert_eq!(
+        errors,
+        [
+            ExecutionError::IncorrectOperation(functions[0].program[1]),
+            ExecutionError::DivisionByZero,
+        ]
+    );
+    assert_eq!(exe.virtual_time(), 3);
+    assert_eq!(exe.consumed_cost(), 3);
+
+    let functions = [Function {
+        frame_size: 0,
+        program: &[Op::Nop],
+    }];
+
+    let mut exe = Executor::new(&functions);
+    exe.call(0, 0).unwrap();
+    assert_eq!(
+        exe.run_skipping_errors(&mut errors),
+        Err(ExecutionError::EndOfProgram)
+    );
+    assert_eq!(exe.skip_current_op(), Err(ExecutionError::EndOfProgram));
+}
+
+#[test]
+fn executor_float_bit_patterns() {
+    let functions = [Function {
+        frame_size: 56,
+        program: &[
+            Op::Add(BinOp::new(Operand::Loc(0), Operand::Loc(8)), OpType::F64),
+            Op::Div(BinOp::new(Operand::Loc(16), Operand::Loc(24)), OpType::F64),
+            Op::Mul(BinOp::new(Operand::Loc(32), Operand::Loc(40)), OpType::F64),
+            Op::Sub(BinOp::new(Operand::Loc(32), Operand::Loc(48)), OpType::F64),
+            Op::Mul(BinOp::new(Operand::Loc(0), Operand::Loc(16)), OpType::F64),
+        ],
+    }];
+
+    let mut exe = Executor::new(&functions);
+    exe.call(0, 0).unwrap();
+
+    for (ptr, val) in [
+        (0, 0.1),
+        (8, 0.2),
+        (16, 1.0),
+        (24, 3.0),
+        (32, 0.1),
+        (40, 10.0),
+        (48, 1.0),
+    ] {
+        exe.memory.set::<f64>(ptr, val).unwrap();
+    }
+
+    let bits = |exe: &Executor, ptr| exe.memory.get::<f64>(ptr).unwrap().to_bits();
+
+    assert_eq!(exe.execute(), Ok(ExecutionSuccess::Ok));
+    assert_eq!(bits(&exe, 0), 0x3FD3_3333_3333_3334);
+    assert_eq!(exe.execute(), Ok(ExecutionSuccess::Ok));
+    assert_eq!(bits(&exe, 16), 0x3FD5_5555_5555_5555);
+
+    // A fused multiply-add would give 2^-54 instead of zero
+    assert_eq!(exe.execute(), Ok(ExecutionSuccess::Ok));
+    assert_eq!(exe.execute(), Ok(ExecutionSuccess::Ok));
+    assert_eq!(bits(&exe, 32), 0);
+    assert_eq!(exe.execute(), Ok(ExecutionSuccess::Ok));
+    assert_eq!(bits(&exe, 0), 0x3FB9_9999_9999_999A);
+}
+
+#[test]
+fn executor_grp_srp() {
+    let size = std::mem::size_of::<UWord>() as UWord;
+    let main = [
+        Op::App(Operand::Val(1)),
+        Op::Clf(Operand::Val(0)),
+        Op::End(Operand::Loc(size)),
+    ];
+
+    let program = [
+        Op::Grp(Operand::Loc(0)),
+        Op::Add(BinOp::new(Operand::Loc(0), Operand::Val(size)), OpType::Uw),
+        Op::Srp(Operand::Loc(0)),
+        Op::Srp(Operand::Val(size + 1)),
+    ];
+    let functions = [
+        Function::new(2 * size, &main),
+        Function::new(size, &program),
+    ];
+
+    let mut exe = Executor::new(&functions);
+    exe.call(0, 0).unwrap();
+
+    for _ in 0..5 {
+        assert_eq!(exe.execute(), Ok(ExecutionSuccess::Ok));
+    }
+
+    assert_eq!(exe.get_val::<UWord>(Operand::Loc(0)), Ok(size));
+    assert_eq!(exe.stack_frames().last().unwrap().ret_val_ptr, size);
+    assert_eq!(
+        exe.execute(),
+        Err(ExecutionError::InvalidReturnPointer(size + 1))
+    );
+
+    let program = [
+        Op::Grp(Operand::Loc(0)),
+        Op::Add(BinOp::new(Operand::Loc(0), Operand::Val(size)), OpType::Uw),
+        Op::Srp(Operand::Loc(0)),
+        Op::Ret(UnOp::new(Operand::Val(99)), OpType::U32),
+    ];
+    let functions = [
+        Function::new(2 * size, &main),
+        Function::new(size, &program),
+    ];
+
+    let mut exe = Executor::new(&functions);
+    exe.call(0, 0).unwrap();
+
+    assert_eq!(exe.run_until_break(), Ok(RunOutcome::End(99)));
+    assert_eq!(exe.get_val::<u32>(Operand::Loc(0)), Ok(0));
+}
+
+#[test]
+fn executor_par_overflow() {
+    let functions = [
+        Function {
+            frame_size: 4,
+            program: &[
+                Op::App(Operand::Val(1)),
+                Op::Par(UnOp::new(Operand::Val(1)), OpType::U32),
+                Op::Par(UnOp::new(Operand::Val(2)), OpType::U16),
+                Op::Par(UnOp::new(Operand::Val(3)), OpType::U32),
+                Op::Par(UnOp::new(Operand::Val(3)), OpType::U16),
+            ],
+        },
+        Function {
+            frame_size: 6,
+            program: &[Op::Ret(UnOp::new(Operand::Loc(0)), OpType::U32)],
+        },
+    ];
+
+    let mut exe = Executor::new(&functions);
+    exe.call(0, 0).unwrap();
+
+    for _ in 0..3 {
+        assert_eq!(exe.execute(), Ok(ExecutionSuccess::Ok));
+    }
+
+    assert_eq!(exe.execute(), Err(ExecutionError::ParameterOverflow));
+    exe.skip_current_op().unwrap();
+    assert_eq!(exe.execute(), Err(ExecutionError::ParameterOverflow));
+    assert_eq!(exe.memory.stack.len(), 10);
+}
+
+#[test]
+fn executor_par_without_call() {
+    let program = [
+        Op::Par(UnOp::new(Operand::Val(1)), OpType::U8),
+        Op::App(Operand::Val(0)),
+        Op::Par(UnOp::new(Operand::Val(1)), OpType::U8),
+    ];
+    let functions = [Function::new(4, &program)];
+
+    let mut exe = Executor::new(&functions);
+    exe.call(0, 0).unwrap();
+    assert_eq!(exe.execute(), Err(ExecutionError::NoPreparedCall));
+    assert_eq!(ExecutionError::NoPreparedCall.exit_code(), 132);
+
+    exe.skip_current_op().unwrap();
+    assert_eq!(exe.execute(), Ok(ExecutionSuccess::Ok));
+    assert_eq!(exe.execute(), Ok(ExecutionSuccess::Ok));
+}
+
+#[test]
+fn executor_watchpoint() {
+    let program = [
+        Op::Set(BinOp::new(Operand::Loc(0), Operand::Val(1)), OpType::U32),
+        Op::Set(BinOp::new(Operand::Loc(7), Operand::Val(2)), OpType::U8),
+        Op::Set(BinOp::new(Operand::Loc(3), Operand::Val(3)), OpType::U16),
+        Op::Fil(Operand::Ref(0), Operand::Val(0), Operand::Val(8)),
+        Op::End(Operand::Val(0)),
+    ];
+    let functions = [Function::new(8, &program)];
+
+    let mut exe = Executor::new(&functions);
+    exe.add_watchpoint(4, 2);
+    exe.add_watchpoint(UWord::MAX, UWord::MAX);
+    exe.call(0, 0).unwrap();
+
+    assert_eq!(
+        exe.run_until_break(),
+        Ok(RunOutcome::Watchpoint { addr: 4 })
+    );
+    assert_eq!(exe.program_counter, 3);
+    assert_eq!(exe.get_val::<u16>(Operand::Loc(3)), Ok(3));
+
+    assert_eq!(
+        exe.run_until_break(),
+        Ok(RunOutcome::Watchpoint { addr: 4 })
+    );
+    assert_eq!(exe.program_counter, 4);
+
+    exe.set_val::<u16>(Operand::Loc(4), 5).unwrap();
+    exe.remove_watchpoint(4);
+    assert_eq!(exe.run_until_break(), Ok(RunOutcome::End(0)));
+}
+
+#[test]
+fn executor_exit_code() {
+    use ExecutionError::*;
+
+    let codes = [
+        (EndOfProgram, 132),
+        (IncorrectOperation(Op::Nop), 132),
+        (UnknownFunction(3), 132),
+        (MalformedCondition, 132),
+        (InvalidChar(0xD800), 132),
+        (UnsupportedType(OpType::F16), 132),
+        (Trap(7), 133),
+        (MemoryError(super::MemoryError::PageOverflow("heap")), 134),
+        (NullPointerDereference, 134),
+        (CallStackOverflow, 134),
+        (ParameterOverflow, 134),
+        (IndexOutOfBounds { index: 2, len: 1 }, 134),
+        (InvalidReturnPointer(8), 134),
+        (DivisionByZero, 136),
+        (OperationOverflow, 136),
+        (StepLimitExceeded, 137),
+        (FilesError(super::FilesError::NotFound), 74),
+    ];
+
+    for (err, code) in codes.iter() {
+        assert_eq!(err.exit_code(), *code, "{:?}", err);
+    }
+
+    assert_eq!(ExecutionSuccess::End(0).exit_code(), Some(0));
+    assert_eq!(ExecutionSuccess::End(0x1_02).exit_code(), Some(2));
+    assert_eq!(ExecutionSuccess::Ok.exit_code(), None);
+    assert_eq!(ExecutionSuccess::Yield(1).exit_code(), None);
+}
+
+#[test]
+fn executor_try_clone() {
+    let program = [
+        Op::Inc(UnOp::new(Operand::Loc(0)), OpType::U8),
+        Op::Out(UnOp::new(Operand::Loc(0))),
+        Op::Ifl(BinOp::new(Operand::Loc(0), Operand::Val(4)), OpType::U8),
+        Op::Go(Operand::Val(0)),
+        Op::End(Operand::Loc(0)),
+    ];
+    let functions = [Function::new(
+        std::mem::size_of::<UWord>() as UWord,
+        &program,
+    )];
+
+    let mut exe = Executor::new(&functions);
+    assert_eq!(exe.files.open(Vec::<u8>::new()), Ok(0));
+    exe.files.set_current(0).unwrap();
+    exe.call(0, 0).unwrap();
+
+    for _ in 0..4 {
+        assert_eq!(exe.execute(), Ok(ExecutionSuccess::Ok));
+    }
+
+    let mut fork = exe.try_clone().unwrap();
+    fork.set_val::<u8>(Operand::Loc(0), 3).unwrap();
+    assert_eq!(fork.run_until_break(), Ok(RunOutcome::End(4)));
+    assert_eq!(exe.run_until_break(), Ok(RunOutcome::End(4)));
+
+    let output = |exe: &mut Executor| {
+        let file = exe.files.close(0).unwrap();
+        file.as_any().downcast_ref::<Vec<u8>>().unwrap().clone()
+    };
+    assert_eq!(output(&mut exe), [1, 2, 3, 4]);
+    assert_eq!(output(&mut fork), [1, 4]);
+
+    use crate::executor::files::{File, FileError};
+
+    #[derive(Debug)]
+    struct Unique;
+
+    impl File for Unique {
+        fn read(&mut self) -> Result<Option<u8>, FileError> {
+            Ok(None)
+        }
+
+        fn write(&mut self, _: u8) -> Result<(), FileError> {
+            Ok(())
+        }
+
+        fn as_any(&self) -> &dyn std::any::Any {
+            self
+        }
+    }
+
+    assert_eq!(exe.files.open(Unique).map_err(|(e, _)| e), Ok(0));
+    assert!(exe.try_clone().is_none());
+}
+
+#[test]
+fn executor_cfd() {
+    let program = [
+        Op::Sfd(Operand::Val(1)),
+        Op::Out(UnOp::new(Operand::Val(b'a' as UWord))),
+        Op::Cfd(Operand::Val(1)),
+        Op::Cfd(Operand::Val(1)),
+    ];
+    let functions = [Function::new(0, &program)];
+
+    let mut exe = Executor::new(&functions);
+    assert_eq!(exe.files.open(Vec::<u8>::new()), Ok(0));
+    assert_eq!(exe.files.open(Vec::<u8>::new()), Ok(1));
+    exe.call(0, 0).unwrap();
+
+    for _ in 0..3 {
+        assert_eq!(exe.execute(), Ok(ExecutionSuccess::Ok));
+    }
+
+    assert_eq!(
+        exe.execute(),
+        Err(ExecutionError::FilesError(FilesError::NotFound))
+    );
+    assert_eq!(exe.files.current(), Err(FilesError::CurrentIsNotSet));
+    assert_eq!(exe.files.open(Vec::<u8>::new()), Ok(1));
+}
+
+#[test]
+fn executor_glb_address() {
+    let program = [
+        Op::Set(BinOp::new(Operand::Glb(4), Operand::Val(7)), OpType::U8),
+        Op::Set(BinOp::new(Operand::Glb(12), Operand::Val(9)), OpType::U8),
+        Op::End(Operand::Val(1)),
+    ];
+
+    // The main frame is at the base 0, so `glb` is the same as `loc`
+    let functions = [Function::new(8, &program)];
+    let mut exe = Executor::new(&functions);
+    exe.call(0, 0).unwrap();
+    assert_eq!(exe.execute(), Ok(ExecutionSuccess::Ok));
+    assert_eq!(exe.get_val::<u8>(Operand::Loc(4)), Ok(7));
+    assert_eq!(exe.memory().stack_bytes(), [0, 0, 0, 0, 7, 0, 0, 0]);
+    assert!(exe.execute().is_err());
+
+    let mut memory = Memory::from_limits(12, 4);
+    memory.set_model(MemoryModel::Unified);
+    let mut exe = Executor::with_memory(&functions, memory);
+    exe.load_data(12, &[0]).unwrap();
+    exe.call(0, 0).unwrap();
+    assert_eq!(exe.execute(), Ok(ExecutionSuccess::Ok));
+    assert_eq!(exe.execute(), Ok(ExecutionSuccess::Ok));
+    assert_eq!(exe.memory().stack_bytes(), [0, 0, 0, 0, 7, 0, 0, 0]);
+    assert_eq!(exe.memory().heap_bytes(), [9]);
+}
+
+#[test]
+fn executor_ovf() {
+    let program = [
+        Op::Set(BinOp::new(Operand::Loc(0), Operand::Val(250)), OpType::U8),
+        Op::Add(BinOp::new(Operand::Loc(0), Operand::Val(5)), OpType::U8),
+        Op::Ovf(Operand::Loc(1)),
+        Op::Add(BinOp::new(Operand::Loc(0), Operand::Val(1)), OpType::U8),
+        Op::Ovf(Operand::Loc(1)),
+        Op::Nop,
+        Op::Ovf(Operand::Loc(1)),
+        Op::Sub(BinOp::new(Operand::Loc(0), Operand::Val(2)), OpType::U8),
+        Op::Ovf(Operand::Loc(1)),
+        Op::Mul(BinOp::new(Operand::Loc(0), Operand::Val(2)), OpType::U8),
+        Op::Ovf(Operand::Loc(1)),
+    ];
+    let functions = [Function::new(2, &program)];
+
+    let mut exe = Executor::new(&functions);
+    exe.call(0, 0).unwrap();
+
+    let mut step = |n| {
+        for _ in 0..n {
+            assert_eq!(exe.execute(), Ok(ExecutionSuccess::Ok));
+        }
+
+        (
+            exe.get_val::<u8>(Operand::Loc(0)).unwrap(),
+            exe.get_val::<u8>(Operand::Loc(1)).unwrap(),
+        )
+    };
+
+    assert_eq!(step(3), (255, 0));
+    assert_eq!(step(2), (0, 1));
+    assert_eq!(step(2), (0, 1));
+    assert_eq!(step(2), (254, 1));
+    assert_eq!(step(2), (252, 1));
+
+    let program = [
+        Op::Set(BinOp::new(Operand::Loc(0), Operand::Val(254)), OpType::U8),
+        Op::Add(BinOp::new(Operand::Loc(0), Operand::Val(2)), OpType::U8),
+        Op::Ovf(Operand::Loc(1)),
+    ];
+    let functions = [Function::new(2, &program)];
+
+    let mut exe = Executor::new(&functions);
+    exe.set_checked(true);
+    exe.call(0, 0).unwrap();
+
+    assert_eq!(exe.execute(), Ok(ExecutionSuccess::Ok));
+    assert_eq!(exe.execute(), Err(ExecutionError::OperationOverflow));
+    assert_eq!(exe.get_val::<u8>(Operand::Loc(0)), Ok(254));
+}
+
+#[test]
+fn executor_adc_sbb() {
+    let program = [
+        Op::Add(BinOp::new(Operand::Loc(0), Operand::Loc(16)), OpType::U64),
+        Op::Adc(BinOp::new(Operand::Loc(8), Operand::Loc(24)), OpType::U64),
+        Op::Ovf(Operand::Loc(32)),
+        Op::Sub(BinOp::new(Operand::Loc(0), Operand::Loc(16)), OpType::U64),
+        Op::Sbb(BinOp::new(Operand::Loc(8), Operand::Loc(24)), OpType::U64),
+        Op::Ovf(Operand::Loc(33)),
+        Op::Adc(BinOp::new(Operand::Loc(0), Operand::Loc(0)), OpType::F32),
+    ];
+    let functions = [Function::new(34, &program)];
+
+    let a: u128 = 0x1_FFFF_FFFF_FFFF_FFFF;
+    let b: u128 = 0x2_0000_0000_0000_0001;
+
+    let mut exe = Executor::new(&functions);
+    exe.call(0, 0).unwrap();
+    exe.set_val(Operand::Loc(0), a as u64).unwrap();
+    exe.set_val(Operand::Loc(8), (a >> 64) as u64).unwrap();
+    exe.set_val(Operand::Loc(16), b as u64).unwrap();
+    exe.set_val(Operand::Loc(24), (b >> 64) as u64).unwrap();
+
+    let read = |exe: &Executor| {
+        let lo: u64 = exe.get_val(Operand::Loc(0)).unwrap();
+        let hi: u64 = exe.get_val(Operand::Loc(8)).unwrap();
+        (hi as u128) << 64 | lo as u128
+    };
+
+    for _ in 0..3 {
+        assert_eq!(exe.execute(), Ok(ExecutionSuccess::Ok));
+    }
+
+    assert_eq!(read(&exe), a + b);
+    assert_eq!(exe.get_val::<u8>(Operand::Loc(32)), Ok(0));
+
+    for _ in 0..3 {
+        assert_eq!(exe.execute(), Ok(ExecutionSuccess::Ok));
+    }
+
+    assert_eq!(read(&exe), a);
+    assert_eq!(exe.get_val::<u8>(Operand::Loc(33)), Ok(0));
+    assert!(matches!(
+        exe.execute(),
+        Err(ExecutionError::IncorrectOperation(_))
+    ));
+}
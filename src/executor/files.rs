@@ -1,4 +1,5 @@
-use std::{any::Any, collections::vec_deque::VecDeque};
+use alloc::{boxed::Box, collections::VecDeque, vec::Vec};
+use core::any::Any;
 
 use crate::common::UWord;
 
@@ -8,7 +9,7 @@ pub enum FileError {
     WritingNotAvailable,
 }
 
-pub trait File: std::fmt::Debug {
+pub trait File: core::fmt::Debug {
     fn read(&mut self) -> Result<Option<u8>, FileError>;
 
     fn write(&mut self, val: u8) -> Result<(), FileError>;
@@ -17,6 +18,33 @@ pub trait File: std::fmt::Debug {
         Ok(())
     }
 
+    /// Reads bytes until the buffer is full or the file ends.
+    /// Returns the number of read bytes.
+    fn read_into(&mut self, buf: &mut [u8]) -> Result<usize, FileError> {
+        for (i, byte) in buf.iter_mut().enumerate() {
+            match self.read()? {
+                Some(val) => *byte = val,
+                None => return Ok(i),
+            }
+        }
+
+        Ok(buf.len())
+    }
+
+    fn write_all(&mut self, buf: &[u8]) -> Result<(), FileError> {
+        buf.iter().try_for_each(|&val| self.write(val))
+    }
+
+    /// Checks if the next read returns no byte.
+    fn at_eof(&self) -> Result<bool, FileError> {
+        Err(FileError::ReadingNotAvailable)
+    }
+
+    /// Returns an independent copy of the file if it can be duplicated.
+    fn try_clone(&self) -> Option<Box<dyn File>> {
+        None
+    }
+
     fn as_any(&self) -> &dyn Any;
 }
 
@@ -30,6 +58,15 @@ impl File for Vec<u8> {
         Ok(())
     }
 
+    fn write_all(&mut self, buf: &[u8]) -> Result<(), FileError> {
+        self.extend_from_slice(buf);
+        Ok(())
+    }
+
+    fn try_clone(&self) -> Option<Box<dyn File>> {
+        Some(Box::new(self.clone()))
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
@@ -46,6 +83,14 @@ impl File for VecDeque<u8> {
         Ok(())
     }
 
+    fn at_eof(&self) -> Result<bool, FileError> {
+        Ok(self.is_empty())
+    }
+
+    fn try_clone(&self) -> Option<Box<dyn File>> {
+        Some(Box::new(self.clone()))
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
@@ -140,6 +185,12 @@ impl Files {
 
     pub fn set_current(&mut self, idx: UWord) -> Result<(), FilesError> {
         let idx = idx as usize;
+
+        // Return the previous current file to its cell
+        if let Some((prev, file)) = self.current.take() {
+            self.files[prev] = Some(file);
+        }
+
         let file = self
             .files
             .get_mut(idx)
@@ -151,12 +202,42 @@ impl Files {
         Ok(())
     }
 
+    /// Duplicates all opened files or returns `None` if any of them
+    /// can't be duplicated.
+    pub fn try_clone(&self) -> Option<Self> {
+        let files = self
+            .files
+            .iter()
+            .map(|file| match file {
+                Some(file) => file.try_clone().map(Some),
+                None => Some(None),
+            })
+            .collect::<Option<_>>()?;
+
+        let current = match &self.current {
+            Some((idx, file)) => Some((*idx, file.try_clone()?)),
+            None => None,
+        };
+
+        Some(Self {
+            files,
+            count: self.count,
+            current,
+        })
+    }
+
     pub fn current(&self) -> Result<UWord, FilesError> {
         let (current, _) = self.current.as_ref().ok_or(FilesError::CurrentIsNotSet)?;
 
         Ok(*current as UWord)
     }
 
+    fn get(&self) -> Result<&dyn File, FilesError> {
+        let (_, file) = self.current.as_ref().ok_or(FilesError::CurrentIsNotSet)?;
+
+        Ok(Box::as_ref(file) as &dyn File)
+    }
+
     fn get_mut(&mut self) -> Result<&mut dyn File, FilesError> {
         let (_, file) = self.current.as_mut().ok_or(FilesError::CurrentIsNotSet)?;
 
@@ -175,6 +256,24 @@ impl Files {
         Ok(())
     }
 
+    pub fn read_into(&mut self, buf: &mut [u8]) -> Result<usize, FilesError> {
+        let file = self.get_mut()?;
+        let n = file.read_into(buf)?;
+        Ok(n)
+    }
+
+    pub fn write_all(&mut self, buf: &[u8]) -> Result<(), FilesError> {
+        let file = self.get_mut()?;
+        file.write_all(buf)?;
+        Ok(())
+    }
+
+    pub fn at_eof(&self) -> Result<bool, FilesError> {
+        let file = self.get()?;
+        let eof = file.at_eof()?;
+        Ok(eof)
+    }
+
     pub fn flush(&mut self) -> Result<(), FilesError> {
         let file = self.get_mut()?;
         file.flush()?;
@@ -182,6 +281,12 @@ impl Files {
     }
 }
 
+impl Default for Files {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -200,4 +305,45 @@ mod tests {
         assert_eq!(files.current(), Err(FilesError::CurrentIsNotSet));
         assert_eq!(files.open(Vec::new()), Ok(0));
     }
+
+    #[test]
+    fn files_switch_current() {
+        let mut files = Files::new();
+        let input: VecDeque<u8> = vec![1, 2].into();
+        files.open(input).unwrap();
+        files.open(Vec::new()).unwrap();
+
+        files.set_current(0).unwrap();
+        assert_eq!(files.read(), Ok(Some(1)));
+        files.set_current(1).unwrap();
+        files.write(3).unwrap();
+        files.set_current(0).unwrap();
+        assert_eq!(files.read(), Ok(Some(2)));
+        files.set_current(0).unwrap();
+        assert_eq!(files.current(), Ok(0));
+
+        let file = files.close(1).unwrap();
+        assert_eq!(file.as_any().downcast_ref::<Vec<u8>>(), Some(&vec![3]));
+    }
+
+    #[test]
+    fn files_at_eof() {
+        let mut files = Files::new();
+        assert_eq!(files.at_eof(), Err(FilesError::CurrentIsNotSet));
+
+        let input: VecDeque<u8> = vec![1].into();
+        let fd = files.open(input).unwrap();
+        files.set_current(fd).unwrap();
+
+        assert_eq!(files.at_eof(), Ok(false));
+        assert_eq!(files.read(), Ok(Some(1)));
+        assert_eq!(files.at_eof(), Ok(true));
+
+        let fd = files.open(Vec::new()).unwrap();
+        files.set_current(fd).unwrap();
+        assert_eq!(
+            files.at_eof(),
+            Err(FilesError::FileError(FileError::ReadingNotAvailable))
+        );
+    }
 }
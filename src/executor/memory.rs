@@ -1,109 +1,247 @@
 use super::primary::Primary;
 use crate::common::UWord;
+use alloc::{string::String, vec::Vec};
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Region {
+    Stack,
+    Heap,
+}
+
+impl Region {
+    /// Returns the region of the address in the split memory model.
+    /// Use `Memory::region_of` for the model of the memory.
+    pub fn of(addr: UWord) -> Self {
+        if addr < Memory::HEAP_BASE {
+            Region::Stack
+        } else {
+            Region::Heap
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Region::Stack => "stack",
+            Region::Heap => "heap",
+        }
+    }
+
+    /// The address of the first byte of the region in the split memory model.
+    pub fn base(self) -> UWord {
+        match self {
+            Region::Stack => 0,
+            Region::Heap => Memory::HEAP_BASE,
+        }
+    }
+}
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub enum MemoryError {
     PageOverflow(&'static str),
     RageUnderflow(&'static str),
-    SegmentationFault(UWord, UWord),
-    WrongRange,
+    /// Access of `size` bytes at the address `addr` is outside the region.
+    OutOfBounds {
+        addr: UWord,
+        size: UWord,
+        region: Region,
+    },
+    /// Write to the address `addr` in a read-only range.
+    WriteToReadOnly(UWord),
 }
 
+/// Defines what the bytes added to the stack by a new frame contain.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum StackPolicy {
+    /// The new bytes are zeroed, so a call never sees the locals left by
+    /// a previous call. Costs a fill of the frame on each call.
+    #[default]
+    ZeroOnExpand,
+    /// The new bytes keep the data left by previous calls. Calls are faster,
+    /// but a function must write its locals before reading them.
+    Uninitialized,
+}
+
+/// Placement of the heap in the address space.
+///
+/// The stack always starts at the address 0. `Loc` operands are offsets from
+/// the base of the current frame on the stack, while `Glb` operands are
+/// the addresses in the whole address space, so `glb(n)` below the stack
+/// length is the same byte as `loc(n - base)` of a frame.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum MemoryModel {
+    /// The heap starts at `Memory::HEAP_BASE` leaving a gap after the stack
+    /// limit, so the addresses don't depend on the limits.
+    #[default]
+    Split,
+    /// The heap starts right after the stack limit, so the address space
+    /// is a flat range without a gap.
+    Unified,
+}
+
+/// Byte order of multi-byte values stored in memory.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum Endianness {
+    #[default]
+    Little,
+    Big,
+}
+
+#[derive(Clone)]
 pub struct MemoryPage {
+    // Bytes beyond `len` are left from the narrowed part of the page.
     page: Vec<u8>,
+    len: usize,
     limit: usize,
-    name: &'static str,
+    region: Region,
+    base: UWord,
+    peak: usize,
+    policy: StackPolicy,
 }
 
 impl MemoryPage {
-    fn new(limit: usize, name: &'static str) -> Self {
+    fn new(limit: usize, region: Region) -> Self {
         Self {
             page: Vec::new(),
+            len: 0,
             limit,
-            name,
+            region,
+            base: region.base(),
+            peak: 0,
+            policy: StackPolicy::default(),
+        }
+    }
+
+    fn bytes(&self) -> &[u8] {
+        &self.page[..self.len]
+    }
+
+    fn bytes_mut(&mut self) -> &mut [u8] {
+        &mut self.page[..self.len]
+    }
+
+    fn out_of_bounds(&self, ptr: UWord, size: UWord) -> MemoryError {
+        MemoryError::OutOfBounds {
+            addr: self.base.wrapping_add(ptr),
+            size,
+            region: self.region,
         }
     }
 
     pub fn expand(&mut self, size: UWord) -> Result<(), MemoryError> {
-        let len = self.page.len().saturating_add(size as usize);
+        let len = self.len.saturating_add(size as usize);
 
         if len > self.limit {
-            Err(MemoryError::PageOverflow(self.name))
-        } else {
+            return Err(MemoryError::PageOverflow(self.region.name()));
+        }
+
+        if self.policy == StackPolicy::ZeroOnExpand {
+            let reused = len.min(self.page.len());
+            self.page[self.len..reused].iter_mut().for_each(|b| *b = 0);
+        }
+
+        if len > self.page.len() {
             self.page.resize(len, 0);
-            Ok(())
         }
+
+        self.len = len;
+        self.peak = self.peak.max(len);
+        Ok(())
     }
 
     pub fn narrow(&mut self, size: UWord) -> Result<(), MemoryError> {
         let size = size as usize;
 
-        if self.page.len() < size {
-            Err(MemoryError::PageOverflow(self.name))
+        if self.len < size {
+            Err(MemoryError::PageOverflow(self.region.name()))
         } else {
-            let len = self.page.len() - size;
-            self.page.truncate(len);
+            self.len -= size;
             Ok(())
         }
     }
 
     pub fn len(&self) -> UWord {
-        self.page.len() as UWord
+        self.len as UWord
     }
 
-    pub fn get(&self, ptr: UWord, size: UWord) -> Result<&[u8], MemoryError> {
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Number of bytes the page can still be expanded by.
+    pub fn remaining(&self) -> usize {
+        self.limit.saturating_sub(self.len)
+    }
+
+    /// Maximum size the page has reached.
+    pub fn peak(&self) -> usize {
+        self.peak
+    }
+
+    /// Number of bytes the page can hold without reallocation.
+    pub fn capacity(&self) -> usize {
+        self.page.capacity()
+    }
+
+    /// Allocates the page to hold `bytes` without reallocation, but no more
+    /// than the limit. The length of the page is not changed.
+    pub fn reserve(&mut self, bytes: usize) {
+        let bytes = bytes.min(self.limit);
         self.page
+            .reserve_exact(bytes.saturating_sub(self.page.len()));
+    }
+
+    pub fn get(&self, ptr: UWord, size: UWord) -> Result<&[u8], MemoryError> {
+        self.bytes()
             .get(ptr as usize..ptr.wrapping_add(size) as usize)
-            .ok_or(MemoryError::SegmentationFault(ptr, size))
+            .ok_or_else(|| self.out_of_bounds(ptr, size))
     }
 
     pub fn get_mut(&mut self, ptr: UWord, size: UWord) -> Result<&mut [u8], MemoryError> {
-        self.page
+        let err = self.out_of_bounds(ptr, size);
+
+        self.bytes_mut()
             .get_mut(ptr as usize..ptr.wrapping_add(size) as usize)
-            .ok_or(MemoryError::SegmentationFault(ptr, size))
+            .ok_or(err)
     }
 
+    /// Copies `size` bytes from `src` to `dest`. The ranges may overlap.
     pub fn memmove(&mut self, dest: UWord, src: UWord, size: UWord) -> Result<(), MemoryError> {
-        let src_end = src.wrapping_add(size);
-        let dest_end = dest.wrapping_add(size);
-
-        if src > src_end {
-            Err(MemoryError::WrongRange)
-        } else if src_end > self.len() {
-            Err(MemoryError::SegmentationFault(src, size))
-        } else if dest_end > self.len() {
-            Err(MemoryError::SegmentationFault(dest, size))
+        let in_bounds =
+            |ptr: UWord| matches!(ptr.checked_add(size), Some(end) if end <= self.len());
+
+        if !in_bounds(src) {
+            Err(self.out_of_bounds(src, size))
+        } else if !in_bounds(dest) {
+            Err(self.out_of_bounds(dest, size))
         } else {
-            self.page
-                .as_mut_slice()
-                .copy_within(src as usize..src_end as usize, dest as usize);
+            let src = src as usize;
+            self.bytes_mut()
+                .copy_within(src..src + size as usize, dest as usize);
 
             Ok(())
         }
     }
 }
 
-impl std::fmt::Debug for MemoryPage {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        use std::fmt::Write;
+impl core::fmt::Debug for MemoryPage {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        use core::fmt::Write;
 
-        if self.page.is_empty() {
+        if self.is_empty() {
             return Ok(());
         }
 
         let mut counter = 0;
-        let mut line = 0;
 
         f.write_char('\n')?;
 
-        for &byte in self.page.iter() {
+        for (line, &byte) in self.bytes().iter().enumerate() {
             if counter == 0 {
                 write!(f, "{:02X?}:  ", line)?;
             }
 
             write!(f, "{:02X?} ", byte)?;
             counter += 1;
-            line += 1;
 
             if counter > 8 {
                 f.write_char('\n')?;
@@ -115,17 +253,30 @@ impl std::fmt::Debug for MemoryPage {
     }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct Memory {
     pub stack: MemoryPage,
     pub heap: MemoryPage,
+    endianness: Endianness,
+    model: MemoryModel,
+    readonly: Vec<(UWord, UWord)>,
 }
 
 impl Memory {
-    pub const WORD_SIZE_BITS: UWord = std::mem::size_of::<UWord>() as UWord * 8;
+    pub const WORD_SIZE_BITS: UWord = core::mem::size_of::<UWord>() as UWord * 8;
     pub const HEAP_BASE: UWord = (1 as UWord) << (Self::WORD_SIZE_BITS / 2);
 
     pub fn from_limits(stack_limit: usize, heap_limit: usize) -> Self {
+        Self::from_limits_endian(stack_limit, heap_limit, Endianness::Little)
+    }
+
+    /// Creates the memory which stores values with the given byte order.
+    /// Only `get` and `set` of values are affected, raw bytes are not.
+    pub fn from_limits_endian(
+        stack_limit: usize,
+        heap_limit: usize,
+        endianness: Endianness,
+    ) -> Self {
         if stack_limit >= Self::HEAP_BASE as usize {
             panic!(
                 "Stack limit must be less than heap base ({})",
@@ -134,19 +285,128 @@ impl Memory {
         }
 
         Self {
-            stack: MemoryPage::new(stack_limit, "stack"),
-            heap: MemoryPage::new(heap_limit, "heap"),
+            stack: MemoryPage::new(stack_limit, Region::Stack),
+            heap: MemoryPage::new(heap_limit, Region::Heap),
+            endianness,
+            model: MemoryModel::Split,
+            readonly: Vec::new(),
+        }
+    }
+
+    pub fn endianness(&self) -> Endianness {
+        self.endianness
+    }
+
+    /// Sets the placement of the heap. The bytes of the pages are kept,
+    /// only their addresses change.
+    pub fn set_model(&mut self, model: MemoryModel) {
+        self.model = model;
+        self.heap.base = match model {
+            MemoryModel::Split => Self::HEAP_BASE,
+            MemoryModel::Unified => self.stack.limit as UWord,
+        };
+    }
+
+    pub fn model(&self) -> MemoryModel {
+        self.model
+    }
+
+    /// The address of the first byte of the heap.
+    pub fn heap_base(&self) -> UWord {
+        self.heap.base
+    }
+
+    pub fn region_of(&self, addr: UWord) -> Region {
+        if addr < self.heap.base {
+            Region::Stack
+        } else {
+            Region::Heap
         }
     }
 
+    /// Marks `len` bytes from `start` as read-only. Writing to the range by
+    /// `set`, `copy`, `fill` or `slice_mut` returns `WriteToReadOnly`.
+    pub fn mark_readonly(&mut self, start: UWord, len: UWord) {
+        if len != 0 {
+            self.readonly.push((start, len));
+        }
+    }
+
+    fn check_writable(&self, ptr: UWord, size: UWord) -> Result<(), MemoryError> {
+        if size == 0 {
+            return Ok(());
+        }
+
+        // Compare the last bytes of the ranges, which can't overflow.
+        // An access past the end of the address space is then out of bounds.
+        let last = ptr.saturating_add(size - 1);
+
+        for &(addr, len) in &self.readonly {
+            if addr <= last && ptr <= addr.saturating_add(len - 1) {
+                return Err(MemoryError::WriteToReadOnly(addr.max(ptr)));
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn set_stack_policy(&mut self, policy: StackPolicy) {
+        self.stack.policy = policy;
+    }
+
+    /// Returns the live bytes of the stack. The byte `i` is at the address `i`.
+    pub fn stack_bytes(&self) -> &[u8] {
+        self.stack.bytes()
+    }
+
+    /// Returns the live bytes of the heap.
+    /// The byte `i` is at the address `heap_base() + i`.
+    pub fn heap_bytes(&self) -> &[u8] {
+        self.heap.bytes()
+    }
+
+    /// Same as `stack_bytes`, but the read-only ranges are not checked.
+    #[cfg(feature = "debug")]
+    pub fn stack_bytes_mut(&mut self) -> &mut [u8] {
+        self.stack.bytes_mut()
+    }
+
+    /// Same as `heap_bytes`, but the read-only ranges are not checked.
+    #[cfg(feature = "debug")]
+    pub fn heap_bytes_mut(&mut self) -> &mut [u8] {
+        self.heap.bytes_mut()
+    }
+
+    /// Reserves the stack capacity, see `MemoryPage::reserve`.
+    pub fn reserve_stack(&mut self, bytes: usize) {
+        self.stack.reserve(bytes);
+    }
+
+    /// Reserves the heap capacity, see `MemoryPage::reserve`.
+    pub fn reserve_heap(&mut self, bytes: usize) {
+        self.heap.reserve(bytes);
+    }
+
+    pub fn peak_stack(&self) -> usize {
+        self.stack.peak()
+    }
+
+    pub fn peak_heap(&self) -> usize {
+        self.heap.peak()
+    }
+
     pub fn set<T>(&mut self, ptr: UWord, value: T) -> Result<(), MemoryError>
     where
         T: Primary,
     {
-        use std::borrow::Borrow;
-
+        let endianness = self.endianness;
         let dest = self.slice_mut(ptr, T::SIZE as UWord)?;
-        dest.copy_from_slice(value.to_bytes().borrow());
+        value.write_le(dest);
+
+        if endianness == Endianness::Big {
+            dest.reverse();
+        }
+
         Ok(())
     }
 
@@ -155,7 +415,17 @@ impl Memory {
         T: Primary,
     {
         let src = self.slice(ptr, T::SIZE as UWord)?;
-        Ok(T::from_slice(src))
+
+        match self.endianness {
+            Endianness::Little => Ok(T::read_le(src)),
+            Endianness::Big => {
+                let mut buf = [0; 16];
+                let buf = &mut buf[..T::SIZE];
+                buf.copy_from_slice(src);
+                buf.reverse();
+                Ok(T::read_le(buf))
+            }
+        }
     }
 
     pub fn update<T, F>(&mut self, ptr: UWord, f: F) -> Result<(), MemoryError>
@@ -166,66 +436,144 @@ impl Memory {
         self.set(ptr, f(self.get(ptr)?))
     }
 
+    /// Copies `size` bytes from `src` to `dest` with memmove semantics, so
+    /// the overlapping ranges are copied correctly. Zero size is a no-op.
     pub fn copy(&mut self, dest: UWord, src: UWord, size: UWord) -> Result<(), MemoryError> {
-        let dest_on_stack = dest < Memory::HEAP_BASE;
-        let src_on_stack = src < Memory::HEAP_BASE;
+        if size == 0 {
+            return Ok(());
+        }
+
+        self.check_writable(dest, size)?;
+
+        let heap_base = self.heap.base;
+        let dest_on_stack = dest < heap_base;
+        let src_on_stack = src < heap_base;
 
         // If dest and src are on the left or on the right side together then
         // they are in the same memory page.
-        return if dest_on_stack == src_on_stack {
+        if dest_on_stack == src_on_stack {
             // And then it allows to make a memmove.
             if dest_on_stack {
                 self.stack.memmove(dest, src, size)
             } else {
-                let dest = dest - Memory::HEAP_BASE;
-                let src = src - Memory::HEAP_BASE;
+                let dest = dest - heap_base;
+                let src = src - heap_base;
                 self.heap.memmove(dest, src, size)
             }
         } else {
             // Otherwise it requires to copy from one page to another.
             let (dest_slice, src_slice) = if dest_on_stack {
-                let src = src - Memory::HEAP_BASE;
+                let src = src - heap_base;
                 (self.stack.get_mut(dest, size)?, self.heap.get(src, size)?)
             } else {
-                let dest = dest - Memory::HEAP_BASE;
+                let dest = dest - heap_base;
                 (self.heap.get_mut(dest, size)?, self.stack.get(src, size)?)
             };
 
             dest_slice.copy_from_slice(src_slice);
             Ok(())
-        };
+        }
     }
 
     pub fn set_zeros(&mut self, dest: UWord, size: UWord) -> Result<(), MemoryError> {
+        self.fill(dest, 0, size)
+    }
+
+    /// Sets `size` bytes from `dest` to `byte`. Zero size is a no-op.
+    pub fn fill(&mut self, dest: UWord, byte: u8, size: UWord) -> Result<(), MemoryError> {
+        if size == 0 {
+            return Ok(());
+        }
+
         let slice = self.slice_mut(dest, size)?;
-        slice.iter_mut().for_each(|b| *b = 0);
+        slice.iter_mut().for_each(|b| *b = byte);
 
         Ok(())
     }
 
+    /// Returns the offset of the first `needle` byte within `max` bytes
+    /// from `start` or `max` if there is no such byte.
+    /// The bytes after the found one may be out of bounds.
+    pub fn find(&self, start: UWord, needle: u8, max: UWord) -> Result<UWord, MemoryError> {
+        let (page, ptr) = if start < self.heap.base {
+            (&self.stack, start)
+        } else {
+            (&self.heap, start - self.heap.base)
+        };
+
+        let available = page.len().saturating_sub(ptr).min(max);
+        let bytes = page.get(ptr, available)?;
+
+        match bytes.iter().position(|&b| b == needle) {
+            Some(pos) => Ok(pos as UWord),
+            None if available == max => Ok(max),
+            None => Err(page.out_of_bounds(ptr, max)),
+        }
+    }
+
     pub fn compare(&self, a: UWord, b: UWord, size: UWord) -> Result<bool, MemoryError> {
+        if size == 0 {
+            return Ok(true);
+        }
+
         let a_slice = self.slice(a, size)?;
         let b_slice = self.slice(b, size)?;
         Ok(a_slice == b_slice)
     }
 
-    fn slice(&self, ptr: UWord, size: UWord) -> Result<&[u8], MemoryError> {
-        if ptr < Memory::HEAP_BASE {
+    pub fn slice(&self, ptr: UWord, size: UWord) -> Result<&[u8], MemoryError> {
+        if ptr < self.heap.base {
             self.stack.get(ptr, size)
         } else {
-            let ptr = ptr - Memory::HEAP_BASE;
+            let ptr = ptr - self.heap.base;
             self.heap.get(ptr, size)
         }
     }
 
-    fn slice_mut(&mut self, ptr: UWord, size: UWord) -> Result<&mut [u8], MemoryError> {
-        if ptr < Memory::HEAP_BASE {
+    pub fn slice_mut(&mut self, ptr: UWord, size: UWord) -> Result<&mut [u8], MemoryError> {
+        self.check_writable(ptr, size)?;
+
+        if ptr < self.heap.base {
             self.stack.get_mut(ptr, size)
         } else {
-            let ptr = ptr - Memory::HEAP_BASE;
+            let ptr = ptr - self.heap.base;
             self.heap.get_mut(ptr, size)
         }
     }
+
+    /// Formats `len` bytes from `start` as a hex dump. Each line holds
+    /// the address, up to 16 bytes in hex and their printable ASCII chars.
+    pub fn dump(&self, start: UWord, len: UWord) -> Result<String, MemoryError> {
+        use core::fmt::Write;
+
+        let bytes = self.slice(start, len)?;
+        let mut out = String::new();
+
+        for (i, line) in bytes.chunks(16).enumerate() {
+            let addr = start.wrapping_add(i as UWord * 16);
+            write!(out, "{:08x} ", addr).unwrap();
+
+            for b in line {
+                write!(out, " {:02x}", b).unwrap();
+            }
+
+            for _ in line.len()..16 {
+                out.push_str("   ");
+            }
+
+            out.push_str("  |");
+            for &b in line {
+                out.push(if b.is_ascii_graphic() || b == b' ' {
+                    b as char
+                } else {
+                    '.'
+                });
+            }
+            out.push_str("|\n");
+        }
+
+        Ok(out)
+    }
 }
 
 #[cfg(test)]
@@ -237,7 +585,7 @@ mod tests {
         let mut mem = Memory::from_limits(2048, 2048);
         mem.stack.expand(4).unwrap();
         assert_eq!(mem.stack.len(), 4);
-        assert_eq!(mem.stack.page.as_slice(), [0, 0, 0, 0]);
+        assert_eq!(mem.stack.bytes(), [0, 0, 0, 0]);
 
         let mut mem = Memory::from_limits(2048, 2048);
         assert_eq!(
@@ -246,12 +594,184 @@ mod tests {
         );
     }
 
+    #[test]
+    fn memory_peak() {
+        let mut mem = Memory::from_limits(2048, 2048);
+        mem.stack.expand(12).unwrap();
+        mem.stack.narrow(8).unwrap();
+        mem.stack.expand(4).unwrap();
+        mem.heap.expand(2).unwrap();
+        assert_eq!(mem.peak_stack(), 12);
+        assert_eq!(mem.peak_heap(), 2);
+
+        assert!(mem.stack.expand(4096).is_err());
+        assert_eq!(mem.peak_stack(), 12);
+    }
+
+    #[test]
+    fn memory_reserve() {
+        let mut mem = Memory::from_limits(2048, 256);
+        mem.stack.expand(4).unwrap();
+        mem.reserve_stack(1024);
+        mem.reserve_heap(4096);
+
+        assert!(mem.stack.capacity() >= 1024);
+        assert_eq!(mem.stack.len(), 4);
+        assert!(mem.heap.capacity() >= 256 && mem.heap.capacity() < 4096);
+        assert_eq!(mem.heap.len(), 0);
+
+        let capacity = mem.stack.capacity();
+        mem.stack.expand(1020).unwrap();
+        assert_eq!(mem.stack.capacity(), capacity);
+    }
+
+    #[test]
+    fn memory_bytes() {
+        let mut mem = Memory::from_limits(2048, 2048);
+        mem.stack.expand(4).unwrap();
+        mem.heap.expand(2).unwrap();
+        mem.set::<u16>(2, 0x0201).unwrap();
+        mem.set::<u8>(Memory::HEAP_BASE + 1, 7).unwrap();
+
+        assert_eq!(mem.stack_bytes(), [0, 0, 1, 2]);
+        assert_eq!(mem.heap_bytes(), [0, 7]);
+
+        mem.stack.narrow(2).unwrap();
+        assert_eq!(mem.stack_bytes(), [0, 0]);
+    }
+
+    #[test]
+    fn memory_model() {
+        let mut mem = Memory::from_limits(16, 16);
+        mem.stack.expand(4).unwrap();
+        mem.heap.expand(4).unwrap();
+        assert_eq!(mem.model(), MemoryModel::Split);
+        assert_eq!(mem.heap_base(), Memory::HEAP_BASE);
+        assert_eq!(mem.region_of(16), Region::Stack);
+
+        mem.set_model(MemoryModel::Unified);
+        assert_eq!(mem.heap_base(), 16);
+        assert_eq!(mem.region_of(15), Region::Stack);
+        assert_eq!(mem.region_of(16), Region::Heap);
+
+        mem.set::<u16>(17, 0x0201).unwrap();
+        assert_eq!(mem.heap_bytes(), [0, 1, 2, 0]);
+        mem.copy(0, 16, 4).unwrap();
+        assert_eq!(mem.stack_bytes(), [0, 1, 2, 0]);
+        assert_eq!(
+            mem.get::<u32>(18),
+            Err(MemoryError::OutOfBounds {
+                addr: 18,
+                size: 4,
+                region: Region::Heap,
+            })
+        );
+        assert!(mem.get::<u8>(4).is_err());
+    }
+
+    #[test]
+    fn memory_big_endian() {
+        let mut mem = Memory::from_limits_endian(2048, 2048, Endianness::Big);
+        mem.stack.expand(4).unwrap();
+        mem.set::<u32>(0, 0x0102_0304).unwrap();
+        assert_eq!(mem.stack.bytes(), [1, 2, 3, 4]);
+        assert_eq!(mem.get::<u32>(0), Ok(0x0102_0304));
+
+        mem.stack.bytes_mut().copy_from_slice(&[0xAB, 0xCD, 0, 0]);
+        assert_eq!(mem.get::<u16>(0), Ok(0xABCD));
+    }
+
+    #[test]
+    fn memory_stack_policy() {
+        let mut mem = Memory::from_limits(2048, 2048);
+        mem.stack.expand(4).unwrap();
+        mem.set::<u32>(0, 12).unwrap();
+        mem.stack.narrow(4).unwrap();
+        mem.stack.expand(4).unwrap();
+        assert_eq!(mem.get::<u32>(0), Ok(0));
+
+        mem.set::<u32>(0, 12).unwrap();
+        mem.set_stack_policy(StackPolicy::Uninitialized);
+        mem.stack.narrow(4).unwrap();
+        assert!(mem.get::<u32>(0).is_err());
+        mem.stack.expand(4).unwrap();
+        assert_eq!(mem.get::<u32>(0), Ok(12));
+    }
+
+    #[test]
+    fn memory_find() {
+        let mut mem = Memory::from_limits(2048, 2048);
+        mem.stack.expand(4).unwrap();
+        mem.heap.expand(4).unwrap();
+        mem.stack.get_mut(0, 4).unwrap().copy_from_slice(b"ab!c");
+        mem.heap.get_mut(0, 4).unwrap().copy_from_slice(b"abc!");
+
+        assert_eq!(mem.find(0, b'!', 4), Ok(2));
+        assert_eq!(mem.find(1, b'!', 100), Ok(1));
+        assert_eq!(mem.find(0, b'!', 2), Ok(2));
+        assert_eq!(mem.find(Memory::HEAP_BASE, b'!', 4), Ok(3));
+        assert_eq!(
+            mem.find(0, b'?', 5),
+            Err(MemoryError::OutOfBounds {
+                addr: 0,
+                size: 5,
+                region: Region::Stack,
+            })
+        );
+    }
+
+    #[test]
+    fn memory_dump() {
+        let mut mem = Memory::from_limits(2048, 2048);
+        mem.heap.expand(20).unwrap();
+        mem.heap
+            .get_mut(0, 20)
+            .unwrap()
+            .copy_from_slice(b"Hello, world!\n\0\x7fabcd");
+
+        let base = Memory::HEAP_BASE;
+        let expected = format!(
+            "{:08x}  48 65 6c 6c 6f 2c 20 77 6f 72 6c 64 21 0a 00 7f  |Hello, world!...|\n\
+             {:08x}  61 62 63 64                                      |abcd|\n",
+            base,
+            base + 16,
+        );
+        assert_eq!(mem.dump(base, 20), Ok(expected));
+        assert_eq!(mem.dump(base, 0), Ok(String::new()));
+        assert!(mem.dump(base + 8, 16).is_err());
+    }
+
+    #[test]
+    fn memory_readonly() {
+        let mut mem = Memory::from_limits(2048, 2048);
+        mem.stack.expand(8).unwrap();
+        mem.set::<u32>(4, 7).unwrap();
+        mem.mark_readonly(4, 2);
+
+        assert_eq!(mem.get::<u32>(4), Ok(7));
+        assert_eq!(mem.set::<u32>(2, 0), Err(MemoryError::WriteToReadOnly(4)));
+        assert_eq!(mem.set::<u8>(5, 0), Err(MemoryError::WriteToReadOnly(5)));
+        assert_eq!(mem.copy(0, 4, 8), Err(MemoryError::WriteToReadOnly(4)));
+        assert_eq!(mem.set_zeros(0, 8), Err(MemoryError::WriteToReadOnly(4)));
+        assert_eq!(mem.fill(5, 1, 1), Err(MemoryError::WriteToReadOnly(5)));
+        assert_eq!(mem.get::<u32>(4), Ok(7));
+        assert!(matches!(
+            mem.set_zeros(6, UWord::MAX),
+            Err(MemoryError::OutOfBounds { .. })
+        ));
+
+        assert_eq!(mem.set::<u32>(0, 1), Ok(()));
+        assert_eq!(mem.fill(6, 1, 2), Ok(()));
+        assert_eq!(mem.copy(0, 4, 4), Ok(()));
+        assert_eq!(mem.get::<u32>(0), Ok(0x0101_0007));
+    }
+
     #[test]
     fn memory_set_get_stack() {
         let mut mem = Memory::from_limits(2048, 2048);
         mem.stack.expand(9).unwrap();
         mem.set(1, 0xFF000F0A_usize).unwrap();
-        assert_eq!(mem.stack.page.as_slice(), [0, 10, 15, 0, 255, 0, 0, 0, 0]);
+        assert_eq!(mem.stack.bytes(), [0, 10, 15, 0, 255, 0, 0, 0, 0]);
 
         let value: usize = mem.get(1).unwrap();
         assert_eq!(value, 0xFF000F0A_usize);
@@ -262,7 +782,7 @@ mod tests {
         let mut mem = Memory::from_limits(2048, 2048);
         mem.heap.expand(9).unwrap();
         mem.set(Memory::HEAP_BASE + 1, 0xFF000F0A_usize).unwrap();
-        assert_eq!(mem.heap.page.as_slice(), [0, 10, 15, 0, 255, 0, 0, 0, 0]);
+        assert_eq!(mem.heap.bytes(), [0, 10, 15, 0, 255, 0, 0, 0, 0]);
 
         let value: usize = mem.get(Memory::HEAP_BASE + 1).unwrap();
         assert_eq!(value, 0xFF000F0A_usize);
@@ -294,7 +814,7 @@ mod tests {
         mem.heap.expand(8).unwrap();
         mem.copy(Memory::HEAP_BASE, 0, 8).unwrap();
 
-        assert_eq!(mem.stack.page.as_slice(), mem.heap.page.as_slice());
+        assert_eq!(mem.stack.bytes(), mem.heap.bytes());
 
         let mut mem = Memory::from_limits(2048, 2048);
         mem.heap.expand(8).unwrap();
@@ -303,7 +823,7 @@ mod tests {
         mem.stack.expand(8).unwrap();
         mem.copy(0, Memory::HEAP_BASE, 8).unwrap();
 
-        assert_eq!(mem.stack.page.as_slice(), mem.heap.page.as_slice());
+        assert_eq!(mem.stack.bytes(), mem.heap.bytes());
     }
 
     #[test]
@@ -314,7 +834,7 @@ mod tests {
 
         mem.copy(8, 0, 8).unwrap();
         assert_eq!(
-            mem.stack.page.as_slice(),
+            mem.stack.bytes(),
             [4, 255, 0, 0, 0, 0, 0, 0, 4, 255, 0, 0, 0, 0, 0, 0,]
         );
 
@@ -325,7 +845,7 @@ mod tests {
         mem.copy(Memory::HEAP_BASE + 8, Memory::HEAP_BASE, 8)
             .unwrap();
         assert_eq!(
-            mem.heap.page.as_slice(),
+            mem.heap.bytes(),
             [4, 255, 0, 0, 0, 0, 0, 0, 4, 255, 0, 0, 0, 0, 0, 0,]
         );
     }
@@ -342,16 +862,65 @@ mod tests {
         mem.copy(0, 1, 0).unwrap();
         mem.copy(1, 1, 0).unwrap();
 
-        assert_eq!(mem.copy(1, 0, 1), Err(MemoryError::SegmentationFault(1, 1)));
-        assert_eq!(mem.copy(0, 0, 2), Err(MemoryError::SegmentationFault(0, 2)));
+        let out_of_bounds = |addr, size| MemoryError::OutOfBounds {
+            addr,
+            size,
+            region: Region::Stack,
+        };
+
+        assert_eq!(mem.copy(1, 0, 1), Err(out_of_bounds(1, 1)));
+        assert_eq!(mem.copy(0, 0, 2), Err(out_of_bounds(0, 2)));
+
+        let heap = Memory::HEAP_BASE;
+        assert_eq!(
+            mem.copy(heap, 0, 1),
+            Err(MemoryError::OutOfBounds {
+                addr: heap,
+                size: 1,
+                region: Region::Heap,
+            })
+        );
+    }
+
+    #[test]
+    fn memory_copy_overlap() {
+        let mut mem = Memory::from_limits(2048, 2048);
+        mem.stack.expand(8).unwrap();
+        mem.slice_mut(0, 8)
+            .unwrap()
+            .copy_from_slice(&[1, 2, 3, 4, 5, 6, 0, 0]);
+
+        mem.copy(2, 0, 6).unwrap();
+        assert_eq!(mem.stack.bytes(), [1, 2, 1, 2, 3, 4, 5, 6]);
+
+        mem.copy(0, 2, 6).unwrap();
+        assert_eq!(mem.stack.bytes(), [1, 2, 3, 4, 5, 6, 5, 6]);
     }
 
     #[test]
-    fn memory_copy_wrong_range() {
+    fn memory_copy_overflow() {
         let mut mem = Memory::from_limits(2048, 2048);
         mem.stack.expand(2).unwrap();
 
-        assert_eq!(mem.copy(0, 1, UWord::MAX), Err(MemoryError::WrongRange));
+        let out_of_bounds = |addr| MemoryError::OutOfBounds {
+            addr,
+            size: UWord::MAX,
+            region: Region::Stack,
+        };
+
+        assert_eq!(mem.copy(0, 1, UWord::MAX), Err(out_of_bounds(1)));
+        assert_eq!(mem.set_zeros(1, UWord::MAX), Err(out_of_bounds(1)));
+        assert_eq!(mem.compare(1, 0, UWord::MAX), Err(out_of_bounds(1)));
+    }
+
+    #[test]
+    fn memory_zero_size() {
+        let mut mem = Memory::from_limits(2048, 2048);
+        let far = Memory::HEAP_BASE - 1;
+
+        assert_eq!(mem.copy(far, 0, 0), Ok(()));
+        assert_eq!(mem.set_zeros(far, 0), Ok(()));
+        assert_eq!(mem.compare(far, 0, 0), Ok(true));
     }
 
     #[test]
@@ -362,7 +931,7 @@ mod tests {
         mem.set(8, 0xFFFF).unwrap();
         mem.set_zeros(0, 16).unwrap();
 
-        assert!(mem.stack.page.iter().all(|b| *b == 0));
+        assert!(mem.stack.bytes().iter().all(|b| *b == 0));
 
         let mut mem = Memory::from_limits(2048, 2048);
         mem.heap.expand(16).unwrap();
@@ -370,6 +939,6 @@ mod tests {
         mem.set(Memory::HEAP_BASE + 8, 0xFFFF).unwrap();
         mem.set_zeros(Memory::HEAP_BASE, 16).unwrap();
 
-        assert!(mem.heap.page.iter().all(|b| *b == 0));
+        assert!(mem.heap.bytes().iter().all(|b| *b == 0));
     }
 }
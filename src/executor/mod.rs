@@ -1,6 +1,17 @@
+mod builder;
+mod cost;
+#[allow(clippy::module_inception)]
 mod executor;
-mod files;
-mod memory;
+pub mod files;
+pub mod memory;
 pub mod primary;
+mod profile;
+mod scheduler;
+mod validate;
 
+pub use builder::*;
+pub use cost::*;
 pub use executor::*;
+pub use profile::*;
+pub use scheduler::*;
+pub use validate::*;
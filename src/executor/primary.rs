@@ -1,9 +1,10 @@
-use crate::common::UWord;
+use crate::common::{IWord, UWord};
+use half::f16;
 
 pub trait Primary: Sized {
-    const SIZE: usize = std::mem::size_of::<Self>();
+    const SIZE: usize = core::mem::size_of::<Self>();
 
-    type Bytes: std::borrow::Borrow<[u8]>;
+    type Bytes: core::borrow::Borrow<[u8]>;
 
     fn to_bytes(&self) -> Self::Bytes;
 
@@ -15,14 +16,36 @@ pub trait Primary: Sized {
 
     fn one() -> Self;
 
+    /// Converts the word to the value.
+    /// Signed values wider than the word are sign-extended.
     fn from_word(val: UWord) -> Self;
+
+    /// Reads the value from the first `SIZE` bytes in little endian.
+    ///
+    /// Panics if the slice is shorter than `SIZE`.
+    fn read_le(bytes: &[u8]) -> Self {
+        Self::from_slice(&bytes[..Self::SIZE])
+    }
+
+    /// Writes the value to the first `SIZE` bytes in little endian.
+    ///
+    /// Panics if the slice is shorter than `SIZE`.
+    fn write_le(self, bytes: &mut [u8]) {
+        use core::borrow::Borrow;
+
+        bytes[..Self::SIZE].copy_from_slice(self.to_bytes().borrow());
+    }
+
+    /// Replaces any float NaN with the single NaN bit pattern.
+    /// Other values are returned as is.
+    fn canonical(self) -> Self;
 }
 
 macro_rules! impl_primary {
-    ($($t:ty),+) => {
+    ($signed:expr, $canonical:expr; $($t:ty),+) => {
         $(
         impl Primary for $t {
-            type Bytes = [u8; std::mem::size_of::<Self>()];
+            type Bytes = [u8; core::mem::size_of::<Self>()];
 
             fn to_bytes(&self) -> Self::Bytes { self.to_le_bytes() }
 
@@ -43,21 +66,67 @@ macro_rules! impl_primary {
             fn one() -> Self { 1 as $t }
 
             fn from_word(val: UWord) -> Self {
-                let mut buf = [0; Self::SIZE];
+                let negative = $signed && (val as IWord) < 0;
+                let mut buf = [if negative { 0xFF } else { 0 }; Self::SIZE];
                 let ubytes = UWord::to_le_bytes(val);
 
-                for i in 0..Self::SIZE.min(std::mem::size_of::<UWord>()) {
+                for i in 0..Self::SIZE.min(core::mem::size_of::<UWord>()) {
                     buf[i] = ubytes[i];
                 }
 
                 Self::from_bytes(buf)
             }
+
+            fn canonical(self) -> Self {
+                let f: fn(Self) -> Self = $canonical;
+                f(self)
+            }
         }
         )+
     }
 }
 
-impl_primary!(u8, i8, u16, i16, u32, i32, u64, i64, u128, i128, usize, isize, f32, f64);
+impl_primary!(false, |v| v; u8, u16, u32, u64, u128, usize);
+impl_primary!(true, |v| v; i8, i16, i32, i64, i128, isize);
+impl_primary!(false, |v| if v.is_nan() { Self::NAN } else { v }; f32, f64);
+
+impl Primary for f16 {
+    type Bytes = [u8; 2];
+
+    fn to_bytes(&self) -> Self::Bytes {
+        self.to_le_bytes()
+    }
+
+    fn from_bytes(bytes: Self::Bytes) -> Self {
+        Self::from_le_bytes(bytes)
+    }
+
+    fn from_slice(slice: &[u8]) -> Self {
+        let mut buf = [0; Self::SIZE];
+        buf[..slice.len()].copy_from_slice(slice);
+        Self::from_bytes(buf)
+    }
+
+    fn zero() -> Self {
+        f16::ZERO
+    }
+
+    fn one() -> Self {
+        f16::ONE
+    }
+
+    fn from_word(val: UWord) -> Self {
+        Self::from_bits(val as u16)
+    }
+
+    fn canonical(self) -> Self {
+        if self.is_nan() {
+            f16::NAN
+        } else {
+            self
+        }
+    }
+}
 
 pub trait Add: Primary {
     fn wrapping(self, r: Self) -> Self;
@@ -79,6 +148,10 @@ macro_rules! impl_add {
 
 impl_add!(u8, i8, u16, i16, u32, i32, u64, i64, u128, i128, usize, isize);
 
+// The float ops are single IEEE 754 ops rounded to the type. Rust never
+// contracts `x * y + z` into a fused multiply-add and never reorders float
+// ops, so the results are bit-identical on every platform. Only the NaN
+// payloads may differ, see `Executor::set_canonical_nan`.
 impl Add for f32 {
     fn wrapping(self, r: Self) -> Self {
         self + r
@@ -91,6 +164,18 @@ impl Add for f32 {
     }
 }
 
+impl Add for f16 {
+    fn wrapping(self, r: Self) -> Self {
+        self + r
+    }
+    fn saturating(self, r: Self) -> Self {
+        self + r
+    }
+    fn checked(self, r: Self) -> Option<Self> {
+        Some(self + r)
+    }
+}
+
 impl Add for f64 {
     fn wrapping(self, r: Self) -> Self {
         self + r
@@ -135,6 +220,18 @@ impl Sub for f32 {
     }
 }
 
+impl Sub for f16 {
+    fn wrapping(self, r: Self) -> Self {
+        self - r
+    }
+    fn saturating(self, r: Self) -> Self {
+        self - r
+    }
+    fn checked(self, r: Self) -> Option<Self> {
+        Some(self - r)
+    }
+}
+
 impl Sub for f64 {
     fn wrapping(self, r: Self) -> Self {
         self - r
@@ -179,6 +276,18 @@ impl Mul for f32 {
     }
 }
 
+impl Mul for f16 {
+    fn wrapping(self, r: Self) -> Self {
+        self * r
+    }
+    fn saturating(self, r: Self) -> Self {
+        self * r
+    }
+    fn checked(self, r: Self) -> Option<Self> {
+        Some(self * r)
+    }
+}
+
 impl Mul for f64 {
     fn wrapping(self, r: Self) -> Self {
         self * r
@@ -191,15 +300,66 @@ impl Mul for f64 {
     }
 }
 
+/// Integer ops chained through the carry bit for the multi-word arithmetic.
+///
+/// The carry and the borrow are of the unsigned bits, so the signed types
+/// give the same flag as the unsigned of the same size.
+pub trait Carry: Primary {
+    /// Returns `self + r + carry` and the carry out.
+    fn add_carry(self, r: Self, carry: bool) -> (Self, bool);
+    /// Returns `self - r - borrow` and the borrow out.
+    fn sub_borrow(self, r: Self, borrow: bool) -> (Self, bool);
+}
+
+macro_rules! impl_carry {
+    ($($t:ty => $u:ty),+) => {
+        $(
+        impl Carry for $t {
+            fn add_carry(self, r: Self, carry: bool) -> (Self, bool) {
+                let (a, x) = (self as $u).overflowing_add(r as $u);
+                let (b, y) = a.overflowing_add(carry as $u);
+                (b as Self, x || y)
+            }
+
+            fn sub_borrow(self, r: Self, borrow: bool) -> (Self, bool) {
+                let (a, x) = (self as $u).overflowing_sub(r as $u);
+                let (b, y) = a.overflowing_sub(borrow as $u);
+                (b as Self, x || y)
+            }
+        }
+        )+
+    }
+}
+
+impl_carry!(
+    u8 => u8,
+    i8 => u8,
+    u16 => u16,
+    i16 => u16,
+    u32 => u32,
+    i32 => u32,
+    u64 => u64,
+    i64 => u64,
+    u128 => u128,
+    i128 => u128,
+    usize => usize,
+    isize => usize
+);
+
 pub trait Div: Primary {
     fn wrapping(self, r: Self) -> Self;
+    fn checked(self, r: Self) -> Option<Self>;
 }
 
+// The integer `wrapping_div` and `wrapping_rem` differ from `/` and `%` only
+// for the signed `MIN / -1`, which wraps to `MIN` and `0` instead of panicking.
+// The float `wrapping` is the plain op.
 macro_rules! impl_div {
     ($($t:ty),+) => {
         $(
         impl Div for $t {
             fn wrapping(self, r: Self) -> Self { self.wrapping_div(r) }
+            fn checked(self, r: Self) -> Option<Self> { self.checked_div(r) }
         }
         )+
     }
@@ -211,16 +371,32 @@ impl Div for f32 {
     fn wrapping(self, r: Self) -> Self {
         self / r
     }
+    fn checked(self, r: Self) -> Option<Self> {
+        Some(self / r)
+    }
+}
+
+impl Div for f16 {
+    fn wrapping(self, r: Self) -> Self {
+        self / r
+    }
+    fn checked(self, r: Self) -> Option<Self> {
+        Some(self / r)
+    }
 }
 
 impl Div for f64 {
     fn wrapping(self, r: Self) -> Self {
         self / r
     }
+    fn checked(self, r: Self) -> Option<Self> {
+        Some(self / r)
+    }
 }
 
 pub trait Rem: Primary {
     fn wrapping(self, r: Self) -> Self;
+    fn checked(self, r: Self) -> Option<Self>;
 }
 
 macro_rules! impl_rem {
@@ -228,6 +404,7 @@ macro_rules! impl_rem {
         $(
         impl Rem for $t {
             fn wrapping(self, r: Self) -> Self { self.wrapping_rem(r) }
+            fn checked(self, r: Self) -> Option<Self> { self.checked_rem(r) }
         }
         )+
     }
@@ -239,12 +416,18 @@ impl Rem for f32 {
     fn wrapping(self, r: Self) -> Self {
         self % r
     }
+    fn checked(self, r: Self) -> Option<Self> {
+        Some(self % r)
+    }
 }
 
 impl Rem for f64 {
     fn wrapping(self, r: Self) -> Self {
         self % r
     }
+    fn checked(self, r: Self) -> Option<Self> {
+        Some(self % r)
+    }
 }
 
 pub trait Shl: Primary {
@@ -279,6 +462,116 @@ macro_rules! impl_shr {
 
 impl_shr!(u8, i8, u16, i16, u32, i32, u64, i64, u128, i128, usize, isize);
 
+pub trait Rol: Primary {
+    fn rotate(self, r: u8) -> Self;
+}
+
+macro_rules! impl_rol {
+    ($($t:ty),+) => {
+        $(
+        impl Rol for $t {
+            fn rotate(self, r: u8) -> Self { self.rotate_left(r as u32) }
+        }
+        )+
+    }
+}
+
+impl_rol!(u8, i8, u16, i16, u32, i32, u64, i64, u128, i128, usize, isize);
+
+pub trait Ror: Primary {
+    fn rotate(self, r: u8) -> Self;
+}
+
+macro_rules! impl_ror {
+    ($($t:ty),+) => {
+        $(
+        impl Ror for $t {
+            fn rotate(self, r: u8) -> Self { self.rotate_right(r as u32) }
+        }
+        )+
+    }
+}
+
+impl_ror!(u8, i8, u16, i16, u32, i32, u64, i64, u128, i128, usize, isize);
+
+pub trait Count: Primary {
+    fn count_ones(self) -> Self;
+    fn leading_zeros(self) -> Self;
+    fn trailing_zeros(self) -> Self;
+}
+
+macro_rules! impl_count {
+    ($($t:ty),+) => {
+        $(
+        impl Count for $t {
+            fn count_ones(self) -> Self { <$t>::count_ones(self) as Self }
+            fn leading_zeros(self) -> Self { <$t>::leading_zeros(self) as Self }
+            fn trailing_zeros(self) -> Self { <$t>::trailing_zeros(self) as Self }
+        }
+        )+
+    }
+}
+
+impl_count!(u8, i8, u16, i16, u32, i32, u64, i64, u128, i128, usize, isize);
+
+/// For floats a NaN operand is ignored, the result is NaN only if both
+/// operands are NaN.
+pub trait MinMax: Primary {
+    fn minimum(self, r: Self) -> Self;
+    fn maximum(self, r: Self) -> Self;
+}
+
+macro_rules! impl_min_max {
+    ($($t:ty),+) => {
+        $(
+        impl MinMax for $t {
+            fn minimum(self, r: Self) -> Self { <$t>::min(self, r) }
+            fn maximum(self, r: Self) -> Self { <$t>::max(self, r) }
+        }
+        )+
+    }
+}
+
+impl_min_max!(u8, i8, u16, i16, u32, i32, u64, i64, u128, i128, usize, isize, f32, f64);
+
+/// The absolute value of the signed integer minimum is the minimum itself.
+pub trait Abs: Primary {
+    fn abs(self) -> Self;
+}
+
+macro_rules! impl_abs {
+    (signed: $($s:ty),+; unsigned: $($u:ty),+) => {
+        $(
+        impl Abs for $s {
+            fn abs(self) -> Self { self.wrapping_abs() }
+        }
+        )+
+
+        $(
+        impl Abs for $u {
+            fn abs(self) -> Self { self }
+        }
+        )+
+    }
+}
+
+impl_abs!(
+    signed: i8, i16, i32, i64, i128, isize;
+    unsigned: u8, u16, u32, u64, u128, usize
+);
+
+impl Abs for f32 {
+    fn abs(self) -> Self {
+        f32::abs(self)
+    }
+}
+
+impl Abs for f64 {
+    fn abs(self) -> Self {
+        f64::abs(self)
+    }
+}
+
 pub trait Neg: Primary {
     fn wrapping(self) -> Self;
     fn saturating(self) -> Self;
@@ -369,8 +662,69 @@ where
     }
 }
 
+/// Float functions. Without the `std` feature they are computed by `libm`,
+/// so the results of the transcendental functions may differ in the last bit.
+pub trait Float: Primary {
+    fn sqrt(self) -> Self;
+    fn sin(self) -> Self;
+    fn cos(self) -> Self;
+    fn ln(self) -> Self;
+    fn exp(self) -> Self;
+    fn round(self) -> Self;
+    fn is_nan(&self) -> bool;
+}
+
+#[cfg(feature = "std")]
+macro_rules! impl_float {
+    ($($t:ty: $sqrt:ident, $sin:ident, $cos:ident, $ln:ident, $exp:ident, $round:ident);+) => {
+        $(
+        impl Float for $t {
+            fn sqrt(self) -> Self { <$t>::sqrt(self) }
+            fn sin(self) -> Self { <$t>::sin(self) }
+            fn cos(self) -> Self { <$t>::cos(self) }
+            fn ln(self) -> Self { <$t>::ln(self) }
+            fn exp(self) -> Self { <$t>::exp(self) }
+            fn round(self) -> Self { <$t>::round(self) }
+            fn is_nan(&self) -> bool { <$t>::is_nan(*self) }
+        }
+        )+
+    }
+}
+
+#[cfg(not(feature = "std"))]
+macro_rules! impl_float {
+    ($($t:ty: $sqrt:ident, $sin:ident, $cos:ident, $ln:ident, $exp:ident, $round:ident);+) => {
+        $(
+        impl Float for $t {
+            fn sqrt(self) -> Self { libm::$sqrt(self) }
+            fn sin(self) -> Self { libm::$sin(self) }
+            fn cos(self) -> Self { libm::$cos(self) }
+            fn ln(self) -> Self { libm::$ln(self) }
+            fn exp(self) -> Self { libm::$exp(self) }
+            fn round(self) -> Self { libm::$round(self) }
+            fn is_nan(&self) -> bool { <$t>::is_nan(*self) }
+        }
+        )+
+    }
+}
+
+impl_float!(
+    f32: sqrtf, sinf, cosf, logf, expf, roundf;
+    f64: sqrt, sin, cos, log, exp, round
+);
+
+/// Conversion of the value of type `T`.
+///
+/// Floats are converted to integers truncating toward zero and saturating
+/// on overflow. NaN and infinities are converted to zero.
 pub trait Convert<T>: Primary {
     fn convert(v: T) -> Self;
+
+    /// Same as `convert`, but floats are rounded to the nearest integer
+    /// first, with ties rounded away from zero.
+    fn convert_round(v: T) -> Self {
+        Self::convert(v)
+    }
 }
 
 macro_rules! impl_convert {
@@ -396,6 +750,10 @@ macro_rules! impl_convert_f {
                     v as Self
                 }
             }
+
+            fn convert_round(v: f32) -> Self {
+                Self::convert(Float::round(v))
+            }
         }
 
         impl Convert<f64> for $u {
@@ -406,6 +764,10 @@ macro_rules! impl_convert_f {
                     v as Self
                 }
             }
+
+            fn convert_round(v: f64) -> Self {
+                Self::convert(Float::round(v))
+            }
         }
         )+
     }
@@ -426,3 +788,160 @@ impl_convert!(usize; u8, i8, u16, i16, u32, i32, u64, i64, u128, i128, usize, is
 impl_convert!(isize; u8, i8, u16, i16, u32, i32, u64, i64, u128, i128, usize, isize);
 impl_convert!(f32; u8, i8, u16, i16, u32, i32, u64, i64, u128, i128, usize, isize);
 impl_convert!(f64; u8, i8, u16, i16, u32, i32, u64, i64, u128, i128, usize, isize);
+
+// Half floats are converted through `f32`, which holds any `f16` exactly.
+macro_rules! impl_convert_f16 {
+    ($($t:ty),+) => {
+        $(
+        impl Convert<f16> for $t {
+            fn convert(v: f16) -> Self {
+                <$t as Convert<f32>>::convert(v.to_f32())
+            }
+
+            fn convert_round(v: f16) -> Self {
+                <$t as Convert<f32>>::convert_round(v.to_f32())
+            }
+        }
+
+        impl Convert<$t> for f16 {
+            fn convert(v: $t) -> Self {
+                f16::from_f32(<f32 as Convert<$t>>::convert(v))
+            }
+
+            fn convert_round(v: $t) -> Self {
+                f16::from_f32(<f32 as Convert<$t>>::convert_round(v))
+            }
+        }
+        )+
+    }
+}
+
+impl_convert_f16!(u8, i8, u16, i16, u32, i32, u64, i64, u128, i128, usize, isize, f32);
+
+impl Convert<f16> for f64 {
+    fn convert(v: f16) -> Self {
+        <f64 as Convert<f32>>::convert(v.to_f32())
+    }
+
+    fn convert_round(v: f16) -> Self {
+        <f64 as Convert<f32>>::convert_round(v.to_f32())
+    }
+}
+
+impl Convert<f64> for f16 {
+    fn convert(v: f64) -> Self {
+        f16::from_f64(<f64 as Convert<f64>>::convert(v))
+    }
+
+    fn convert_round(v: f64) -> Self {
+        f16::from_f64(<f64 as Convert<f64>>::convert_round(v))
+    }
+}
+
+impl Convert<f16> for f16 {
+    fn convert(v: f16) -> Self {
+        f16::from_f32(<f32 as Convert<f32>>::convert(v.to_f32()))
+    }
+
+    fn convert_round(v: f16) -> Self {
+        f16::from_f32(<f32 as Convert<f32>>::convert_round(v.to_f32()))
+    }
+}
+
+/// Conversion of random bits to a value. Floats are in `[0, 1)`.
+pub trait Random: Primary {
+    fn from_random(bits: u64) -> Self;
+}
+
+macro_rules! impl_random {
+    ($($t:ty),+) => {
+        $(
+        impl Random for $t {
+            fn from_random(bits: u64) -> Self { bits as Self }
+        }
+        )+
+    }
+}
+
+impl_random!(u8, i8, u16, i16, u32, i32, u64, i64, usize, isize);
+
+impl Random for f32 {
+    fn from_random(bits: u64) -> Self {
+        (bits >> 40) as f32 / (1_u32 << 24) as f32
+    }
+}
+
+impl Random for f64 {
+    fn from_random(bits: u64) -> Self {
+        (bits >> 11) as f64 / (1_u64 << 53) as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip<T>(val: T)
+    where
+        T: Primary + PartialEq + core::fmt::Debug + Copy,
+    {
+        let mut buf = [0xFF; 12];
+        val.write_le(&mut buf[1..]);
+
+        assert_eq!(T::read_le(&buf[1..]), val);
+        assert_eq!(buf[0], 0xFF);
+        assert!(buf[T::SIZE + 1..].iter().all(|&b| b == 0xFF));
+    }
+
+    #[test]
+    fn primary_read_write_le() {
+        round_trip(0xAB_u8);
+        round_trip(-3_i8);
+        round_trip(0xABCD_u16);
+        round_trip(-300_i16);
+        round_trip(0xABCD_EF01_u32);
+        round_trip(-70000_i32);
+        round_trip(0x0123_4567_89AB_CDEF_u64);
+        round_trip(i64::MIN);
+        round_trip(UWord::MAX);
+        round_trip(IWord::MIN);
+        round_trip(1.5_f32);
+        round_trip(-0.25_f64);
+
+        let mut buf = [0; 4];
+        0x0102_0304_u32.write_le(&mut buf);
+        assert_eq!(buf, [4, 3, 2, 1]);
+    }
+
+    #[test]
+    fn carry() {
+        assert_eq!(255u8.add_carry(0, true), (0, true));
+        assert_eq!(254u8.add_carry(1, false), (255, false));
+        assert_eq!(0u8.sub_borrow(0, true), (255, true));
+        assert_eq!(127i8.add_carry(0, true), (-128, false));
+        assert_eq!((-1i8).add_carry(0, true), (0, true));
+        assert_eq!((-1i8).add_carry(-1, false), (-2, true));
+        assert_eq!(0i8.sub_borrow(1, false), (-1, true));
+        assert_eq!((-128i8).sub_borrow(0, true), (127, false));
+    }
+
+    #[test]
+    fn wrapping_ops() {
+        assert_eq!(Div::wrapping(1.0_f64, 3.0).to_bits(), 0x3FD5_5555_5555_5555);
+        assert_eq!(Div::wrapping(1.0_f32, 3.0).to_bits(), 0x3EAA_AAAB);
+        assert_eq!(Div::checked(1.0_f64, 3.0), Some(1.0 / 3.0));
+        assert_eq!(Rem::wrapping(7.5_f64, 2.0), 1.5);
+        assert_eq!(Add::wrapping(f32::MAX, f32::MAX), f32::INFINITY);
+        assert_eq!(Mul::wrapping(0.1_f64, 3.0), 0.1 * 3.0);
+        assert_eq!(Inc::wrapping(0.5_f64), 1.5);
+
+        assert_eq!(Div::wrapping(i32::MIN, -1), i32::MIN);
+        assert_eq!(Div::checked(i32::MIN, -1), None);
+        assert_eq!(Rem::wrapping(i32::MIN, -1), 0);
+        assert_eq!(Rem::checked(i32::MIN, -1), None);
+        assert_eq!(Div::wrapping(-7_i32, 2), -3);
+        assert_eq!(Rem::wrapping(-7_i32, 2), -1);
+        assert_eq!(Neg::wrapping(i32::MIN), i32::MIN);
+        assert_eq!(Dec::wrapping(0_u8), 255);
+    }
+}
@@ -0,0 +1,54 @@
+/// Opcode execution counters collected by the executor.
+#[derive(Clone, Debug)]
+pub struct Profile {
+    ops: [u64; Profile::OP_CODES],
+    steps: u64,
+    skipped: u64,
+}
+
+impl Profile {
+    pub const OP_CODES: usize = u8::MAX as usize + 1;
+
+    pub fn new() -> Self {
+        Self {
+            ops: [0; Self::OP_CODES],
+            steps: 0,
+            skipped: 0,
+        }
+    }
+
+    /// Number of executed ops with the given op code.
+    pub fn op_count(&self, op_code: u8) -> u64 {
+        self.ops[op_code as usize]
+    }
+
+    /// Counters of all op codes indexed by op code.
+    pub fn op_counts(&self) -> &[u64; Profile::OP_CODES] {
+        &self.ops
+    }
+
+    /// Total number of executed ops.
+    pub fn steps(&self) -> u64 {
+        self.steps
+    }
+
+    /// Number of ops skipped by failed conditions.
+    pub fn skipped(&self) -> u64 {
+        self.skipped
+    }
+
+    pub(super) fn count_op(&mut self, op_code: u8) {
+        self.ops[op_code as usize] += 1;
+        self.steps += 1;
+    }
+
+    pub(super) fn count_skipped(&mut self) {
+        self.skipped += 1;
+    }
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Self::new()
+    }
+}
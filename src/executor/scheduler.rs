@@ -0,0 +1,191 @@
+use super::{ExecutionError, ExecutionSuccess, Executor};
+use crate::common::UWord;
+use alloc::vec::Vec;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum TaskState {
+    Ready,
+    /// The task is parked for the number of ticks.
+    Sleep(u64),
+    End(UWord),
+}
+
+/// Error of the task with the `task` id.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct TaskError {
+    pub task: usize,
+    pub error: ExecutionError,
+}
+
+#[derive(Debug)]
+struct Task<'f> {
+    executor: Executor<'f>,
+    state: TaskState,
+}
+
+/// Round-robin scheduler of executors.
+///
+/// Every tick each ready executor executes one op. An executor which sleeps
+/// until its virtual time `t` is parked and skips the ticks until then.
+#[derive(Debug, Default)]
+pub struct Scheduler<'f> {
+    tasks: Vec<Task<'f>>,
+    ticks: u64,
+}
+
+impl<'f> Scheduler<'f> {
+    pub fn new() -> Self {
+        Self {
+            tasks: Vec::new(),
+            ticks: 0,
+        }
+    }
+
+    /// Adds the executor with a prepared call and returns the task id.
+    pub fn spawn(&mut self, executor: Executor<'f>) -> usize {
+        self.tasks.push(Task {
+            executor,
+            state: TaskState::Ready,
+        });
+
+        self.tasks.len() - 1
+    }
+
+    pub fn state(&self, task: usize) -> Option<TaskState> {
+        self.tasks.get(task).map(|t| t.state)
+    }
+
+    pub fn executor(&self, task: usize) -> Option<&Executor<'f>> {
+        self.tasks.get(task).map(|t| &t.executor)
+    }
+
+    /// Number of elapsed ticks.
+    pub fn ticks(&self) -> u64 {
+        self.ticks
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.tasks
+            .iter()
+            .all(|t| matches!(t.state, TaskState::End(_)))
+    }
+
+    /// Runs one tick.
+    pub fn tick(&mut self) -> Result<(), TaskError> {
+        for (id, task) in self.tasks.iter_mut().enumerate() {
+            match task.state {
+                TaskState::Ready => (),
+                TaskState::Sleep(0) => task.state = TaskState::Ready,
+                TaskState::Sleep(n) => {
+                    task.state = TaskState::Sleep(n - 1);
+                    continue;
+                }
+                TaskState::End(_) => continue,
+            }
+
+            task.state = match task.executor.execute() {
+                Ok(ExecutionSuccess::Ok) | Ok(ExecutionSuccess::Yield(_)) => TaskState::Ready,
+                Ok(ExecutionSuccess::Sleep(wake)) => {
+                    TaskState::Sleep(wake.saturating_sub(task.executor.virtual_time()))
+                }
+                Ok(ExecutionSuccess::End(val)) => TaskState::End(val),
+                Err(error) => return Err(TaskError { task: id, error }),
+            };
+        }
+
+        self.ticks += 1;
+        Ok(())
+    }
+
+    /// Runs until every executor ends and returns their end values.
+    pub fn run(&mut self) -> Result<Vec<UWord>, TaskError> {
+        while !self.is_done() {
+            self.tick()?;
+        }
+
+        Ok(self
+            .tasks
+            .iter()
+            .map(|t| match t.state {
+                TaskState::End(val) => val,
+                _ => unreachable!(),
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        common::{Op, Operand},
+        executor::Function,
+    };
+    use std::{cell::RefCell, rc::Rc};
+
+    #[test]
+    fn scheduler_run() {
+        let functions = [
+            Function::new(
+                0,
+                &[
+                    Op::Nop,
+                    Op::Slp(Operand::Val(2)),
+                    Op::Nop,
+                    Op::End(Operand::Val(1)),
+                ],
+            ),
+            Function::new(
+                0,
+                &[Op::Nop, Op::Nop, Op::Nop, Op::Nop, Op::End(Operand::Val(2))],
+            ),
+        ];
+
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let mut scheduler = Scheduler::new();
+
+        for id in 0..2 {
+            let mut exe = Executor::new(&functions);
+            exe.call(id, 0).unwrap();
+
+            let log = Rc::clone(&log);
+            exe.set_trace(Box::new(move |pc, _| log.borrow_mut().push((id, pc))));
+            scheduler.spawn(exe);
+        }
+
+        assert_eq!(scheduler.run(), Ok(vec![1, 2]));
+        assert_eq!(scheduler.ticks(), 6);
+        assert_eq!(
+            *log.borrow(),
+            [
+                (0, 0),
+                (1, 0),
+                (0, 1),
+                (1, 1),
+                (1, 2),
+                (1, 3),
+                (0, 2),
+                (1, 4),
+                (0, 3),
+            ]
+        );
+    }
+
+    #[test]
+    fn scheduler_error() {
+        let functions = [Function::new(0, &[Op::Nop])];
+
+        let mut scheduler = Scheduler::new();
+        let mut exe = Executor::new(&functions);
+        exe.call(0, 0).unwrap();
+        scheduler.spawn(exe);
+
+        assert_eq!(
+            scheduler.run(),
+            Err(TaskError {
+                task: 0,
+                error: ExecutionError::EndOfProgram,
+            })
+        );
+    }
+}
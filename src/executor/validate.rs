@@ -0,0 +1,227 @@
+use super::Function;
+use crate::common::{IWord, Op, Operand, UWord};
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ValidationErrorKind {
+    /// The `go`, `gor` or `skp` target is beyond the program.
+    JumpOutOfRange(UWord),
+    /// The `app` refers to a nonexistent function.
+    UnknownFunction(UWord),
+    /// The destination operand is a value or empty.
+    IncorrectDestination(Operand),
+    /// The conditional op is not followed by an op to skip.
+    UnterminatedCondition,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct ValidationError {
+    pub function: UWord,
+    pub op: UWord,
+    pub kind: ValidationErrorKind,
+}
+
+/// Checks the functions for errors which can be found without execution.
+pub fn validate(functions: &[Function]) -> Result<(), ValidationError> {
+    for (function_id, function) in functions.iter().enumerate() {
+        let program = function.program();
+
+        for (idx, op) in program.iter().enumerate() {
+            if let Some(kind) = check_op(op, program, idx, functions.len()) {
+                return Err(ValidationError {
+                    function: function_id as UWord,
+                    op: idx as UWord,
+                    kind,
+                });
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn check_op(op: &Op, program: &[Op], idx: usize, functions: usize) -> Option<ValidationErrorKind> {
+    use Op::*;
+
+    if op.is_conditional() {
+        let terminated = program[idx + 1..].iter().any(|op| !op.is_conditional());
+
+        return if terminated {
+            None
+        } else {
+            Some(ValidationErrorKind::UnterminatedCondition)
+        };
+    }
+
+    let dest = match *op {
+        Go(Operand::Val(pc)) if pc as usize >= program.len() => {
+            return Some(ValidationErrorKind::JumpOutOfRange(pc))
+        }
+        Gor(Operand::Val(offset))
+            if idx.wrapping_add(offset as IWord as usize) >= program.len() =>
+        {
+            return Some(ValidationErrorKind::JumpOutOfRange(
+                (idx as UWord).wrapping_add(offset),
+            ))
+        }
+        Skp(Operand::Val(n))
+            if (idx + 1)
+                .checked_add(n as usize)
+                .filter(|&pc| pc < program.len())
+                .is_none() =>
+        {
+            return Some(ValidationErrorKind::JumpOutOfRange(
+                (idx as UWord + 1).wrapping_add(n),
+            ))
+        }
+        App(Operand::Val(id)) | Tap(Operand::Val(id)) if id as usize >= functions => {
+            return Some(ValidationErrorKind::UnknownFunction(id))
+        }
+        Set(bin, _)
+        | Add(bin, _)
+        | Sub(bin, _)
+        | Mul(bin, _)
+        | Div(bin, _)
+        | Mod(bin, _)
+        | And(bin, _)
+        | Or(bin, _)
+        | Xor(bin, _)
+        | Ads(bin, _)
+        | Sbs(bin, _)
+        | Adc(bin, _)
+        | Sbb(bin, _)
+        | Mls(bin, _)
+        | Min(bin, _)
+        | Max(bin, _)
+        | Shl(bin, _)
+        | Shr(bin, _)
+        | In(bin) => bin.x(),
+        Not(un, _)
+        | Neg(un, _)
+        | Inc(un, _)
+        | Dec(un, _)
+        | Sqr(un, _)
+        | Fun(un, _, _)
+        | Cnt(un, _)
+        | Clz(un, _)
+        | Ctz(un, _)
+        | Abs(un, _) => un.x(),
+        Cnv(x, _, _, _)
+        | Cnr(x, _, _, _)
+        | Bit(x, _, _, _)
+        | Rol(x, _, _)
+        | Ror(x, _, _)
+        | Gfd(x)
+        | Ovf(x)
+        | Lda(x, _)
+        | Sel(x, _, _, _, _)
+        | Gip(x)
+        | Grp(x)
+        | Scn(x, _, _, _)
+        | Rnd(x, _)
+        | Eof(x) => x,
+        Inb(_, y) => y,
+        Swp(x, y, _) | Rem(x, y) | Cas(x, y, _, _) => {
+            return check_dest(x).or_else(|| check_dest(y))
+        }
+        _ => return None,
+    };
+
+    check_dest(dest)
+}
+
+fn check_dest(dest: Operand) -> Option<ValidationErrorKind> {
+    match dest {
+        Operand::Val(_) | Operand::Ref(_) | Operand::Emp => {
+            Some(ValidationErrorKind::IncorrectDestination(dest))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::{BinOp, OpType, UnOp};
+
+    #[test]
+    fn validate_program() {
+        let program = [
+            Op::Ifl(BinOp::new(Operand::Loc(0), Operand::Val(3)), OpType::U32),
+            Op::Go(Operand::Val(3)),
+            Op::App(Operand::Val(0)),
+            Op::Inc(UnOp::new(Operand::Loc(0)), OpType::U32),
+        ];
+        assert_eq!(validate(&[Function::new(4, &program)]), Ok(()));
+
+        let cases = [
+            (
+                Op::Go(Operand::Val(4)),
+                ValidationErrorKind::JumpOutOfRange(4),
+            ),
+            (
+                Op::Gor(Operand::Val(-3 as IWord as UWord)),
+                ValidationErrorKind::JumpOutOfRange(UWord::MAX),
+            ),
+            (
+                Op::Skp(Operand::Val(0)),
+                ValidationErrorKind::JumpOutOfRange(3),
+            ),
+            (
+                Op::Skp(Operand::Val(UWord::MAX)),
+                ValidationErrorKind::JumpOutOfRange(2),
+            ),
+            (
+                Op::App(Operand::Val(1)),
+                ValidationErrorKind::UnknownFunction(1),
+            ),
+            (
+                Op::Set(BinOp::new(Operand::Val(0), Operand::Val(1)), OpType::U8),
+                ValidationErrorKind::IncorrectDestination(Operand::Val(0)),
+            ),
+            (
+                Op::Adc(BinOp::new(Operand::Val(1), Operand::Loc(0)), OpType::U32),
+                ValidationErrorKind::IncorrectDestination(Operand::Val(1)),
+            ),
+            (
+                Op::Sbb(BinOp::new(Operand::Emp, Operand::Loc(0)), OpType::U32),
+                ValidationErrorKind::IncorrectDestination(Operand::Emp),
+            ),
+            (
+                Op::Rnd(Operand::Val(0), OpType::U32),
+                ValidationErrorKind::IncorrectDestination(Operand::Val(0)),
+            ),
+            (
+                Op::Inc(UnOp::new(Operand::Emp), OpType::U8),
+                ValidationErrorKind::IncorrectDestination(Operand::Emp),
+            ),
+            (
+                Op::Inb(Operand::Ref(0), Operand::Val(4)),
+                ValidationErrorKind::IncorrectDestination(Operand::Val(4)),
+            ),
+            (
+                Op::Cas(
+                    Operand::Loc(0),
+                    Operand::Val(5),
+                    Operand::Val(1),
+                    OpType::U32,
+                ),
+                ValidationErrorKind::IncorrectDestination(Operand::Val(5)),
+            ),
+            (
+                Op::Iff(UnOp::new(Operand::Loc(0)), OpType::U8),
+                ValidationErrorKind::UnterminatedCondition,
+            ),
+        ];
+
+        for &(op, kind) in cases.iter() {
+            let program = [Op::Nop, Op::Nop, op];
+            let error = ValidationError {
+                function: 0,
+                op: 2,
+                kind,
+            };
+
+            assert_eq!(validate(&[Function::new(4, &program)]), Err(error));
+        }
+    }
+}
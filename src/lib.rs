@@ -1,8 +1,29 @@
+//! Without the default `std` feature the crate is `no_std` and needs only
+//! `alloc`. The executor, the memory and the ops are available, while the
+//! assembler, the decoder, the encoder and the NIL parser require `std`.
+//! The float functions of the `no_std` build are computed by `libm`, which
+//! is enabled by the `libm` feature.
+
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+#[cfg(not(any(feature = "std", feature = "libm")))]
+compile_error!("the `libm` feature is required without the `std` feature");
+
+extern crate alloc;
+
+#[cfg(feature = "std")]
 #[macro_use]
 extern crate pest_derive;
 
+#[cfg(feature = "std")]
+pub mod assembler;
 pub mod common;
+#[cfg(feature = "std")]
 pub mod decoder;
+#[cfg(feature = "std")]
 pub mod encoder;
 pub mod executor;
+#[cfg(feature = "std")]
 pub mod nil;
+#[cfg(feature = "testing")]
+pub mod testing;
@@ -5,7 +5,11 @@ use super::{view::View, LayoutBuilder, WORD_SIZE};
 #[derive(Debug)]
 pub struct Layout<'n, 't> {
     pub(super) fields: View<Field<'n, 't>>,
+    // Owns the types which are referenced by the fields.
+    #[allow(dead_code)]
     pub(super) types: View<Ty<'t>>,
+    // Fields are placed at multiples of their alignment.
+    pub(super) aligned: bool,
 }
 
 impl<'n, 't> Layout<'n, 't> {
@@ -14,10 +18,126 @@ impl<'n, 't> Layout<'n, 't> {
     }
 
     pub fn size(&self, layouts: &[Layout]) -> UWord {
-        self.fields.iter().map(|f| f.ty.size(layouts)).sum()
+        let end = bytes(self.walk(None, layouts).unwrap());
+
+        if self.aligned {
+            align_up(end, self.align(layouts))
+        } else {
+            end
+        }
+    }
+
+    /// Returns the alignment of the layout. Packed layouts are aligned to 1.
+    pub fn align(&self, layouts: &[Layout]) -> UWord {
+        if self.aligned {
+            self.fields
+                .iter()
+                .map(|f| f.ty.align(layouts))
+                .max()
+                .unwrap_or(1)
+        } else {
+            1
+        }
+    }
+
+    fn place(&self, offset: UWord, ty: &Ty, layouts: &[Layout]) -> UWord {
+        if self.aligned {
+            align_up(offset, ty.align(layouts))
+        } else {
+            offset
+        }
+    }
+
+    /// Returns the bit position of the field with the given name
+    /// or the end of the layout if the name is `None`.
+    ///
+    /// Consecutive bitfields are packed in the declaration order
+    /// starting from the least significant bit of a byte.
+    /// Other fields start at the next whole byte.
+    fn walk(&self, field_name: Option<&str>, layouts: &[Layout]) -> Option<UWord> {
+        let mut pos = 0;
+
+        for field in self.fields.iter() {
+            if let Ty::Bits(n) = field.ty {
+                if Some(field.name) == field_name {
+                    return Some(pos);
+                }
+
+                pos += n as UWord;
+                continue;
+            }
+
+            pos = self.place(bytes(pos), &field.ty, layouts) * 8;
+
+            if Some(field.name) == field_name {
+                return Some(pos);
+            }
+
+            pos += field.ty.size(layouts) * 8;
+        }
+
+        match field_name {
+            Some(_) => None,
+            None => Some(pos),
+        }
+    }
+
+    /// Returns the byte offset of the field with the given name.
+    /// For a bitfield it is the offset of the byte containing its first bit.
+    pub fn offset_of(&self, field_name: &str, layouts: &[Layout]) -> Option<UWord> {
+        self.walk(Some(field_name), layouts).map(|pos| pos / 8)
+    }
+
+    /// Returns the byte offset and the index of the first bit of the field.
+    pub fn bit_offset_of(&self, field_name: &str, layouts: &[Layout]) -> Option<(UWord, u8)> {
+        self.walk(Some(field_name), layouts)
+            .map(|pos| (pos / 8, (pos % 8) as u8))
+    }
+
+    /// Resolves a path like `a.b[3][1]` to the byte offset and the type of the value.
+    pub fn resolve_path(&self, path: &str, layouts: &[Layout<'_, 't>]) -> Option<(UWord, Ty<'t>)> {
+        let mut offset = 0;
+        let mut layout = self;
+        let mut ty = None;
+
+        for segment in path.split('.') {
+            if let Some(Ty::Layout(idx)) = ty {
+                layout = layouts.get(idx)?;
+            } else if ty.is_some() {
+                return None;
+            }
+
+            let (name, mut indices) = match segment.find('[') {
+                Some(pos) => segment.split_at(pos),
+                None => (segment, ""),
+            };
+
+            let field = layout.fields.iter().find(|f| f.name == name)?;
+            offset += layout.offset_of(name, layouts)?;
+            let mut field_ty = field.ty;
+
+            while !indices.is_empty() {
+                let end = indices.find(']')?;
+                let idx: UWord = indices.strip_prefix('[')?[..end - 1].parse().ok()?;
+                indices = &indices[end + 1..];
+
+                match field_ty {
+                    Ty::Array(&elem, len) if idx < len => {
+                        offset += elem.size(layouts) * idx;
+                        field_ty = elem;
+                    }
+                    _ => return None,
+                }
+            }
+
+            ty = Some(field_ty);
+        }
+
+        ty.map(|ty| (offset, ty))
     }
 }
 
+#[allow(dead_code)]
 #[derive(Copy, Clone, Debug)]
 pub struct Field<'n, 't> {
     pub(super) name: &'n str,
@@ -32,9 +152,12 @@ pub enum Ty<'t> {
     Array(&'t Ty<'t>, UWord),
     Indirect(&'t Ty<'t>),
     Function,
+    /// Bitfield of N bits.
+    Bits(u8),
 }
 
 impl<'t> Ty<'t> {
+    #[allow(clippy::len_without_is_empty)]
     pub fn len(&self) -> UWord {
         match self {
             Ty::Array(_, len) => *len,
@@ -49,10 +172,30 @@ impl<'t> Ty<'t> {
             Ty::Array(&ty, len) => ty.size(layouts) * len,
             Ty::Indirect(_) => WORD_SIZE,
             Ty::Function => WORD_SIZE,
+            Ty::Bits(n) => bytes(*n as UWord),
+        }
+    }
+
+    pub fn align(&self, layouts: &[Layout]) -> UWord {
+        match self {
+            Ty::OpType(op) => op.size(),
+            Ty::Layout(lay_idx) => layouts[*lay_idx].align(layouts),
+            Ty::Array(&ty, _) => ty.align(layouts),
+            Ty::Indirect(_) => WORD_SIZE,
+            Ty::Function => WORD_SIZE,
+            Ty::Bits(_) => 1,
         }
     }
 }
 
+fn bytes(bits: UWord) -> UWord {
+    bits.div_ceil(8)
+}
+
+fn align_up(offset: UWord, align: UWord) -> UWord {
+    offset.div_ceil(align) * align
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -98,4 +241,159 @@ mod tests {
                 + 1 + 2 // other
         );
     }
+
+    #[test]
+    fn layout_offset_of() {
+        let other = Layout::builder()
+            .new_op_type("a", OpType::U8)
+            .new_op_type("b", OpType::U16)
+            .build()
+            .unwrap();
+
+        let lay = Layout::builder()
+            .new_fn("f")
+            .new_op_type("x", OpType::U32)
+            .add_indirect()
+            .new_op_type("y", OpType::I32)
+            .add_array(12)
+            .add_array(4)
+            .new_layout("self", 0)
+            .add_indirect()
+            .new_layout("other", 0)
+            .build()
+            .unwrap();
+
+        let layouts = [other];
+
+        assert_eq!(layouts[0].offset_of("a", &layouts), Some(0));
+        assert_eq!(layouts[0].offset_of("b", &layouts), Some(1));
+
+        assert_eq!(lay.offset_of("f", &layouts), Some(0));
+        assert_eq!(lay.offset_of("x", &layouts), Some(WORD_SIZE));
+        assert_eq!(lay.offset_of("y", &layouts), Some(WORD_SIZE * 2));
+        assert_eq!(
+            lay.offset_of("self", &layouts),
+            Some(WORD_SIZE * 2 + 4 * 12 * 4)
+        );
+        assert_eq!(
+            lay.offset_of("other", &layouts),
+            Some(WORD_SIZE * 3 + 4 * 12 * 4)
+        );
+        assert_eq!(lay.offset_of("z", &layouts), None);
+    }
+
+    #[test]
+    fn layout_resolve_path() {
+        let other = Layout::builder()
+            .new_op_type("a", OpType::U8)
+            .new_op_type("b", OpType::U16)
+            .add_array(3)
+            .build()
+            .unwrap();
+
+        let lay = Layout::builder()
+            .new_op_type("x", OpType::U32)
+            .new_op_type("y", OpType::I32)
+            .add_array(12)
+            .add_array(4)
+            .new_layout("self", 0)
+            .add_indirect()
+            .new_layout("other", 0)
+            .add_array(2)
+            .build()
+            .unwrap();
+
+        let layouts = [other];
+        let other_offset = 4 + 4 * 12 * 4 + WORD_SIZE;
+
+        assert!(matches!(
+            lay.resolve_path("x", &layouts),
+            Some((0, Ty::OpType(OpType::U32)))
+        ));
+        assert!(matches!(
+            lay.resolve_path("y[3][1]", &layouts),
+            Some((offset, Ty::OpType(OpType::I32))) if offset == 4 + 3 * 48 + 4
+        ));
+        assert!(matches!(
+            lay.resolve_path("y[3]", &layouts),
+            Some((offset, Ty::Array(Ty::OpType(OpType::I32), 12))) if offset == 4 + 3 * 48
+        ));
+        assert!(matches!(
+            lay.resolve_path("other[1].b[2]", &layouts),
+            Some((offset, Ty::OpType(OpType::U16))) if offset == other_offset + 7 + 1 + 2 * 2
+        ));
+        assert!(matches!(
+            lay.resolve_path("other[0].a", &layouts),
+            Some((offset, Ty::OpType(OpType::U8))) if offset == other_offset
+        ));
+
+        assert!(lay.resolve_path("y[4]", &layouts).is_none());
+        assert!(lay.resolve_path("y[1][12]", &layouts).is_none());
+        assert!(lay.resolve_path("x[0]", &layouts).is_none());
+        assert!(lay.resolve_path("z", &layouts).is_none());
+        assert!(lay.resolve_path("x.a", &layouts).is_none());
+        assert!(lay.resolve_path("self.a", &layouts).is_none());
+        assert!(lay.resolve_path("other[0].c", &layouts).is_none());
+        assert!(lay.resolve_path("y[a]", &layouts).is_none());
+    }
+
+    #[test]
+    fn layout_aligned() {
+        let builder = || {
+            Layout::builder()
+                .new_op_type("a", OpType::U8)
+                .new_op_type("b", OpType::U32)
+                .new_op_type("c", OpType::U8)
+        };
+
+        let packed = builder().build().unwrap();
+        let aligned = builder().build_aligned().unwrap();
+
+        assert_eq!(packed.offset_of("a", &[]), Some(0));
+        assert_eq!(packed.offset_of("b", &[]), Some(1));
+        assert_eq!(packed.offset_of("c", &[]), Some(5));
+        assert_eq!(packed.size(&[]), 6);
+        assert_eq!(packed.align(&[]), 1);
+
+        assert_eq!(aligned.offset_of("a", &[]), Some(0));
+        assert_eq!(aligned.offset_of("b", &[]), Some(4));
+        assert_eq!(aligned.offset_of("c", &[]), Some(8));
+        assert_eq!(aligned.size(&[]), 12);
+        assert_eq!(aligned.align(&[]), 4);
+
+        let outer = Layout::builder()
+            .new_op_type("x", OpType::U8)
+            .new_layout("y", 0)
+            .add_array(2)
+            .build_aligned()
+            .unwrap();
+
+        let layouts = [aligned];
+        assert_eq!(outer.offset_of("y", &layouts), Some(4));
+        assert_eq!(outer.size(&layouts), 28);
+        assert!(matches!(
+            outer.resolve_path("y[1].c", &layouts),
+            Some((24, Ty::OpType(OpType::U8)))
+        ));
+    }
+
+    #[test]
+    fn layout_bits() {
+        let lay = Layout::builder()
+            .new_bits("a", 1)
+            .new_bits("b", 3)
+            .new_bits("c", 6)
+            .new_op_type("x", OpType::U8)
+            .new_bits("d", 1)
+            .build()
+            .unwrap();
+
+        assert_eq!(lay.bit_offset_of("a", &[]), Some((0, 0)));
+        assert_eq!(lay.bit_offset_of("b", &[]), Some((0, 1)));
+        assert_eq!(lay.bit_offset_of("c", &[]), Some((0, 4)));
+        assert_eq!(lay.bit_offset_of("x", &[]), Some((2, 0)));
+        assert_eq!(lay.bit_offset_of("d", &[]), Some((3, 0)));
+        assert_eq!(lay.offset_of("c", &[]), Some(0));
+        assert_eq!(lay.size(&[]), 4);
+    }
 }
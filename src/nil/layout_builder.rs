@@ -7,6 +7,7 @@ pub enum LayoutError {
     UnexpectedIndirection,
 }
 
+#[derive(Default)]
 pub struct LayoutBuilder<'n> {
     blocks: Vec<Block<'n>>,
 }
@@ -32,6 +33,15 @@ impl<'n> LayoutBuilder<'n> {
         self
     }
 
+    /// Adds a bitfield of `n` bits. Consecutive bitfields are packed into bytes.
+    pub fn new_bits(mut self, name: &'n str, n: u8) -> Self {
+        self.blocks.push(Block::New {
+            ty: BlockType::Bits(n),
+            name,
+        });
+        self
+    }
+
     pub fn new_fn(mut self, name: &'n str) -> Self {
         self.blocks.push(Block::New {
             ty: BlockType::Function,
@@ -51,6 +61,15 @@ impl<'n> LayoutBuilder<'n> {
     }
 
     pub fn build<'t>(self) -> Result<Layout<'n, 't>, LayoutError> {
+        self.build_with(false)
+    }
+
+    /// Builds the layout which places each field at a multiple of its alignment.
+    pub fn build_aligned<'t>(self) -> Result<Layout<'n, 't>, LayoutError> {
+        self.build_with(true)
+    }
+
+    fn build_with<'t>(self, aligned: bool) -> Result<Layout<'n, 't>, LayoutError> {
         let (n_fields, n_types) =
             self.blocks
                 .iter()
@@ -99,6 +118,7 @@ impl<'n> LayoutBuilder<'n> {
         Ok(Layout {
             fields: fields.into(),
             types: types.into(),
+            aligned,
         })
     }
 }
@@ -113,6 +133,7 @@ enum BlockType {
     OpType(OpType),
     Layout(usize),
     Function,
+    Bits(u8),
 }
 
 impl BlockType {
@@ -121,6 +142,7 @@ impl BlockType {
             BlockType::OpType(op) => Ty::OpType(op),
             BlockType::Layout(idx) => Ty::Layout(idx),
             BlockType::Function => Ty::Function,
+            BlockType::Bits(n) => Ty::Bits(n),
         }
     }
 }
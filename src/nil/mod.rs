@@ -1,5 +1,6 @@
 mod layout;
 mod layout_builder;
+#[allow(dead_code)]
 mod parser;
 mod view;
 
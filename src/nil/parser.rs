@@ -1,7 +1,6 @@
 use pest::{
     error::Error,
     iterators::{Pair, Pairs},
-    Parser,
 };
 
 use crate::common::UWord;
@@ -9,7 +8,7 @@ use crate::common::UWord;
 // TODO: Refactor this mod
 
 fn parse_int(s: &str) -> UWord {
-    fn replace_underscore(s: &str) -> std::borrow::Cow<str> {
+    fn replace_underscore(s: &str) -> std::borrow::Cow<'_, str> {
         if s.contains("_") {
             let s: String = s.chars().filter(|&c| c != '_').collect();
 
@@ -32,7 +31,7 @@ fn parse_int(s: &str) -> UWord {
         (s.into(), 10)
     };
 
-    UWord::from_str_radix(&*s, rad).unwrap()
+    UWord::from_str_radix(&s, rad).unwrap()
 }
 
 #[derive(Copy, Clone, Debug)]
@@ -320,6 +319,7 @@ fn parse_const_expr<'r>(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use pest::Parser;
 
     #[test]
     fn parse() {
@@ -333,7 +333,7 @@ mod tests {
         let nil = NilParser::parse(Rule::nil, code).unwrap().next().unwrap();
 
         let mut exprs = Vec::new();
-        let _ = parse_const_expr(nil, &mut exprs).unwrap();
+        parse_const_expr(nil, &mut exprs).unwrap();
 
         let res = exec_const_expr(exprs.as_slice()).unwrap();
         assert_eq!(res, 12);
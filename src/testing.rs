@@ -0,0 +1,75 @@
+//! Helpers for the tests of programs.
+
+use crate::{
+    common::UWord,
+    executor::{ExecutionError, Executor, Function, RunOutcome},
+};
+use std::collections::vec_deque::VecDeque;
+
+/// Input file descriptor of `run_program`.
+pub const INPUT_FD: UWord = 0;
+
+/// Output file descriptor of `run_program`.
+pub const OUTPUT_FD: UWord = 1;
+
+/// Runs the first function until the end and returns the exit code with the output.
+///
+/// The `input` is opened as `INPUT_FD`, which is the current file at the start,
+/// and the output is collected from `OUTPUT_FD`. Sleeps, yields, breakpoints
+/// and watchpoints are skipped.
+pub fn run_program(
+    functions: &[Function],
+    input: &[u8],
+) -> Result<(UWord, Vec<u8>), ExecutionError> {
+    let mut exe = Executor::new(functions);
+    let input: VecDeque<u8> = input.iter().copied().collect();
+
+    let files = exe.files_mut();
+    files.open(input).map_err(|(e, _)| e)?;
+    files.open(Vec::<u8>::new()).map_err(|(e, _)| e)?;
+    files.set_current(INPUT_FD)?;
+
+    exe.call(0, 0)?;
+
+    let code = loop {
+        match exe.run_until_break()? {
+            RunOutcome::End(code) => break code,
+            RunOutcome::Sleep(_)
+            | RunOutcome::Yield(_)
+            | RunOutcome::Breakpoint { .. }
+            | RunOutcome::Watchpoint { .. } => (),
+        }
+    };
+
+    let file = exe.files_mut().close(OUTPUT_FD)?;
+    let output = file
+        .as_any()
+        .downcast_ref::<Vec<u8>>()
+        .cloned()
+        .unwrap_or_default();
+
+    Ok((code, output))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::*;
+
+    #[test]
+    fn testing_run_program() {
+        let program = [
+            Op::In(BinOp::new(Operand::Loc(0), Operand::Emp)),
+            Op::In(BinOp::new(Operand::Loc(1), Operand::Loc(2))),
+            Op::Mul(BinOp::new(Operand::Loc(0), Operand::Loc(1)), OpType::U8),
+            Op::Set(BinOp::new(Operand::Loc(4), Operand::Loc(0)), OpType::U8),
+            Op::Sfd(Operand::Val(OUTPUT_FD)),
+            Op::Out(UnOp::new(Operand::Loc(0))),
+            Op::Out(UnOp::new(Operand::Loc(2))),
+            Op::End(Operand::Loc(4)),
+        ];
+        let functions = [Function::new(8, &program)];
+
+        assert_eq!(run_program(&functions, &[3, 4]), Ok((12, vec![12, 1])));
+    }
+}